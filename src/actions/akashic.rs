@@ -0,0 +1,100 @@
+use itertools::Either;
+
+use crate::hex_pattern::HexPattern;
+use crate::simulator::{Action, ActionError, Iota, OutputType, Signature, StackHolder, StackState};
+
+use super::{invalid_type, ANY, PATTERN, VEC};
+
+/// The arguments of an akashic action: the position of the library's record, the key, and any iotas above them.
+struct AkashicArgs {
+	/// the stack below the arguments.
+	stack: Vec<Iota>,
+	position: Either<(f64, f64, f64), bool>,
+	key: HexPattern,
+	extra: Vec<Iota>
+}
+
+impl AkashicArgs {
+	/// Splits the arguments off the stack, with `extra` iotas of any type above the position and key.
+	fn split(stack_state: &StackState, extra: usize) -> Result<AkashicArgs, ActionError> {
+		let mut stack = stack_state.stack().to_vec();
+		let len = 2 + extra;
+
+		if stack.len() < len {
+			return Err(ActionError::StackTooSmall { expected: len, found: stack.len() })
+		}
+
+		let args = stack.split_off(stack.len() - len);
+		match (args[0].value(), args[1].value()) {
+			(Iota::Vec(position), Iota::Pattern(key)) => Ok(AkashicArgs {
+				stack, position: *position, key: key.clone(), extra: args[2..].iter().map(|iota| iota.value().clone()).collect()
+			}),
+			_ => Err(invalid_type(&args, &[&[VEC, PATTERN][..], &vec![ANY; extra]].concat())),
+		}
+	}
+}
+
+/// The branches an akashic action can take, given whether the library's record is at the position it was given: the
+/// state it results in, a mishap, or both if that isn't known.
+fn at_record(is_record_at: Option<bool>, argument: usize, result: StackState) -> StackHolder {
+	match is_record_at {
+		Some(true) => StackHolder::single_state(result),
+		Some(false) => StackHolder::single(Err(ActionError::NoAkashicRecord { argument })),
+		None => StackHolder::new(vec![Ok(result), Err(ActionError::NoAkashicRecord { argument })]),
+	}
+}
+
+/// Akashic Read; replaces the position of an Akashic Library's record and a pattern with the iota the library stores
+/// under the pattern, or Null if it stores nothing there. Without a library in the scene, the iota isn't known.
+pub struct AkashicRead;
+
+impl Action for AkashicRead {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let AkashicArgs { mut stack, position, key, .. } = match AkashicArgs::split(stack_state, 0) {
+			Ok(args) => args,
+			Err(action_error) => return StackHolder::single(Err(action_error)),
+		};
+
+		match stack_state.scene().and_then(|scene| scene.library.as_ref()) {
+			Some(library) => {
+				stack.push(library.get(&key).map_or(Iota::Widget, |entry| entry.value.clone()));
+				at_record(library.is_record_at(&position), 1, stack_state.with_stack(stack))
+			},
+			None => {
+				stack.push(Iota::Unknown);
+				StackHolder::single_state(stack_state.with_stack(stack))
+			},
+		}
+	}
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![VEC, PATTERN], outputs: vec![OutputType::Types(ANY)] }) }
+}
+
+/// Akashic Write; stores an iota in an Akashic Library under a pattern, given the position of the library's record,
+/// which must be in the caster's ambit. The write only changes the library of the branch it is cast on.
+pub struct AkashicWrite;
+
+impl Action for AkashicWrite {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let AkashicArgs { stack, position, key, mut extra } = match AkashicArgs::split(stack_state, 1) {
+			Ok(args) => args,
+			Err(action_error) => return StackHolder::single(Err(action_error)),
+		};
+
+		let mut state = stack_state.with_stack(stack);
+		match state.scene_mut().and_then(|scene| scene.library.as_mut()) {
+			Some(library) => {
+				let is_record_at = library.is_record_at(&position);
+				if is_record_at != Some(false) {
+					library.write(key, extra.remove(0));
+				}
+				at_record(is_record_at, 2, state)
+			},
+			None => StackHolder::single_state(state),
+		}
+	}
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![VEC, PATTERN, ANY], outputs: vec![] }) }
+
+	fn targets(&self) -> Vec<usize> { vec![2] }
+}
@@ -0,0 +1,170 @@
+use itertools::Either::{Left, Right};
+
+use crate::interval::Interval;
+use crate::simulator::{Action, ActionError, EntityType, Iota, IotaEntity, IotaList, IotaType, OutputType, Signature, StackHolder, StackState};
+use crate::world::{Scene, SceneEntity};
+
+use super::{invalid_type, DOUBLE, ENTITY, LIST, VEC};
+
+/// Replaces the entity on top of the stack with one of its properties, read from the scene by `read`. The scene entity
+/// is None if the entity isn't in the scene, in which case `read` returns what is known about the property anyway.
+fn read_entity(stack_state: &StackState, read: impl Fn(&IotaEntity, Option<&SceneEntity>) -> Iota) -> StackHolder {
+	let mut stack = stack_state.stack().to_vec();
+
+	let entity = match stack.pop() {
+		Some(iota) => match iota.value() {
+			Iota::Entity(entity) => entity.clone(),
+			_ => return StackHolder::single(Err(invalid_type(&[iota], &[ENTITY]))),
+		},
+		None => return StackHolder::single(Err(ActionError::StackTooSmall { expected: 1, found: 0 })),
+	};
+
+	stack.push(read(&entity, stack_state.scene().and_then(|scene| scene.entity(&entity))));
+	StackHolder::single_state(stack_state.with_stack(stack))
+}
+
+fn entity_signature(output: &'static [IotaType]) -> Option<Signature> {
+	Some(Signature { inputs: vec![ENTITY], outputs: vec![OutputType::Types(output)] })
+}
+
+/// Compass' Purification; the position of an entity's eyes. If it isn't known, it is at least in range if the entity is.
+pub struct EntityPos;
+
+impl Action for EntityPos {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		read_entity(stack_state, |entity, scene_entity| match scene_entity.and_then(|scene_entity| scene_entity.position) {
+			Some(position) => position.into(),
+			None => Iota::Vec(Right(entity.is_guaranteed_in_range())),
+		})
+	}
+
+	fn signature(&self) -> Option<Signature> { entity_signature(VEC) }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+}
+
+/// Alidade's Purification; the unit vector an entity is looking along.
+pub struct EntityLook;
+
+impl Action for EntityLook {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		read_entity(stack_state, |_, scene_entity| match scene_entity.and_then(|scene_entity| scene_entity.look) {
+			Some(look) => look.into(),
+			None => Iota::Vec(Right(false)),
+		})
+	}
+
+	fn signature(&self) -> Option<Signature> { entity_signature(VEC) }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+}
+
+/// Stadiometer's Purification; the height of an entity. If it isn't known, it is at least not negative.
+pub struct EntityHeight;
+
+impl Action for EntityHeight {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		read_entity(stack_state, |_, scene_entity| match scene_entity.and_then(|scene_entity| scene_entity.height) {
+			Some(height) => Iota::Double(Left(height)),
+			None => Interval::new(0.0, f64::INFINITY).into(),
+		})
+	}
+
+	fn signature(&self) -> Option<Signature> { entity_signature(DOUBLE) }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+}
+
+/// Pace Purification; the velocity of an entity.
+pub struct EntityVelocity;
+
+impl Action for EntityVelocity {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		read_entity(stack_state, |_, scene_entity| match scene_entity.and_then(|scene_entity| scene_entity.velocity) {
+			Some(velocity) => velocity.into(),
+			None => Iota::Vec(Right(false)),
+		})
+	}
+
+	fn signature(&self) -> Option<Signature> { entity_signature(VEC) }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+}
+
+/// Which entities a Zone Distillation finds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZoneFilter {
+	Any,
+	Is(EntityType),
+	IsNot(EntityType)
+}
+
+impl ZoneFilter {
+	/// Returns whether the entity passes the filter, or None if that depends on types of the entity that aren't known.
+	fn matches(&self, entity: &IotaEntity) -> Option<bool> {
+		match self {
+			ZoneFilter::Any => Some(true),
+			ZoneFilter::Is(entity_type) => entity.is_type(*entity_type),
+			ZoneFilter::IsNot(entity_type) => entity.is_type(*entity_type).map(|is| !is),
+		}
+	}
+
+	/// What is known about every entity the filter finds in a world that isn't known.
+	fn element(&self) -> IotaEntity {
+		match self {
+			ZoneFilter::Any => IotaEntity::new("entity").set_guaranteed_in_range(false).into(),
+			ZoneFilter::Is(entity_type) => IotaEntity::of_type(entity_type.name(), *entity_type, true),
+			ZoneFilter::IsNot(entity_type) => IotaEntity::of_type(&format!("non-{}", entity_type.name()), *entity_type, false),
+		}
+	}
+}
+
+/// Zone Distillations; replace a position and a radius with a list of the entities within the radius of the position that
+/// pass the filter and are in the caster's ambit. With a scene, the list is known if it is known which entities are
+/// within the radius and which pass the filter; otherwise it is a list of unknown length whose elements pass the filter.
+pub struct Zone(pub ZoneFilter);
+
+impl Zone {
+	/// The entities in the scene found by the zone, leaving out those out of ambit, or None if that can't be known.
+	fn find(&self, scene: &Scene, position: (f64, f64, f64), radius: f64) -> Option<Vec<Iota>> {
+		let mut found = vec![];
+
+		for scene_entity in &scene.entities {
+			let (x, y, z) = scene_entity.position?;
+			let distance_squared = (x - position.0).powi(2) + (y - position.1).powi(2) + (z - position.2).powi(2);
+
+			let iota = scene_entity.iota();
+			if distance_squared <= radius * radius && scene.in_ambit(&iota)? && self.0.matches(&scene_entity.entity())? {
+				found.push(iota);
+			}
+		}
+
+		Some(found)
+	}
+}
+
+impl Action for Zone {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+
+		if stack.len() < 2 {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: 2, found: stack.len() }))
+		}
+
+		let args = stack.split_off(stack.len() - 2);
+		let found = match (args[0].value(), args[1].value()) {
+			(Iota::Vec(Left(position)), Iota::Double(Left(radius))) => stack_state.scene().and_then(|scene| self.find(scene, *position, *radius)),
+			(Iota::Vec(_), Iota::Double(_)) => None,
+			_ => return StackHolder::single(Err(invalid_type(&args, &[VEC, DOUBLE]))),
+		};
+
+		stack.push(found.map_or_else(|| IotaList::unknown(None, Some(Iota::Entity(self.0.element()))).into(), Iota::from));
+		StackHolder::single_state(stack_state.with_stack(stack))
+	}
+
+	fn signature(&self) -> Option<Signature> {
+		Some(Signature { inputs: vec![VEC, DOUBLE], outputs: vec![OutputType::Types(LIST)] })
+	}
+
+	fn targets(&self) -> Vec<usize> { vec![1] }
+}
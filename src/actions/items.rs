@@ -0,0 +1,72 @@
+use crate::items::Item;
+use crate::simulator::{Action, ActionError, Iota, OutputType, Signature, StackHolder, StackState};
+
+use super::{ANY, BOOL};
+
+/// The item in the caster's other hand, or None if it (or the caster) isn't known.
+fn held_item(stack_state: &StackState) -> Option<&Item> {
+	stack_state.scene().and_then(|scene| scene.caster.as_ref()).and_then(|caster| caster.held_item.as_ref())
+}
+
+/// Scribe's Reflection; pushes the iota in the focus or on the selected spellbook page in the caster's other hand, or
+/// Null if nothing is written there. If the item isn't known, neither is the iota.
+pub struct ReadHeldItem;
+
+impl Action for ReadHeldItem {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let iota = match held_item(stack_state).map(Item::read) {
+			Some(Ok(iota)) => iota,
+			Some(Err(_)) => return StackHolder::single(Err(ActionError::BadHeldItem { writing: false })),
+			None => Iota::Unknown,
+		};
+
+		let mut stack = stack_state.stack().to_vec();
+		stack.push(iota);
+		StackHolder::single_state(stack_state.with_stack(stack))
+	}
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![], outputs: vec![OutputType::Types(ANY)] }) }
+}
+
+/// Scribe's Gambit; writes the top iota to the focus or the selected spellbook page in the caster's other hand. The
+/// write only changes the item of the branch it is cast on.
+pub struct WriteHeldItem;
+
+impl Action for WriteHeldItem {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+		let iota = match stack.pop() {
+			Some(iota) => iota.value().clone(),
+			None => return StackHolder::single(Err(ActionError::StackTooSmall { expected: 1, found: 0 })),
+		};
+
+		let mut state = stack_state.with_stack(stack);
+		let item = state.scene_mut().and_then(|scene| scene.caster.as_mut()).and_then(|caster| caster.held_item.as_mut());
+		match item.map(|item| item.write(iota)) {
+			Some(Err(_)) => StackHolder::single(Err(ActionError::BadHeldItem { writing: true })),
+			_ => StackHolder::single_state(state),
+		}
+	}
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![ANY], outputs: vec![] }) }
+}
+
+/// Auditor's Reflection (`writing` false) and Assessor's Reflection (`writing` true); push whether Scribe's Reflection
+/// could read the item in the caster's other hand, or Scribe's Gambit write to it.
+pub struct HeldItemAccess {
+	pub writing: bool
+}
+
+impl Action for HeldItemAccess {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let access = held_item(stack_state).map(|item| if self.writing { item.writable() } else { item.readable() });
+
+		let mut stack = stack_state.stack().to_vec();
+		stack.push(Iota::Bool(access));
+		StackHolder::single_state(stack_state.with_stack(stack))
+	}
+
+	fn name(&self) -> String { if self.writing { "held_item_writable" } else { "held_item_readable" }.to_string() }
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![], outputs: vec![OutputType::Types(BOOL)] }) }
+}
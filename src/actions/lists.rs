@@ -0,0 +1,332 @@
+use itertools::Either::{Left, Right};
+
+use crate::interval::Interval;
+use crate::numbers::round;
+use crate::simulator::{Iota, IotaType, IotaList, ConstLenAction, Action, ActionError, OutputType, StackState, StackHolder, MAX_STACK_LEN};
+use crate::symbolic::Op;
+
+use super::{int_between, invalid_type, IntArg, ANY, DOUBLE, LIST};
+
+/// The largest index an action could be asked for in a list whose length isn't known.
+fn max_index(list: &IotaList) -> i64 { list.len().map_or(i64::MAX, |len| len as i64) }
+
+/// Integration Distillation; appends the top iota to the end of the list below it.
+pub struct Append;
+
+impl ConstLenAction for Append {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST, ANY] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => {
+					let mut list = list.clone();
+					list.push(iotas[1].clone());
+					Ok(vec![list.into()])
+				},
+				Iota::List(list @ IotaList(Right(length), _)) => {
+					let element = list.1.as_ref().and_then(|element| element.join(&iotas[1]));
+					Ok(vec![IotaList::unknown(length.map(|length| length + 1), element).into()])
+				},
+				_ => Err(invalid_type(iotas, &[LIST, ANY]))
+			}
+		]
+	}
+}
+
+/// Derivation Decomposition; removes the last iota from a list and pushes it, pushing Null if the list is empty.
+pub struct Pop;
+
+impl ConstLenAction for Pop {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST), OutputType::Types(ANY)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => {
+					let mut list = list.clone();
+					let last = list.pop().unwrap_or(Iota::Widget);
+					Ok(vec![list.into(), last])
+				},
+				Iota::List(IotaList(Right(Some(0)), _)) => Ok(vec![iotas[0].clone(), Iota::Widget]),
+				Iota::List(list @ IotaList(Right(length), _)) => Ok(vec![list.with_len(length.map(|length| length - 1)).into(), list.element()]),
+				_ => Err(invalid_type(iotas, &[LIST]))
+			}
+		]
+	}
+}
+
+/// Selection Distillation; replaces a list and an index with the iota at that index, or Null if the index is out of range.
+/// If the index isn't known, the result is unknown, and is possibly Null if the index might be out of range.
+pub struct Index;
+
+impl ConstLenAction for Index {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(ANY)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Index) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match (&iotas[0], &iotas[1]) {
+			(Iota::List(IotaList(Left(list), _)), Iota::Double(Left(index))) => {
+				let index = round(*index);
+				if index < 0.0 {
+					vec![Ok(vec![Iota::Widget])]
+				} else {
+					vec![Ok(vec![list.get(index as usize).cloned().unwrap_or(Iota::Widget)])]
+				}
+			},
+			(Iota::List(list), Iota::Double(index)) => {
+				let index = Interval::from_double(index);
+				let (lower, upper) = (round(index.lower), round(index.upper));
+				let in_range = Interval::new(0.0, list.len().map_or(f64::INFINITY, |len| len as f64 - 1.0));
+
+				let element = list.element();
+
+				if upper < in_range.lower || lower > in_range.upper {
+					vec![Ok(vec![Iota::Widget])]
+				} else if lower >= in_range.lower && upper <= in_range.upper {
+					vec![Ok(vec![element])]
+				} else {
+					vec![Ok(vec![element]), Ok(vec![Iota::Widget])]
+				}
+			},
+			_ => vec![Err(invalid_type(iotas, &[LIST, DOUBLE]))]
+		}
+	}
+}
+
+/// Selection Exaltation; replaces a list and two indices with the sublist between those indices.
+pub struct Slice;
+
+impl ConstLenAction for Slice {
+	fn len() -> usize { 3 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST, DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		let list = match &iotas[0] {
+			Iota::List(list) => list,
+			_ => return vec![Err(invalid_type(iotas, &[LIST, ANY, ANY]))]
+		};
+
+		let indices = int_between(&iotas[1], 1, 0, max_index(list))
+			.and_then(|start| int_between(&iotas[2], 0, 0, max_index(list)).map(|end| (start, end)));
+
+		match indices {
+			Ok((IntArg::Known(start), IntArg::Known(end))) => {
+				let (start, end) = (i64::min(start, end) as usize, i64::max(start, end) as usize);
+				match &list.0 {
+					Left(values) => vec![Ok(vec![values[start..end].to_vec().into()])],
+					Right(_) => vec![Ok(vec![list.with_len(Some(end - start)).into()])],
+				}
+			},
+			Ok((start, end)) => {
+				let mut results = vec![Ok(vec![list.with_len(None).into()])];
+				if let IntArg::Unknown { may_mishap: true, .. } = start {
+					results.push(Err(ActionError::OutOfBounds { argument: 1 }))
+				} else if let IntArg::Unknown { may_mishap: true, .. } = end {
+					results.push(Err(ActionError::OutOfBounds { argument: 0 }))
+				}
+				results
+			},
+			Err(action_error) => vec![Err(action_error)]
+		}
+	}
+}
+
+/// Speaker's Decomposition; removes the first iota from a list and pushes it on top of the rest of the list, pushing Null if the list is empty.
+pub struct Deconstruct;
+
+impl ConstLenAction for Deconstruct {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST), OutputType::Types(ANY)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => {
+					if list.is_empty() {
+						Ok(vec![iotas[0].clone(), Iota::Widget])
+					} else {
+						Ok(vec![list[1..].to_vec().into(), list[0].clone()])
+					}
+				},
+				Iota::List(IotaList(Right(Some(0)), _)) => Ok(vec![iotas[0].clone(), Iota::Widget]),
+				Iota::List(list @ IotaList(Right(length), _)) => Ok(vec![list.with_len(length.map(|length| length - 1)).into(), list.element()]),
+				_ => Err(invalid_type(iotas, &[LIST]))
+			}
+		]
+	}
+}
+
+/// Flock's Gambit; pops a number n, then collects the n iotas below it into a list.
+/// If n isn't known, every length it could be is a possible outcome.
+pub struct Construct;
+
+impl Action for Construct {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let stack = stack_state.stack();
+
+		let (count, rest) = match stack.split_last() {
+			Some(split) => split,
+			None => return StackHolder::single(Err(ActionError::StackTooSmall { expected: 1, found: 0 })),
+		};
+
+		let construct = |n: usize| {
+			let mut stack = rest.to_vec();
+			let list = stack.split_off(rest.len() - n);
+			stack.push(list.into());
+			Ok(stack_state.with_stack(stack))
+		};
+
+		match int_between(count, 0, 0, rest.len() as i64) {
+			Ok(IntArg::Known(n)) => StackHolder::single(construct(n as usize)),
+			Ok(IntArg::Unknown { min, max, may_mishap }) => {
+				let mut results: Vec<_> = (min as usize..=max as usize).map(construct).collect();
+				if may_mishap {
+					results.push(Err(ActionError::OutOfBounds { argument: 0 }));
+				}
+				StackHolder::new(results)
+			},
+			Err(action_error) => StackHolder::single(Err(action_error)),
+		}
+	}
+}
+
+/// Flock's Disintegration; removes a list and pushes each of its elements in order.
+/// A list whose elements aren't known is only splatted if its length is known and at most [`MAX_STACK_LEN`].
+pub struct Splat;
+
+impl ConstLenAction for Splat {
+	fn len() -> usize { 1 }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => Ok(list.clone()),
+				Iota::List(list @ IotaList(Right(Some(length)), _)) if *length <= MAX_STACK_LEN => Ok(vec![list.element(); *length]),
+				Iota::List(IotaList(Right(_), _)) => Err(ActionError::Indeterminate),
+				_ => Err(invalid_type(iotas, &[LIST]))
+			}
+		]
+	}
+}
+
+/// Retrograde Purification; reverses a list.
+pub struct Reverse;
+
+impl ConstLenAction for Reverse {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => Ok(vec![list.iter().rev().cloned().collect::<Vec<_>>().into()]),
+				Iota::List(_) => Ok(vec![iotas[0].clone()]),
+				_ => Err(invalid_type(iotas, &[LIST]))
+			}
+		]
+	}
+}
+
+/// Locator's Distillation; replaces a list and an iota with the index of the first element equal to that iota, or -1 if there is none.
+pub struct IndexOf;
+
+impl ConstLenAction for IndexOf {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST, ANY] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::List(IotaList(Left(list), _)) => {
+					let mut index = Left(-1.0);
+
+					for (i, iota) in list.iter().enumerate() {
+						match iota.tolerates(&iotas[1]) {
+							Some(true) => { index = Left(i as f64); break },
+							Some(false) => {},
+							None => { index = Right(Interval::integer(-1.0, list.len() as f64 - 1.0)); break },
+						}
+					}
+
+					Ok(vec![index.into()])
+				},
+				Iota::List(list) => Ok(vec![Interval::integer(-1.0, list.len().map_or(f64::INFINITY, |len| len as f64 - 1.0)).into()]),
+				_ => Err(invalid_type(iotas, &[LIST, ANY]))
+			}
+		]
+	}
+}
+
+/// Surgeon's Exaltation; replaces the element of a list at an index with a new iota.
+pub struct Replace;
+
+impl ConstLenAction for Replace {
+	fn len() -> usize { 3 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST, DOUBLE, ANY] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		let list = match &iotas[0] {
+			Iota::List(list) => list,
+			_ => return vec![Err(invalid_type(iotas, &[LIST, ANY, ANY]))]
+		};
+
+		match (int_between(&iotas[1], 1, 0, max_index(list) - 1), &list.0) {
+			(Ok(IntArg::Known(index)), Left(list)) => {
+				let mut list = list.clone();
+				list[index as usize] = iotas[2].clone();
+				vec![Ok(vec![list.into()])]
+			},
+			(Ok(index), _) => {
+				let element = list.1.as_ref().and_then(|element| element.join(&iotas[2]));
+				let mut results = vec![Ok(vec![IotaList::unknown(list.len(), element).into()])];
+				if let IntArg::Unknown { may_mishap: true, .. } = index {
+					results.push(Err(ActionError::OutOfBounds { argument: 1 }))
+				}
+				results
+			},
+			(Err(action_error), _) => vec![Err(action_error)]
+		}
+	}
+}
+
+/// Vacant Reflection; pushes an empty list.
+pub struct EmptyList;
+
+impl ConstLenAction for EmptyList {
+	fn len() -> usize { 0 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Vec::new().into()])]
+	}
+}
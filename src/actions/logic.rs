@@ -0,0 +1,254 @@
+use itertools::Either::Left;
+
+use crate::interval::Interval;
+use crate::numbers::tolerates;
+use crate::simulator::{Iota, IotaType, ConstLenAction, ActionError, OutputType, TOLERANCE};
+use crate::symbolic::Op;
+
+use super::{invalid_type, ANY, BOOL, DOUBLE};
+
+/// True Reflection; pushes True.
+pub struct PushTrue;
+
+impl ConstLenAction for PushTrue {
+	fn len() -> usize { 0 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![true.into()])]
+	}
+}
+
+/// False Reflection; pushes False.
+pub struct PushFalse;
+
+impl ConstLenAction for PushFalse {
+	fn len() -> usize { 0 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![false.into()])]
+	}
+}
+
+/// Compares two doubles the way Hex Casting does; if they are within TOLERANCE of each other they count as equal,
+/// and `accepts_equal` is returned, otherwise `cmp` decides. Unknown doubles give a known result if their bounds
+/// are far enough apart that every value they could take gives the same result.
+fn compare(iotas: &[Iota], accepts_equal: bool, cmp: fn(f64, f64) -> bool) -> Vec<Result<Vec<Iota>, ActionError>> {
+	vec![
+		match (&iotas[0], &iotas[1]) {
+			(Iota::Double(Left(d0)), Iota::Double(Left(d1))) => {
+				if tolerates(*d0, *d1) {
+					Ok(vec![accepts_equal.into()])
+				} else {
+					Ok(vec![cmp(*d0, *d1).into()])
+				}
+			},
+			(Iota::Double(d0), Iota::Double(d1)) => {
+				let (i0, i1) = (Interval::from_double(d0), Interval::from_double(d1));
+				if i0.lower - i1.upper >= TOLERANCE {
+					Ok(vec![cmp(1.0, 0.0).into()])
+				} else if i1.lower - i0.upper >= TOLERANCE {
+					Ok(vec![cmp(0.0, 1.0).into()])
+				} else {
+					Ok(vec![Iota::Bool(None)])
+				}
+			},
+			_ => Err(invalid_type(iotas, &[DOUBLE, DOUBLE]))
+		}
+	]
+}
+
+/// Maximus Distillation; pushes whether the first double is greater than the second.
+pub struct Greater;
+
+impl ConstLenAction for Greater {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		compare(iotas, false, |d0, d1| d0 > d1)
+	}
+}
+
+/// Minimus Distillation; pushes whether the first double is less than the second.
+pub struct Less;
+
+impl ConstLenAction for Less {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		compare(iotas, false, |d0, d1| d0 < d1)
+	}
+}
+
+/// Maximus Distillation II; pushes whether the first double is greater than or equal to the second.
+pub struct GreaterEq;
+
+impl ConstLenAction for GreaterEq {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		compare(iotas, true, |d0, d1| d0 > d1)
+	}
+}
+
+/// Minimus Distillation II; pushes whether the first double is less than or equal to the second.
+pub struct LessEq;
+
+impl ConstLenAction for LessEq {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		compare(iotas, true, |d0, d1| d0 < d1)
+	}
+}
+
+/// Equality Distillation; pushes whether the two iotas are equal.
+pub struct Equals;
+
+impl ConstLenAction for Equals {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	// equal expressions are known to be equal even if their values aren't known.
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Iota::Bool(iotas[0].tolerates(&iotas[1]))])]
+	}
+}
+
+/// Inequality Distillation; pushes whether the two iotas are not equal.
+pub struct NotEquals;
+
+impl ConstLenAction for NotEquals {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	// equal expressions are known to be equal even if their values aren't known.
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Iota::Bool(iotas[0].tolerates(&iotas[1]).map(|equal| !equal))])]
+	}
+}
+
+/// Augur's Exaltation; replaces a boolean and two iotas with the first iota if the boolean is true, and the second otherwise.
+/// If the boolean isn't known, both are possible outcomes.
+pub struct If;
+
+impl ConstLenAction for If {
+	fn len() -> usize { 3 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![BOOL, ANY, ANY] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(ANY)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match iotas[0].value() {
+			Iota::Bool(Some(true)) => vec![Ok(vec![iotas[1].clone()])],
+			Iota::Bool(Some(false)) => vec![Ok(vec![iotas[2].clone()])],
+			Iota::Bool(None) => vec![Ok(vec![iotas[1].clone()]), Ok(vec![iotas[2].clone()])],
+			_ => vec![Err(invalid_type(iotas, &[BOOL, ANY, ANY]))]
+		}
+	}
+}
+
+/// Disjunction Distillation; pushes whether either boolean is true.
+pub struct Or;
+
+impl ConstLenAction for Or {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![BOOL, BOOL] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Bool(Some(true)), Iota::Bool(_)) | (Iota::Bool(_), Iota::Bool(Some(true))) => Ok(vec![true.into()]),
+				(Iota::Bool(Some(false)), Iota::Bool(Some(false))) => Ok(vec![false.into()]),
+				(Iota::Bool(_), Iota::Bool(_)) => Ok(vec![Iota::Bool(None)]),
+				_ => Err(invalid_type(iotas, &[BOOL, BOOL]))
+			}
+		]
+	}
+}
+
+/// Conjunction Distillation; pushes whether both booleans are true.
+pub struct And;
+
+impl ConstLenAction for And {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![BOOL, BOOL] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Bool(Some(false)), Iota::Bool(_)) | (Iota::Bool(_), Iota::Bool(Some(false))) => Ok(vec![false.into()]),
+				(Iota::Bool(Some(true)), Iota::Bool(Some(true))) => Ok(vec![true.into()]),
+				(Iota::Bool(_), Iota::Bool(_)) => Ok(vec![Iota::Bool(None)]),
+				_ => Err(invalid_type(iotas, &[BOOL, BOOL]))
+			}
+		]
+	}
+}
+
+/// Negation Purification; inverts a boolean.
+pub struct Not;
+
+impl ConstLenAction for Not {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![BOOL] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Bool(b) => Ok(vec![Iota::Bool(b.map(|b| !b))]),
+				_ => Err(invalid_type(iotas, &[BOOL]))
+			}
+		]
+	}
+}
+
+/// Augur's Purification; replaces an iota with whether it is truthy. False, 0, Null, Garbage, the zero vector and empty lists are falsy.
+pub struct Truthy;
+
+impl ConstLenAction for Truthy {
+	fn len() -> usize { 1 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(BOOL)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Iota::Bool(iotas[0].truthy())])]
+	}
+}
@@ -1,38 +1,346 @@
-use itertools::Either::{Left, Right};
+use itertools::Either::{self, Left, Right};
 
-use crate::simulator::{Iota, ConstLenAction, ActionError};
+use crate::interval::Interval;
+use crate::numbers::{ceil, floor, modulo};
+use crate::simulator::{Iota, IotaType, ConstLenAction, ActionError, OutputType};
+use crate::symbolic::Op;
 
+use super::{invalid_type, DOUBLE, NUMERIC, VEC};
+
+type Double = Either<f64, Interval>;
+type Vector = Either<(f64, f64, f64), bool>;
+
+/// The types Length Purification accepts.
+pub(crate) const MEASURABLE: &[IotaType] = &[IotaType::Double, IotaType::Vec, IotaType::List];
+
+/// Applies a binary operation to two doubles, using `known` if both are known and `interval` to work out the bounds otherwise.
+fn double_op(d0: &Double, d1: &Double, known: fn(f64, f64) -> f64, interval: fn(&Interval, &Interval) -> Interval) -> Iota {
+	match (d0, d1) {
+		(Left(d0), Left(d1)) => known(*d0, *d1).into(),
+		_ => interval(&Interval::from_double(d0), &Interval::from_double(d1)).into(),
+	}
+}
+
+/// Applies a binary operation to each component of the vector, with the double as the second argument.
+fn vec_double_op(vec: &Vector, d: &Double, op: fn(f64, f64) -> f64) -> Iota {
+	match (vec, d) {
+		(Left(vec), Left(d)) => (op(vec.0, *d), op(vec.1, *d), op(vec.2, *d)).into(),
+		_ => Right(false).into(),
+	}
+}
+
+/// Applies a binary operation to each component of the vector, with the double as the first argument.
+fn double_vec_op(d: &Double, vec: &Vector, op: fn(f64, f64) -> f64) -> Iota {
+	match (d, vec) {
+		(Left(d), Left(vec)) => (op(*d, vec.0), op(*d, vec.1), op(*d, vec.2)).into(),
+		_ => Right(false).into(),
+	}
+}
+
+/// Applies a binary operation to each pair of components of the vectors.
+fn vec_op(vec0: &Vector, vec1: &Vector, op: fn(f64, f64) -> f64) -> Iota {
+	match (vec0, vec1) {
+		(Left(vec0), Left(vec1)) => (op(vec0.0, vec1.0), op(vec0.1, vec1.1), op(vec0.2, vec1.2)).into(),
+		_ => Right(false).into(),
+	}
+}
+
+fn dot(vec0: &(f64, f64, f64), vec1: &(f64, f64, f64)) -> f64 {
+	vec0.0 * vec1.0 + vec0.1 * vec1.1 + vec0.2 * vec1.2
+}
+
+/// Returns the possible results of dividing by `divisor`, which are a mishap if the divisor is zero, `result` if it isn't, or both if it might be.
+fn checked_div(divisor: &Double, result: impl FnOnce() -> Iota) -> Vec<Result<Vec<Iota>, ActionError>> {
+	match divisor {
+		Left(d) if *d == 0.0 => vec![Err(ActionError::DivByZero)],
+		Right(interval) if *interval == Interval::point(0.0) => vec![Err(ActionError::DivByZero)],
+		Right(interval) if interval.contains_zero() => vec![Ok(vec![result()]), Err(ActionError::DivByZero)],
+		_ => vec![Ok(vec![result()])]
+	}
+}
+
+/// Additive Distillation; adds two doubles, two vectors, or a double to each component of a vector.
 pub struct Add;
 
 impl ConstLenAction for Add {
-    fn len() -> usize { 2 }
-
-    fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
-			vec![
-			match iotas[0] {
-        Iota::Double(d0) => {
-					if let Some(d0) = d0 {
-						match iotas[1] {
-							Iota::Double(d1) => Ok(vec![d1.map(|d1| d0 + d1).into()]),
-							Iota::Vec(vec1) => Ok(vec![vec1.map_left(|vec1| (vec1.0 + d0, vec1.1 + d0, vec1.2 + d0)).into()]),
-							_ => Err(ActionError::InvalidType),
-						}
-					} else {
-						Ok(vec![Iota::Double(None)])
-					}
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Add) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Double(d0), Iota::Double(d1)) => Ok(vec![double_op(d0, d1, |d0, d1| d0 + d1, Interval::add)]),
+				(Iota::Double(d0), Iota::Vec(vec1)) => Ok(vec![double_vec_op(d0, vec1, |d0, d1| d0 + d1)]),
+				(Iota::Vec(vec0), Iota::Double(d1)) => Ok(vec![vec_double_op(vec0, d1, |d0, d1| d0 + d1)]),
+				(Iota::Vec(vec0), Iota::Vec(vec1)) => Ok(vec![vec_op(vec0, vec1, |d0, d1| d0 + d1)]),
+				_ => Err(invalid_type(iotas, &[NUMERIC, NUMERIC])),
+			}
+		]
+	}
+}
+
+/// Subtractive Distillation; subtracts two doubles, two vectors, or a double from each component of a vector.
+pub struct Sub;
+
+impl ConstLenAction for Sub {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Sub) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Double(d0), Iota::Double(d1)) => Ok(vec![double_op(d0, d1, |d0, d1| d0 - d1, Interval::sub)]),
+				(Iota::Double(d0), Iota::Vec(vec1)) => Ok(vec![double_vec_op(d0, vec1, |d0, d1| d0 - d1)]),
+				(Iota::Vec(vec0), Iota::Double(d1)) => Ok(vec![vec_double_op(vec0, d1, |d0, d1| d0 - d1)]),
+				(Iota::Vec(vec0), Iota::Vec(vec1)) => Ok(vec![vec_op(vec0, vec1, |d0, d1| d0 - d1)]),
+				_ => Err(invalid_type(iotas, &[NUMERIC, NUMERIC])),
+			}
+		]
+	}
+}
+
+/// Multiplicative Distillation; multiplies two doubles, scales a vector by a double, or takes the dot product of two vectors.
+pub struct Mul;
+
+impl ConstLenAction for Mul {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Mul) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Double(d0), Iota::Double(d1)) => Ok(vec![double_op(d0, d1, |d0, d1| d0 * d1, Interval::mul)]),
+				(Iota::Double(d0), Iota::Vec(vec1)) => Ok(vec![double_vec_op(d0, vec1, |d0, d1| d0 * d1)]),
+				(Iota::Vec(vec0), Iota::Double(d1)) => Ok(vec![vec_double_op(vec0, d1, |d0, d1| d0 * d1)]),
+				(Iota::Vec(Left(vec0)), Iota::Vec(Left(vec1))) => Ok(vec![dot(vec0, vec1).into()]),
+				(Iota::Vec(_), Iota::Vec(_)) => Ok(vec![Iota::Double(Right(Interval::unbounded()))]),
+				_ => Err(invalid_type(iotas, &[NUMERIC, NUMERIC])),
+			}
+		]
+	}
+}
+
+/// Division Distillation; divides two doubles, divides each component of a vector by a double (or a double by each component),
+/// or takes the cross product of two vectors. Dividing by zero is a mishap.
+pub struct Div;
+
+impl ConstLenAction for Div {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Div) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match (&iotas[0], &iotas[1]) {
+			(Iota::Double(d0), Iota::Double(d1)) => checked_div(d1, || double_op(d0, d1, |d0, d1| d0 / d1, Interval::div)),
+			(Iota::Vec(vec0), Iota::Double(d1)) => checked_div(d1, || vec_double_op(vec0, d1, |d0, d1| d0 / d1)),
+			(Iota::Double(d0), Iota::Vec(vec1)) => match vec1 {
+				Left(vec1) if vec1.0 == 0.0 || vec1.1 == 0.0 || vec1.2 == 0.0 => vec![Err(ActionError::DivByZero)],
+				Left(_) => vec![Ok(vec![double_vec_op(d0, vec1, |d0, d1| d0 / d1)])],
+				Right(_) => vec![Ok(vec![Right(false).into()]), Err(ActionError::DivByZero)],
+			},
+			(Iota::Vec(Left(vec0)), Iota::Vec(Left(vec1))) => vec![Ok(vec![(
+				vec0.1 * vec1.2 - vec0.2 * vec1.1,
+				vec0.2 * vec1.0 - vec0.0 * vec1.2,
+				vec0.0 * vec1.1 - vec0.1 * vec1.0
+			).into()])],
+			(Iota::Vec(_), Iota::Vec(_)) => vec![Ok(vec![Right(false).into()])],
+			_ => vec![Err(invalid_type(iotas, &[NUMERIC, NUMERIC]))],
+		}
+	}
+}
+
+/// Length Purification; the absolute value of a double, the length of a vector, or the number of elements in a list.
+pub struct Abs;
+
+impl ConstLenAction for Abs {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![MEASURABLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Abs) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Double(Left(d)) => Ok(vec![d.abs().into()]),
+				Iota::Double(Right(interval)) => Ok(vec![interval.abs().into()]),
+				Iota::Vec(Left(vec)) => Ok(vec![dot(vec, vec).sqrt().into()]),
+				Iota::Vec(Right(_)) => Ok(vec![Interval::new(0.0, f64::INFINITY).into()]),
+				Iota::List(list) => Ok(vec![match list.len() {
+					Some(len) => (len as f64).into(),
+					None => Interval::integer(0.0, f64::INFINITY).into(),
+				}]),
+				_ => Err(invalid_type(iotas, &[MEASURABLE])),
+			}
+		]
+	}
+}
+
+/// Power Distillation; raises a double to a power, raises each component of a vector to a power (or a double to each
+/// component), or projects the first vector onto the second.
+pub struct Pow;
+
+impl ConstLenAction for Pow {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Pow) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match (&iotas[0], &iotas[1]) {
+			(Iota::Double(d0), Iota::Double(d1)) => vec![Ok(vec![double_op(d0, d1, f64::powf, Interval::pow)])],
+			(Iota::Double(d0), Iota::Vec(vec1)) => vec![Ok(vec![double_vec_op(d0, vec1, f64::powf)])],
+			(Iota::Vec(vec0), Iota::Double(d1)) => vec![Ok(vec![vec_double_op(vec0, d1, f64::powf)])],
+			(Iota::Vec(Left(vec0)), Iota::Vec(Left(vec1))) => {
+				let len_sq = dot(vec1, vec1);
+				if len_sq == 0.0 {
+					vec![Err(ActionError::DivByZero)]
+				} else {
+					let scale = dot(vec0, vec1) / len_sq;
+					vec![Ok(vec![(vec1.0 * scale, vec1.1 * scale, vec1.2 * scale).into()])]
 				}
-        Iota::Vec(vec0) => {
-					match vec0 {
-						Left(vec0) => match iotas[1] {
-							Iota::Double(d1) => Ok(vec![ if let Some(d1) = d1 { (vec0.0 + d1, vec0.1 + d1, vec0.2 + d1).into() } else { Right(false).into() } ]),
-							Iota::Vec(_) => todo!(),
-							_ => Err(ActionError::InvalidType),
-						},
-						Right(_) => Ok(vec![vec0.into()]),
-					}
-				},
-        _ => Err(ActionError::InvalidType),
-    	}
-			]
-    }
-}
\ No newline at end of file
+			},
+			(Iota::Vec(_), Iota::Vec(Left(vec1))) if dot(vec1, vec1) == 0.0 => vec![Err(ActionError::DivByZero)],
+			(Iota::Vec(_), Iota::Vec(Left(_))) => vec![Ok(vec![Right(false).into()])],
+			(Iota::Vec(_), Iota::Vec(Right(_))) => vec![Ok(vec![Right(false).into()]), Err(ActionError::DivByZero)],
+			_ => vec![Err(invalid_type(iotas, &[NUMERIC, NUMERIC]))],
+		}
+	}
+}
+
+/// Floor Purification; rounds a double (or each component of a vector) down.
+pub struct Floor;
+
+impl ConstLenAction for Floor {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Floor) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Double(Left(d)) => Ok(vec![floor(*d).into()]),
+				Iota::Double(Right(interval)) => Ok(vec![interval.floor().into()]),
+				Iota::Vec(vec) => Ok(vec![vec.map_left(|vec| (floor(vec.0), floor(vec.1), floor(vec.2))).into()]),
+				_ => Err(invalid_type(iotas, &[NUMERIC])),
+			}
+		]
+	}
+}
+
+/// Ceiling Purification; rounds a double (or each component of a vector) up.
+pub struct Ceil;
+
+impl ConstLenAction for Ceil {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Ceil) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Double(Left(d)) => Ok(vec![ceil(*d).into()]),
+				Iota::Double(Right(interval)) => Ok(vec![interval.ceil().into()]),
+				Iota::Vec(vec) => Ok(vec![vec.map_left(|vec| (ceil(vec.0), ceil(vec.1), ceil(vec.2))).into()]),
+				_ => Err(invalid_type(iotas, &[NUMERIC])),
+			}
+		]
+	}
+}
+
+/// Modulus Distillation; the remainder of dividing a double (or each component of a vector) by a double. Dividing by zero is a mishap.
+pub struct Modulo;
+
+impl ConstLenAction for Modulo {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![NUMERIC, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(NUMERIC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Mod) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match (&iotas[0], &iotas[1]) {
+			(Iota::Double(d0), Iota::Double(d1)) => checked_div(d1, || double_op(d0, d1, modulo, Interval::rem)),
+			(Iota::Vec(vec0), Iota::Double(d1)) => checked_div(d1, || vec_double_op(vec0, d1, modulo)),
+			_ => vec![Err(invalid_type(iotas, &[NUMERIC, DOUBLE]))],
+		}
+	}
+}
+
+/// Vector Exaltation; combines three doubles into a vector.
+pub struct ConstructVec;
+
+impl ConstLenAction for ConstructVec {
+	fn len() -> usize { 3 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE, DOUBLE, DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(VEC)]) }
+
+	fn op(&self) -> Option<Op> { Some(Op::Vec) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1], &iotas[2]) {
+				(Iota::Double(Left(x)), Iota::Double(Left(y)), Iota::Double(Left(z))) => Ok(vec![(*x, *y, *z).into()]),
+				(Iota::Double(_), Iota::Double(_), Iota::Double(_)) => Ok(vec![Right(false).into()]),
+				_ => Err(invalid_type(iotas, &[DOUBLE, DOUBLE, DOUBLE])),
+			}
+		]
+	}
+}
+
+/// Vector Disintegration; splits a vector into its three components.
+pub struct DeconstructVec;
+
+impl ConstLenAction for DeconstructVec {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![VEC] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE), OutputType::Types(DOUBLE), OutputType::Types(DOUBLE)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Vec(Left(vec)) => Ok(vec![vec.0.into(), vec.1.into(), vec.2.into()]),
+				Iota::Vec(Right(_)) => Ok(vec![Iota::Double(Right(Interval::unbounded())); 3]),
+				_ => Err(invalid_type(iotas, &[VEC])),
+			}
+		]
+	}
+}
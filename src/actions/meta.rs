@@ -0,0 +1,90 @@
+use itertools::Either::{Left, Right};
+
+use crate::hex_pattern::HexPattern;
+use crate::simulator::{Action, ActionError, Frame, Iota, IotaList, IotaType, StackHolder, StackState};
+use crate::version::GameVersion;
+
+use super::{LIST, PATTERN};
+
+pub(crate) const PATTERN_OR_LIST: &[IotaType] = &[IotaType::Pattern, IotaType::List];
+
+/// Reads the iota as code to cast: a pattern, or a list of patterns. A list that isn't known, or that might hold iotas
+/// other than patterns, can't be cast by the simulator.
+fn code(iota: &Iota, argument: usize) -> Result<Vec<HexPattern>, ActionError> {
+	match iota.value() {
+		Iota::Pattern(pattern) => Ok(vec![pattern.clone()]),
+		Iota::List(IotaList(Left(iotas), _)) => iotas.iter().map(|iota| match iota.value() {
+			Iota::Pattern(pattern) => Ok(pattern.clone()),
+			Iota::Unknown => Err(ActionError::Indeterminate),
+			other => Err(ActionError::InvalidType { argument, expected: PATTERN, found: IotaType::of(other) }),
+		}).collect(),
+		Iota::List(IotaList(Right(_), _)) => Err(ActionError::Indeterminate),
+		other => Err(ActionError::InvalidType { argument, expected: PATTERN_OR_LIST, found: IotaType::of(other) }),
+	}
+}
+
+/// Hermes' Gambit; casts the pattern or list of patterns on top of the stack, as a frame the branch casts before carrying
+/// on with the rest of the hex.
+pub struct Eval;
+
+impl Action for Eval {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+
+		let patterns = match stack.pop() {
+			Some(iota) => match code(&iota, 0) {
+				Ok(patterns) => patterns,
+				Err(action_error) => return StackHolder::single(Err(action_error)),
+			},
+			None => return StackHolder::single(Err(ActionError::StackTooSmall { expected: 1, found: 0 })),
+		};
+
+		let mut state = stack_state.with_stack(stack);
+		state.push_frame(Frame::Evaluate { patterns, next: 0 });
+		StackHolder::single_state(state)
+	}
+}
+
+/// Thoth's Gambit; casts a pattern or list of patterns once for each element of a list, each time on the rest of the
+/// stack with the element pushed on top, then pushes a list of every iota left on the stacks.
+pub struct ForEach;
+
+impl Action for ForEach {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+
+		if stack.len() < 2 {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: 2, found: stack.len() }))
+		}
+
+		let args = stack.split_off(stack.len() - 2);
+		let data = match args[1].value() {
+			Iota::List(IotaList(Left(data), _)) => data.clone(),
+			Iota::List(IotaList(Right(_), _)) => return StackHolder::single(Err(ActionError::Indeterminate)),
+			other => return StackHolder::single(Err(ActionError::InvalidType { argument: 0, expected: LIST, found: IotaType::of(other) })),
+		};
+		let code = match code(&args[0], 1) {
+			Ok(code) => code,
+			Err(action_error) => return StackHolder::single(Err(action_error)),
+		};
+
+		let mut state = stack_state.with_stack(stack.clone());
+		state.push_frame(Frame::ForEach { code, data, next: 0, base: stack, acc: vec![] });
+		StackHolder::single_state(state)
+	}
+}
+
+/// A pattern that doesn't exist in the version of Hex Casting being simulated; drawing it mishaps, pushing Garbage.
+pub struct MissingPattern {
+	/// the name of the pattern in the versions that have it.
+	pub name: String,
+	pub version: GameVersion
+}
+
+impl Action for MissingPattern {
+	fn apply(&self, _stack_state: &StackState) -> StackHolder {
+		StackHolder::single(Err(ActionError::InvalidPattern { version: self.version }))
+	}
+
+	fn name(&self) -> String { self.name.clone() }
+}
@@ -1 +1,76 @@
 pub mod maths;
+pub mod lists;
+pub mod logic;
+pub mod stack;
+pub mod meta;
+pub mod spells;
+pub mod entities;
+pub mod raycasts;
+pub mod akashic;
+pub mod items;
+pub mod registry;
+
+use itertools::Either::{Left, Right};
+
+use crate::numbers::{ceil, floor, round, TOLERANCE};
+use crate::simulator::{Iota, IotaType, ActionError};
+
+pub(crate) const DOUBLE: &[IotaType] = &[IotaType::Double];
+pub(crate) const NUMERIC: &[IotaType] = &[IotaType::Double, IotaType::Vec];
+pub(crate) const VEC: &[IotaType] = &[IotaType::Vec];
+pub(crate) const BOOL: &[IotaType] = &[IotaType::Bool];
+pub(crate) const LIST: &[IotaType] = &[IotaType::List];
+pub(crate) const ANY: &[IotaType] = IotaType::ANY;
+pub(crate) const ENTITY: &[IotaType] = &[IotaType::Entity];
+pub(crate) const PATTERN: &[IotaType] = &[IotaType::Pattern];
+
+/// Every list of more than one type an action can expect an argument to be, besides [`ANY`]. Mishaps read from files
+/// can only expect one of these or a single type (see [`crate::serialization`]).
+pub(crate) const TYPE_LISTS: &[&[IotaType]] = &[NUMERIC, maths::MEASURABLE, raycasts::VEC_OR_NULL, meta::PATTERN_OR_LIST];
+
+/// Returns the mishap for the first of the action's arguments (deepest first) that isn't one of the types expected for it.
+pub(crate) fn invalid_type(iotas: &[Iota], expected: &[&'static [IotaType]]) -> ActionError {
+	let last = iotas.len() - 1;
+	let (i, expected) = iotas.iter().zip(expected.iter())
+		.enumerate()
+		.find(|(_, (iota, expected))| !expected.contains(&IotaType::of(iota)))
+		.map_or((last, expected[last]), |(i, (_, expected))| (i, *expected));
+
+	ActionError::InvalidType { argument: last - i, expected, found: IotaType::of(&iotas[i]) }
+}
+
+/// An integer read from an iota; either known, or any of the integers in min..=max.
+/// `may_mishap` is true if the value might also be outside of the accepted range, or might not be a whole number.
+pub(crate) enum IntArg {
+	Known(i64),
+	Unknown { min: i64, max: i64, may_mishap: bool }
+}
+
+/// Reads the iota as an integer in min..=max, the way Hex Casting reads list indices and stack depths.
+/// `argument` is where the iota is among the action's arguments, counting from the top of the stack.
+pub(crate) fn int_between(iota: &Iota, argument: usize, min: i64, max: i64) -> Result<IntArg, ActionError> {
+	match iota.value() {
+		Iota::Double(Left(d)) => {
+			let rounded = round(*d);
+			if (rounded - d).abs() >= TOLERANCE || rounded < min as f64 || rounded > max as f64 {
+				return Err(ActionError::OutOfBounds { argument })
+			}
+
+			Ok(IntArg::Known(rounded as i64))
+		},
+		Iota::Double(Right(interval)) => {
+			let (lower, upper) = (f64::max(ceil(interval.lower), min as f64), f64::min(floor(interval.upper), max as f64));
+			if lower > upper {
+				return Err(ActionError::OutOfBounds { argument })
+			}
+
+			let may_mishap = !interval.integer || interval.lower < min as f64 || interval.upper > max as f64;
+			if lower == upper && !may_mishap {
+				return Ok(IntArg::Known(lower as i64))
+			}
+
+			Ok(IntArg::Unknown { min: lower as i64, max: upper as i64, may_mishap })
+		},
+		_ => Err(ActionError::InvalidType { argument, expected: DOUBLE, found: IotaType::of(iota) })
+	}
+}
@@ -0,0 +1,73 @@
+use itertools::Either::{Left, Right};
+
+use crate::simulator::{Action, ActionError, Iota, IotaType, OutputType, Signature, StackHolder, StackState};
+use crate::world::{RaycastHit, RAYCAST_DISTANCE};
+
+use super::{invalid_type, VEC};
+
+pub(crate) const VEC_OR_NULL: &[IotaType] = &[IotaType::Vec, IotaType::Null];
+
+/// Archer's and Architect's Distillations; replace a position and a direction with the block a raycast from the position
+/// along the direction hits (Archer's), or the normal of the face it hits (Architect's). Pushes Null if the raycast
+/// doesn't hit a block in the caster's ambit. If the blocks along the ray aren't known, the result is an unknown vector
+/// or Null.
+pub struct BlockRaycast {
+	pub normal: bool
+}
+
+impl BlockRaycast {
+	/// The unknown results a raycast could have, as separate branches.
+	fn unknown(&self, can_miss: bool) -> Vec<Iota> {
+		let hit = Iota::Vec(Right(!self.normal));
+		if can_miss { vec![hit, Iota::Widget] } else { vec![hit] }
+	}
+}
+
+impl Action for BlockRaycast {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+
+		if stack.len() < 2 {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: 2, found: stack.len() }))
+		}
+
+		let args = stack.split_off(stack.len() - 2);
+		let (origin, direction) = match (args[0].value(), args[1].value()) {
+			(Iota::Vec(origin), Iota::Vec(direction)) => (*origin, *direction),
+			_ => return StackHolder::single(Err(invalid_type(&args, &[VEC, VEC]))),
+		};
+
+		let results = match (stack_state.scene(), origin, direction) {
+			(Some(scene), Left(origin), Left(direction)) => match scene.blocks.raycast(origin, direction, RAYCAST_DISTANCE) {
+				RaycastHit::Hit { block, normal } => {
+					let hit = if self.normal { normal.into() } else { (block.0 as f64, block.1 as f64, block.2 as f64).into() };
+					let centre: Iota = (block.0 as f64 + 0.5, block.1 as f64 + 0.5, block.2 as f64 + 0.5).into();
+
+					match scene.in_ambit(&centre) {
+						Some(true) => vec![hit],
+						Some(false) => vec![Iota::Widget],
+						None => vec![hit, Iota::Widget],
+					}
+				},
+				RaycastHit::Miss => vec![Iota::Widget],
+				// an unknown block that was hit might be out of ambit, unless there is no caster to check against
+				RaycastHit::Unknown { can_miss } => self.unknown(can_miss || scene.caster.is_some()),
+			},
+			_ => self.unknown(true),
+		};
+
+		StackHolder::new(results.into_iter().map(|result| {
+			let mut stack = stack.clone();
+			stack.push(result);
+			Ok(stack_state.with_stack(stack))
+		}).collect())
+	}
+
+	fn name(&self) -> String { if self.normal { "block_axis_raycast".to_string() } else { "block_raycast".to_string() } }
+
+	fn signature(&self) -> Option<Signature> {
+		Some(Signature { inputs: vec![VEC, VEC], outputs: vec![OutputType::Types(VEC_OR_NULL)] })
+	}
+
+	fn targets(&self) -> Vec<usize> { vec![1] }
+}
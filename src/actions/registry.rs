@@ -0,0 +1,194 @@
+use crate::addons::with_registry;
+use crate::debugger::Debugger;
+use crate::hex_pattern::HexPattern;
+use crate::simulator::{Action, EntityType, Iota, StackManager, StackState};
+use crate::stack_effect::{block_end, INTROSPECTION, RETROSPECTION};
+use crate::version::profile;
+
+use super::akashic::*;
+use super::entities::*;
+use super::items::*;
+use super::lists::*;
+use super::logic::*;
+use super::maths::*;
+use super::meta::*;
+use super::raycasts::*;
+use super::spells::*;
+use super::stack::*;
+
+/// Patterns Hex Casting has that the simulator knows the names of but can't cast, by angle signature.
+const UNCASTABLE: &[(&str, &str)] = &[("qwaqde", "iris_gambit"), ("qqaed", "thanatos_reflection")];
+
+/// Returns the action Hex Casting performs when the pattern is drawn, or None if the simulator doesn't know the pattern.
+/// Patterns Hex Casting doesn't have are looked up in the installed addon packs (see [`crate::addons`]). Patterns that
+/// aren't in the selected version of Hex Casting (see [`crate::version::Profile`]) mishap.
+pub fn action_for(pattern: &HexPattern) -> Option<Box<dyn Action>> {
+	let signature = pattern.angle_signature();
+	let profile = profile();
+	if !profile.has_pattern(&signature) {
+		let name = pattern_name(pattern).unwrap_or(signature);
+		return Some(Box::new(MissingPattern { name, version: profile.version }))
+	}
+
+	builtin_action(&signature).or_else(|| with_registry(|registry| registry.action_for(&signature)))
+}
+
+/// The action Hex Casting itself performs for the angle signature.
+fn builtin_action(signature: &str) -> Option<Box<dyn Action>> {
+	let action: Box<dyn Action> = match signature {
+		"waaw" => Box::new(Add),
+		"wddw" => Box::new(Sub),
+		"waqaw" => Box::new(Mul),
+		"wdedw" => Box::new(Div),
+		"wqaqw" => Box::new(Abs),
+		"wedew" => Box::new(Pow),
+		"ewq" => Box::new(Floor),
+		"qwe" => Box::new(Ceil),
+		"addwaad" => Box::new(Modulo),
+		"eqqqqq" => Box::new(ConstructVec),
+		"qeeeee" => Box::new(DeconstructVec),
+
+		"aqae" => Box::new(PushTrue),
+		"dedq" => Box::new(PushFalse),
+		"e" => Box::new(Greater),
+		"q" => Box::new(Less),
+		"ee" => Box::new(GreaterEq),
+		"qq" => Box::new(LessEq),
+		"ad" => Box::new(Equals),
+		"da" => Box::new(NotEquals),
+		"awdd" => Box::new(If),
+		"waw" => Box::new(Or),
+		"wdw" => Box::new(And),
+		"dw" => Box::new(Not),
+		"aw" => Box::new(Truthy),
+
+		"edqde" => Box::new(Append),
+		"deeed" => Box::new(Index),
+		"qaeaqwded" => Box::new(Slice),
+		"aaqwqaa" => Box::new(Deconstruct),
+		"ewdqdwe" => Box::new(Construct),
+		"qwaeawq" => Box::new(Splat),
+		"qqqaede" => Box::new(Reverse),
+		"dedqde" => Box::new(IndexOf),
+		"wqaeaqw" => Box::new(Replace),
+		"qqaeaae" => Box::new(EmptyList),
+
+		"aadaa" => Box::new(Duplicate),
+		"aadadaaw" => Box::new(TwoDup),
+		"aawdd" => Box::new(Swap),
+		"aaeaa" => Box::new(Rotate),
+		"ddqdd" => Box::new(RotateReverse),
+		"aaedd" => Box::new(Over),
+		"ddqaa" => Box::new(Tuck),
+		"aadaadaa" => Box::new(DuplicateN),
+		"ddad" => Box::new(Fisherman { copy: false }),
+		"aada" => Box::new(Fisherman { copy: true }),
+		"qwaeawqaeaqa" => Box::new(StackSize),
+
+		"deaqq" => Box::new(Eval),
+		"dadad" => Box::new(ForEach),
+
+		"aa" => Box::new(EntityPos),
+		"wa" => Box::new(EntityLook),
+		"awq" => Box::new(EntityHeight),
+		"wq" => Box::new(EntityVelocity),
+		"qqqqqwded" => Box::new(Zone(ZoneFilter::Any)),
+		"qqqqqwdeddwa" => Box::new(Zone(ZoneFilter::Is(EntityType::Animal))),
+		"eeeeewaqaawa" => Box::new(Zone(ZoneFilter::IsNot(EntityType::Animal))),
+		"qqqqqwdeddwq" => Box::new(Zone(ZoneFilter::Is(EntityType::Monster))),
+		"eeeeewaqaawq" => Box::new(Zone(ZoneFilter::IsNot(EntityType::Monster))),
+		"qqqqqwdeddww" => Box::new(Zone(ZoneFilter::Is(EntityType::Item))),
+		"eeeeewaqaaww" => Box::new(Zone(ZoneFilter::IsNot(EntityType::Item))),
+		"qqqqqwdeddwe" => Box::new(Zone(ZoneFilter::Is(EntityType::Player))),
+		"eeeeewaqaawe" => Box::new(Zone(ZoneFilter::IsNot(EntityType::Player))),
+		"qqqqqwdeddwd" => Box::new(Zone(ZoneFilter::Is(EntityType::Living))),
+		"eeeeewaqaawd" => Box::new(Zone(ZoneFilter::IsNot(EntityType::Living))),
+		"wqaawdd" => Box::new(BlockRaycast { normal: false }),
+		"weddwaa" => Box::new(BlockRaycast { normal: true }),
+		"qqqwqqqqqaq" => Box::new(AkashicRead),
+		"eeeweeeeede" => Box::new(AkashicWrite),
+		"aqqqqq" => Box::new(ReadHeldItem),
+		"deeeee" => Box::new(WriteHeldItem),
+		"aqqqqqe" => Box::new(HeldItemAccess { writing: false }),
+		"deeeeeq" => Box::new(HeldItemAccess { writing: true }),
+
+		"awqqqwaqw" => Box::new(Impulse),
+		"aawaawaa" => Box::new(Explode { fire: false }),
+		"ddwddwdd" => Box::new(Explode { fire: true }),
+		"awqqqwaq" => Box::new(Blink),
+		"qaqqqqq" => Box::new(BreakBlock),
+		"eeeeede" => Box::new(PlaceBlock),
+		"aqawqadaq" => Box::new(CreateWater),
+		"eaqawqadaqd" => Box::new(CreateLava),
+		"qqa" => Box::new(ConjureBlock { light: false }),
+		"qqd" => Box::new(ConjureBlock { light: true }),
+
+		_ => return None
+	};
+
+	Some(action)
+}
+
+/// The name of the pattern: the name of the action it casts, or of the addon pattern it is, or of the Introspection or
+/// Retrospection it is. None if the simulator doesn't know the pattern.
+pub fn pattern_name(pattern: &HexPattern) -> Option<String> {
+	let signature = pattern.angle_signature();
+	match signature.as_str() {
+		INTROSPECTION => return Some("introspection".to_string()),
+		RETROSPECTION => return Some("retrospection".to_string()),
+		_ => {},
+	}
+
+	builtin_action(&signature).map(|action| action.name())
+		.or_else(|| UNCASTABLE.iter().find(|(uncastable, _)| *uncastable == signature).map(|(_, name)| name.to_string()))
+		.or_else(|| with_registry(|registry| registry.pattern_for(&signature).map(|pattern| pattern.name.to_string())))
+}
+
+/// Returns the action cast by the pattern at `i`, along with the index of the pattern to cast after it. An Introspection
+/// block is one action that pushes the patterns inside it as a list. The action is None if the simulator doesn't know
+/// the pattern, or if it is an Introspection or Retrospection without a match.
+pub fn next_action(patterns: &[HexPattern], i: usize) -> (Option<Box<dyn Action>>, usize) {
+	let signature = patterns[i].angle_signature();
+
+	if signature == INTROSPECTION {
+		return match block_end(patterns, i) {
+			Ok(end) => {
+				let block: Vec<Iota> = patterns[i + 1..end].iter().cloned().map(Iota::Pattern).collect();
+				(Some(Box::new(Push(block.into()))), end + 1)
+			},
+			Err(_) => (None, i + 1),
+		}
+	}
+
+	(if signature == RETROSPECTION { None } else { action_for(&patterns[i]) }, i + 1)
+}
+
+/// Splits the hex into the actions it performs, each with the index of the pattern that performs it (see [`next_action`]).
+pub fn hex_actions(patterns: &[HexPattern]) -> Vec<(usize, Option<Box<dyn Action>>)> {
+	let mut actions = vec![];
+	let mut i = 0;
+
+	while i < patterns.len() {
+		let (action, next) = next_action(patterns, i);
+		actions.push((i, action));
+		i = next;
+	}
+
+	actions
+}
+
+/// Casts the patterns in order, starting from an empty stack, and returns every stack that could result.
+/// Returns the index of the first pattern the simulator doesn't know if there is one.
+pub fn run_hex(patterns: &[HexPattern]) -> Result<StackManager, usize> { run_hex_from(patterns, StackState::default()) }
+
+/// Casts the patterns in order, starting from the given state, and returns every stack that could result.
+/// Returns the index of the first pattern the simulator doesn't know if there is one.
+pub fn run_hex_from(patterns: &[HexPattern], input: StackState) -> Result<StackManager, usize> {
+	if let Some(i) = hex_actions(patterns).into_iter().find_map(|(i, action)| action.is_none().then(|| i)) {
+		return Err(i)
+	}
+
+	let mut debugger = Debugger::new(patterns.to_vec(), input);
+	debugger.run();
+	Ok(debugger.into_stack_manager())
+}
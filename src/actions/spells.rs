@@ -0,0 +1,245 @@
+use itertools::Either::{Left, Right};
+
+use crate::interval::Interval;
+use crate::media::{CHARGED_AMETHYST, DUST, SHARD};
+use crate::simulator::{Action, Iota, IotaType, ConstLenAction, ActionError, EffectKind, OutputType, Signature, SpellEffect, StackHolder, StackState};
+use crate::world::{block_pos, is_breakable, is_replaceable};
+
+use super::{invalid_type, DOUBLE, ENTITY, VEC};
+
+/// Impulse; pushes an entity along a vector. Costs one dust per square metre of the vector's length.
+pub struct Impulse;
+
+impl ConstLenAction for Impulse {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![ENTITY, VEC] }
+
+	fn targets(&self) -> Vec<usize> { vec![1] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Entity(_), Iota::Vec(_)) => Ok(vec![]),
+				_ => Err(invalid_type(iotas, &[ENTITY, VEC]))
+			}
+		]
+	}
+
+	fn cost(&self, iotas: &[Iota]) -> Interval {
+		match &iotas[1] {
+			Iota::Vec(Left(vec)) => Interval::point((vec.0 * vec.0 + vec.1 * vec.1 + vec.2 * vec.2) * DUST),
+			_ => Interval::new(0.0, f64::INFINITY),
+		}
+	}
+
+	fn effect(&self, iotas: &[Iota]) -> Option<SpellEffect> {
+		Some(SpellEffect::new(EffectKind::Impulse, iotas[0].clone(), Some(iotas[1].clone())))
+	}
+}
+
+/// Explosion and Fireball; causes an explosion at a position with a strength between 0 and 10. Costs three dust per point
+/// of strength, plus an eighth of a dust for an explosion or one dust for a fireball.
+pub struct Explode {
+	pub fire: bool
+}
+
+impl ConstLenAction for Explode {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![VEC, DOUBLE] }
+
+	fn targets(&self) -> Vec<usize> { vec![1] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Vec(_), Iota::Double(_)) => Ok(vec![]),
+				_ => Err(invalid_type(iotas, &[VEC, DOUBLE]))
+			}
+		]
+	}
+
+	fn cost(&self, iotas: &[Iota]) -> Interval {
+		let base = if self.fire { 1.0 } else { 0.125 };
+		let strength = match &iotas[1] {
+			Iota::Double(Left(d)) => Interval::point(d.clamp(0.0, 10.0)),
+			Iota::Double(Right(interval)) => Interval::new(interval.lower.clamp(0.0, 10.0), interval.upper.clamp(0.0, 10.0)),
+			_ => Interval::point(0.0),
+		};
+
+		strength.mul(&Interval::point(3.0)).add(&Interval::point(base)).mul(&Interval::point(DUST))
+	}
+
+	fn effect(&self, iotas: &[Iota]) -> Option<SpellEffect> {
+		let kind = if self.fire { EffectKind::Fireball } else { EffectKind::Explosion };
+		Some(SpellEffect::new(kind, iotas[0].clone(), Some(iotas[1].clone())))
+	}
+}
+
+/// Blink; teleports an entity along the direction it is looking by a number of blocks. Costs half a shard per block.
+pub struct Blink;
+
+impl ConstLenAction for Blink {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![ENTITY, DOUBLE] }
+
+	fn targets(&self) -> Vec<usize> { vec![1] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::Entity(_), Iota::Double(_)) => Ok(vec![]),
+				_ => Err(invalid_type(iotas, &[ENTITY, DOUBLE]))
+			}
+		]
+	}
+
+	fn cost(&self, iotas: &[Iota]) -> Interval {
+		match &iotas[1] {
+			Iota::Double(distance) => Interval::from_double(distance).abs().mul(&Interval::point(0.5 * SHARD)),
+			_ => Interval::point(0.0),
+		}
+	}
+
+	fn effect(&self, iotas: &[Iota]) -> Option<SpellEffect> {
+		Some(SpellEffect::new(EffectKind::Blink, iotas[0].clone(), Some(iotas[1].clone())))
+	}
+}
+
+/// Applies a spell to the block at the position on top of the stack. `change` returns what the spell turns a block into,
+/// or None if it leaves the block as it is; Some(None) is a block that isn't known. If the position isn't known, every
+/// block the spell could have changed is forgotten. Without a scene, nothing is known about the world to change.
+fn block_spell(stack_state: &StackState, change: impl Fn(&str) -> Option<Option<String>>) -> StackHolder {
+	let mut stack = stack_state.stack().to_vec();
+
+	let target = match stack.pop() {
+		Some(iota) => match iota.value() {
+			Iota::Vec(vec) => *vec,
+			_ => return StackHolder::single(Err(invalid_type(&[iota], &[VEC]))),
+		},
+		None => return StackHolder::single(Err(ActionError::StackTooSmall { expected: 1, found: 0 })),
+	};
+
+	let mut state = stack_state.with_stack(stack);
+	if let Some(scene) = state.scene_mut() {
+		match target {
+			Left(position) => {
+				let pos = block_pos(position);
+				if let Some(block) = scene.blocks.get(pos).and_then(&change) {
+					scene.blocks.set(pos, block);
+				}
+			},
+			Right(_) => scene.blocks.forget(|block| change(block).is_some()),
+		}
+	}
+
+	StackHolder::single_state(state)
+}
+
+fn block_spell_signature() -> Option<Signature> { Some(Signature { inputs: vec![VEC], outputs: vec![] }) }
+
+/// The effect of a spell on the block at the position on top of the stack.
+fn block_spell_effect(kind: EffectKind, stack_state: &StackState) -> Option<SpellEffect> {
+	stack_state.stack().last().map(|target| SpellEffect::new(kind, target.value().clone(), None))
+}
+
+/// Break Block; breaks the block at a position, unless it is air, a fluid or unbreakable. Costs an eighth of a dust.
+pub struct BreakBlock;
+
+impl Action for BreakBlock {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		block_spell(stack_state, |block| is_breakable(block).then(|| Some("air".to_string())))
+	}
+
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(0.125 * DUST) }
+
+	fn signature(&self) -> Option<Signature> { block_spell_signature() }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> { block_spell_effect(EffectKind::BreakBlock, stack_state) }
+}
+
+/// Place Block; places the block the caster is holding (see [`crate::world::Caster::held_block`]) at a position, if
+/// the block there can be replaced. Costs an eighth of a dust.
+pub struct PlaceBlock;
+
+impl Action for PlaceBlock {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let held = stack_state.scene().and_then(|scene| scene.caster.as_ref()).and_then(|caster| caster.held_block.clone());
+		block_spell(stack_state, |block| is_replaceable(block).then(|| held.clone()))
+	}
+
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(0.125 * DUST) }
+
+	fn signature(&self) -> Option<Signature> { block_spell_signature() }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> { block_spell_effect(EffectKind::PlaceBlock, stack_state) }
+}
+
+/// Create Water; places a water source at a position, if the block there can be replaced. Costs one dust.
+pub struct CreateWater;
+
+impl Action for CreateWater {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		block_spell(stack_state, |block| (is_replaceable(block) && block != "water").then(|| Some("water".to_string())))
+	}
+
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(DUST) }
+
+	fn signature(&self) -> Option<Signature> { block_spell_signature() }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> { block_spell_effect(EffectKind::CreateWater, stack_state) }
+}
+
+/// Create Lava; places a lava source at a position, if the block there can be replaced. Costs a charged amethyst.
+pub struct CreateLava;
+
+impl Action for CreateLava {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		block_spell(stack_state, |block| (is_replaceable(block) && block != "lava").then(|| Some("lava".to_string())))
+	}
+
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(CHARGED_AMETHYST) }
+
+	fn signature(&self) -> Option<Signature> { block_spell_signature() }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> { block_spell_effect(EffectKind::CreateLava, stack_state) }
+}
+
+/// Conjure Block and Conjure Light; conjures a block (or a light, which raycasts pass through) at a position, if the
+/// block there can be replaced. Costs one dust.
+pub struct ConjureBlock {
+	pub light: bool
+}
+
+impl Action for ConjureBlock {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let conjured = if self.light { "conjured_light" } else { "conjured_block" };
+		block_spell(stack_state, |block| is_replaceable(block).then(|| Some(conjured.to_string())))
+	}
+
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(DUST) }
+
+	fn signature(&self) -> Option<Signature> { block_spell_signature() }
+
+	fn targets(&self) -> Vec<usize> { vec![0] }
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> {
+		block_spell_effect(if self.light { EffectKind::ConjureLight } else { EffectKind::ConjureBlock }, stack_state)
+	}
+}
@@ -0,0 +1,256 @@
+use crate::simulator::{Iota, IotaType, ConstLenAction, Action, ActionError, OutputType, Signature, StackState, StackHolder, MAX_STACK_LEN};
+use crate::symbolic::Op;
+use crate::version::profile;
+
+use super::{int_between, IntArg};
+
+/// Gemini Decomposition; duplicates the top iota.
+pub struct Duplicate;
+
+impl ConstLenAction for Duplicate {
+	fn len() -> usize { 1 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(0), OutputType::Input(0)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[0].clone(), iotas[0].clone()])]
+	}
+}
+
+/// Dioscuri Gambit; duplicates the top two iotas, keeping their order.
+pub struct TwoDup;
+
+impl ConstLenAction for TwoDup {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(0), OutputType::Input(1), OutputType::Input(0), OutputType::Input(1)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[0].clone(), iotas[1].clone(), iotas[0].clone(), iotas[1].clone()])]
+	}
+}
+
+/// Jester's Gambit; swaps the top two iotas.
+pub struct Swap;
+
+impl ConstLenAction for Swap {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(1), OutputType::Input(0)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[1].clone(), iotas[0].clone()])]
+	}
+}
+
+/// Rotation Gambit; brings the third iota from the top to the top.
+pub struct Rotate;
+
+impl ConstLenAction for Rotate {
+	fn len() -> usize { 3 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(1), OutputType::Input(2), OutputType::Input(0)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[1].clone(), iotas[2].clone(), iotas[0].clone()])]
+	}
+}
+
+/// Rotation Gambit II; moves the top iota down to third from the top.
+pub struct RotateReverse;
+
+impl ConstLenAction for RotateReverse {
+	fn len() -> usize { 3 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(2), OutputType::Input(0), OutputType::Input(1)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[2].clone(), iotas[0].clone(), iotas[1].clone()])]
+	}
+}
+
+/// Prospector's Gambit; copies the second iota from the top to the top.
+pub struct Over;
+
+impl ConstLenAction for Over {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(0), OutputType::Input(1), OutputType::Input(0)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[0].clone(), iotas[1].clone(), iotas[0].clone()])]
+	}
+}
+
+/// Undertaker's Gambit; copies the top iota to below the second iota from the top.
+pub struct Tuck;
+
+impl ConstLenAction for Tuck {
+	fn len() -> usize { 2 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Input(1), OutputType::Input(0), OutputType::Input(1)]) }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![iotas[1].clone(), iotas[0].clone(), iotas[1].clone()])]
+	}
+}
+
+/// Pushes an iota that is part of the hex itself, like the list of patterns made by an Introspection block.
+pub struct Push(pub Iota);
+
+impl ConstLenAction for Push {
+	fn len() -> usize { 0 }
+
+	fn op(&self) -> Option<Op> { None }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(IotaType::of(&self.0).only())]) }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![self.0.clone()])]
+	}
+}
+
+/// Gemini Gambit; pops a number n, then replaces the iota below it with n copies of itself.
+/// If n isn't known but is bounded, every count it could be is a possible outcome, as long as there aren't more than the
+/// branch cap (see [`StackState::branch_cap`]). Counts that would make the stack longer than [`MAX_STACK_LEN`] aren't
+/// simulated.
+pub struct DuplicateN;
+
+impl Action for DuplicateN {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let stack = stack_state.stack();
+
+		if stack.len() < 2 {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: 2, found: stack.len() }))
+		}
+
+		let duplicate = |n: i64| {
+			if stack.len() as i64 - 2 + n > MAX_STACK_LEN as i64 {
+				return Err(ActionError::Indeterminate)
+			}
+
+			let mut stack = stack[..stack.len() - 1].to_vec();
+			let iota = stack.pop().unwrap();
+			stack.extend(std::iter::repeat(iota).take(n as usize));
+			Ok(stack_state.with_stack(stack))
+		};
+
+		match int_between(&stack[stack.len() - 1], 0, 0, i64::MAX) {
+			Ok(IntArg::Known(n)) => StackHolder::single(duplicate(n)),
+			Ok(IntArg::Unknown { max: i64::MAX, .. }) => StackHolder::single(Err(ActionError::Indeterminate)),
+			Ok(IntArg::Unknown { min, max, .. }) if max - min >= stack_state.branch_cap() as i64 => StackHolder::single(Err(ActionError::TooManyPossibilities)),
+			Ok(IntArg::Unknown { min, max, may_mishap }) => {
+				let mut results: Vec<_> = (min..=max).map(duplicate).collect();
+				if may_mishap {
+					results.push(Err(ActionError::OutOfBounds { argument: 0 }));
+				}
+				StackHolder::new(results)
+			},
+			Err(action_error) => StackHolder::single(Err(action_error)),
+		}
+	}
+}
+
+/// Fisherman's Gambit; pops a number n, then moves the iota n deep in the stack (counting the top as 0) to the top.
+/// If n is negative, instead moves the top iota down -n places, unless the version's profile says it doesn't accept them
+/// (see [`crate::version::Profile::positive_fisherman`]).
+/// Fisherman's Gambit II does the same, but copies the iota instead of moving it, and doesn't accept negative numbers.
+/// If n isn't known, every depth it could be is a possible outcome.
+pub struct Fisherman {
+	pub copy: bool
+}
+
+impl Action for Fisherman {
+	fn name(&self) -> String { if self.copy { "fisherman_copy" } else { "fisherman" }.to_string() }
+
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let stack = stack_state.stack();
+
+		if stack.len() < 2 {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: 2, found: stack.len() }))
+		}
+
+		let (depth, rest) = stack.split_last().unwrap();
+		let max_depth = rest.len() as i64 - 1;
+		let min_depth = if self.copy || !profile().negative_fisherman() { 0 } else { -max_depth };
+
+		let fish = |depth: i64| {
+			let mut stack = rest.to_vec();
+			if depth >= 0 {
+				let index = stack.len() - 1 - depth as usize;
+				let iota = if self.copy { stack[index].clone() } else { stack.remove(index) };
+				stack.push(iota);
+			} else {
+				let iota = stack.pop().unwrap();
+				stack.insert(stack.len() - (-depth) as usize, iota);
+			}
+			Ok(stack_state.with_stack(stack))
+		};
+
+		match int_between(depth, 0, min_depth, max_depth) {
+			Ok(IntArg::Known(depth)) => StackHolder::single(fish(depth)),
+			Ok(IntArg::Unknown { min, max, may_mishap }) => {
+				let mut results: Vec<_> = (min..=max).map(fish).collect();
+				if may_mishap {
+					results.push(Err(ActionError::OutOfBounds { argument: 0 }));
+				}
+				StackHolder::new(results)
+			},
+			Err(action_error) => StackHolder::single(Err(action_error)),
+		}
+	}
+}
+
+/// Flock's Reflection; pushes the number of iotas on the stack.
+pub struct StackSize;
+
+impl Action for StackSize {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let mut stack = stack_state.stack().to_vec();
+		stack.push((stack.len() as f64).into());
+		StackHolder::single_state(stack_state.with_stack(stack))
+	}
+
+	fn signature(&self) -> Option<Signature> { Some(Signature { inputs: vec![], outputs: vec![OutputType::Types(&[IotaType::Double])] }) }
+}
+
+/// Bookkeeper's Gadget; the mask covers the top `mask.len()` iotas, deepest first, with true keeping
+/// the iota and false removing it.
+pub struct Mask(pub Vec<bool>);
+
+impl Action for Mask {
+	fn apply(&self, stack_state: &StackState) -> StackHolder {
+		let stack = stack_state.stack();
+
+		if stack.len() < self.0.len() {
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: self.0.len(), found: stack.len() }))
+		}
+
+		let split = stack.len() - self.0.len();
+		let mut result = stack[..split].to_vec();
+		result.extend(stack[split..].iter().zip(self.0.iter()).filter(|(_, keep)| **keep).map(|(iota, _)| iota.clone()));
+
+		StackHolder::single_state(stack_state.with_stack(result))
+	}
+
+	fn signature(&self) -> Option<Signature> {
+		Some(Signature {
+			inputs: vec![IotaType::ANY; self.0.len()],
+			outputs: self.0.iter().enumerate().filter(|(_, keep)| **keep).map(|(i, _)| OutputType::Input(i)).collect()
+		})
+	}
+}
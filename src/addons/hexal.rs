@@ -0,0 +1,165 @@
+//! Hexal: maths patterns, the game time, iota types, and gates (which wisps and casters are teleported through).
+
+use itertools::Either::{Left, Right};
+
+use crate::actions::{int_between, invalid_type, IntArg, DOUBLE, LIST};
+use crate::interval::Interval;
+use crate::simulator::{ActionError, ConstLenAction, Iota, IotaList, IotaType, OutputType};
+
+use super::{Addon, AddonIota, AddonRegistry, IotaKind};
+
+/// Factorials above this are infinite as doubles.
+const MAX_FACTORIAL: i64 = 170;
+
+pub struct Hexal;
+
+impl Addon for Hexal {
+	fn name(&self) -> &'static str { "hexal" }
+
+	fn register(&self, registry: &mut AddonRegistry) {
+		registry.pattern("ddwaa", "timekeeper", || Box::new(Timekeeper));
+		registry.pattern("wawdedwaw", "factorial", || Box::new(Factorial));
+		registry.pattern("aea", "running_sum", || Box::new(Running { product: false }));
+		registry.pattern("qaawaaq", "running_product", || Box::new(Running { product: true }));
+		registry.pattern("awd", "classifier", || Box::new(Classifier));
+
+		registry.iota_kind(IotaTypeKind);
+		registry.iota_kind(Gate);
+	}
+}
+
+/// Timekeeper's Reflection; pushes the number of ticks the world has existed for, which the simulator doesn't know.
+pub struct Timekeeper;
+
+impl ConstLenAction for Timekeeper {
+	fn len() -> usize { 0 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE)]) }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Interval::integer(0.0, f64::INFINITY).into()])]
+	}
+}
+
+fn factorial(n: i64) -> f64 { (1..=n).map(|i| i as f64).product() }
+
+/// Factorial Purification; the factorial of a whole number, which must not be negative.
+pub struct Factorial;
+
+impl ConstLenAction for Factorial {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match int_between(&iotas[0], 0, 0, i64::MAX) {
+			Ok(IntArg::Known(n)) => vec![Ok(vec![factorial(n.min(MAX_FACTORIAL + 1)).into()])],
+			Ok(IntArg::Unknown { min, max, may_mishap }) => {
+				let upper = if max > MAX_FACTORIAL { f64::INFINITY } else { factorial(max) };
+				let mut results = vec![Ok(vec![Interval::integer(factorial(min.min(MAX_FACTORIAL + 1)), upper).into()])];
+				if may_mishap {
+					results.push(Err(ActionError::OutOfBounds { argument: 0 }));
+				}
+				results
+			},
+			Err(ActionError::InvalidType { .. }) => vec![Err(invalid_type(iotas, &[DOUBLE]))],
+			Err(action_error) => vec![Err(action_error)],
+		}
+	}
+}
+
+/// Running Sum and Running Product Purifications; replace each number in a list with the sum (or product) of it and
+/// every number before it.
+pub struct Running {
+	pub product: bool
+}
+
+impl ConstLenAction for Running {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![LIST] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		let list = match &iotas[0] {
+			Iota::List(list) => list,
+			_ => return vec![Err(invalid_type(iotas, &[LIST]))],
+		};
+
+		let items = match &list.0 {
+			Left(items) => items,
+			Right(len) => {
+				let element = Iota::Double(Right(Interval::unbounded()));
+				return vec![Ok(vec![IotaList::unknown(*len, Some(element)).into()])]
+			},
+		};
+
+		let mut total = Interval::point(if self.product { 1.0 } else { 0.0 });
+		let mut running = vec![];
+		for item in items {
+			let interval = match item.value() {
+				Iota::Double(d) => Interval::from_double(d),
+				_ => return vec![Err(ActionError::InvalidType { argument: 0, expected: LIST, found: IotaType::of(item) })],
+			};
+			total = if self.product { total.mul(&interval) } else { total.add(&interval) };
+			running.push(total.into());
+		}
+
+		vec![Ok(vec![running.into()])]
+	}
+}
+
+/// Classifier's Purification; pushes the type of an iota.
+pub struct Classifier;
+
+impl ConstLenAction for Classifier {
+	fn len() -> usize { 1 }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(IotaType::Addon.only())]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match IotaType::of(&iotas[0]) {
+			IotaType::Unknown => vec![Ok(vec![Iota::Unknown])],
+			iota_type => vec![Ok(vec![Iota::Addon(AddonIota::new("hexal:iota_type", iota_type.name()))])],
+		}
+	}
+}
+
+/// The type of an iota, stored as its name (e.g. "number").
+pub struct IotaTypeKind;
+
+impl IotaKind for IotaTypeKind {
+	fn name(&self) -> &'static str { "hexal:iota_type" }
+
+	fn display(&self, data: &str) -> String { format!("Type: {}", data) }
+
+	fn parse(&self, text: &str) -> Option<String> {
+		let text = text.trim();
+		let name = text.strip_prefix("Type:").unwrap_or(text).trim();
+		IotaType::ANY.iter().find(|iota_type| iota_type.name() == name).map(|iota_type| iota_type.name().to_string())
+	}
+}
+
+/// A gate, stored as its number.
+pub struct Gate;
+
+impl IotaKind for Gate {
+	fn name(&self) -> &'static str { "hexal:gate" }
+
+	fn display(&self, data: &str) -> String { format!("Gate {}", data) }
+
+	fn parse(&self, text: &str) -> Option<String> {
+		let text = text.trim();
+		let number = text.strip_prefix("Gate").unwrap_or(text).trim();
+		number.parse::<u64>().ok().map(|number| number.to_string())
+	}
+
+	fn serialize(&self, data: &str) -> serde_json::Value {
+		data.parse::<u64>().map_or(serde_json::Value::String(data.to_string()), serde_json::Value::from)
+	}
+
+	fn deserialize(&self, value: &serde_json::Value) -> Option<String> { value.as_u64().map(|number| number.to_string()) }
+}
@@ -0,0 +1,145 @@
+//! Patterns, actions and kinds of iota added by Hex Casting addons. Each addon pack is an [`Addon`] that registers what
+//! it adds with an [`AddonRegistry`]; the packs shipped with hexedit are behind cargo features of the same name, and are
+//! installed on every thread's registry. Other packs can be installed with [`install`].
+//!
+//! No pack is shipped for Hexical yet: its patterns' signatures and behaviour haven't been checked against the addon,
+//! and a pack that guesses them would report hexes as casting differently from the game. Servers running it can
+//! install a pack of their own until one is added here.
+
+#[cfg(feature = "hexal")]
+pub mod hexal;
+#[cfg(feature = "moreiotas")]
+pub mod moreiotas;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::simulator::Action;
+
+/// An addon pack.
+pub trait Addon {
+	/// The name of the addon, e.g. "hexal".
+	fn name(&self) -> &'static str;
+
+	/// Registers every pattern and kind of iota the addon adds.
+	fn register(&self, registry: &mut AddonRegistry);
+}
+
+/// How hexedit handles a kind of iota added by an addon. An [`AddonIota`] stores its value as a string, in whatever
+/// encoding the kind chooses; the hooks convert that to and from what is displayed, typed and written to files.
+pub trait IotaKind {
+	/// The name of the kind, namespaced by the addon, e.g. "hexal:gate". Written to files to say what kind an iota is.
+	fn name(&self) -> &'static str;
+
+	/// Displays the value the way it is shown on the stack.
+	fn display(&self, data: &str) -> String;
+
+	/// Reads a value from how it is typed in, e.g. in an input stack file; None if the text isn't a value of this kind.
+	fn parse(&self, text: &str) -> Option<String>;
+
+	/// Writes the value as JSON; as a string holding it by default.
+	fn serialize(&self, data: &str) -> serde_json::Value { serde_json::Value::String(data.to_string()) }
+
+	/// Reads a value written by [`IotaKind::serialize`], or None if the JSON isn't a value of this kind.
+	fn deserialize(&self, value: &serde_json::Value) -> Option<String> { value.as_str().map(str::to_string) }
+}
+
+/// An iota of a kind added by an addon. Kinds that aren't registered (e.g. read from a file written with an addon
+/// pack this build doesn't have) keep their value as the JSON it was read from.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct AddonIota {
+	pub kind: String,
+	pub data: String
+}
+
+impl AddonIota {
+	pub fn new(kind: &str, data: &str) -> AddonIota { AddonIota { kind: kind.to_string(), data: data.to_string() } }
+
+	/// Displays the iota with its kind's hook, or as its kind and value if the kind isn't registered.
+	pub fn display(&self) -> String {
+		match with_registry(|registry| registry.kind(&self.kind)) {
+			Some(kind) => kind.display(&self.data),
+			None => format!("{}({})", self.kind, self.data),
+		}
+	}
+}
+
+/// A pattern added by an addon.
+pub struct AddonPattern {
+	pub addon: &'static str,
+	pub name: &'static str,
+	action: Option<fn() -> Box<dyn Action>>
+}
+
+/// Everything the installed addon packs add.
+#[derive(Default)]
+pub struct AddonRegistry {
+	addons: Vec<&'static str>,
+	/// the addon whose [`Addon::register`] is being called.
+	registering: &'static str,
+	patterns: HashMap<String, AddonPattern>,
+	kinds: HashMap<&'static str, Rc<dyn IotaKind>>
+}
+
+impl AddonRegistry {
+	/// A registry with every addon pack enabled by cargo features installed.
+	pub fn with_features() -> AddonRegistry {
+		#[allow(unused_mut)]
+		let mut registry = AddonRegistry::default();
+		#[cfg(feature = "hexal")]
+		registry.install(&hexal::Hexal);
+		#[cfg(feature = "moreiotas")]
+		registry.install(&moreiotas::MoreIotas);
+		registry
+	}
+
+	/// Installs the addon, unless an addon with the same name already is.
+	pub fn install(&mut self, addon: &dyn Addon) {
+		if self.addons.contains(&addon.name()) {
+			return
+		}
+
+		self.addons.push(addon.name());
+		self.registering = addon.name();
+		addon.register(self);
+	}
+
+	/// Registers a pattern (given by its angle signature) that the simulator casts as the action. Patterns Hex Casting
+	/// itself has take priority over addon patterns with the same signature.
+	pub fn pattern(&mut self, signature: &str, name: &'static str, action: fn() -> Box<dyn Action>) {
+		self.patterns.insert(signature.to_string(), AddonPattern { addon: self.registering, name, action: Some(action) });
+	}
+
+	/// Registers a pattern by name only, for one the simulator can't cast (e.g. because it depends on the world).
+	pub fn named_pattern(&mut self, signature: &str, name: &'static str) {
+		self.patterns.insert(signature.to_string(), AddonPattern { addon: self.registering, name, action: None });
+	}
+
+	pub fn iota_kind(&mut self, kind: impl IotaKind + 'static) {
+		self.kinds.insert(kind.name(), Rc::new(kind));
+	}
+
+	/// The names of the installed addons, in the order they were installed.
+	pub fn addons(&self) -> &[&'static str] { &self.addons }
+
+	pub fn pattern_for(&self, signature: &str) -> Option<&AddonPattern> { self.patterns.get(signature) }
+
+	/// The action cast by the addon pattern with the angle signature, if it is registered with one.
+	pub fn action_for(&self, signature: &str) -> Option<Box<dyn Action>> {
+		self.patterns.get(signature).and_then(|pattern| pattern.action).map(|action| action())
+	}
+
+	pub fn kind(&self, name: &str) -> Option<Rc<dyn IotaKind>> { self.kinds.get(name).cloned() }
+}
+
+thread_local! {
+	/// The registry every part of hexedit looks addon patterns and iotas up in.
+	static REGISTRY: RefCell<AddonRegistry> = RefCell::new(AddonRegistry::with_features());
+}
+
+/// Looks something up in the registry of installed addons.
+pub fn with_registry<T>(f: impl FnOnce(&AddonRegistry) -> T) -> T { REGISTRY.with(|registry| f(&registry.borrow())) }
+
+/// Installs an addon pack on this thread, for packs that aren't shipped with hexedit.
+pub fn install(addon: &dyn Addon) { REGISTRY.with(|registry| registry.borrow_mut().install(addon)) }
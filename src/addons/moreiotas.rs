@@ -0,0 +1,238 @@
+//! MoreIotas: strings and matrices (see [`Iota::String`] and [`Iota::Matrix`]), and the patterns that work on them.
+//!
+//! Strings and matrices are `Iota` variants rather than kinds registered through [`IotaKind`], so that, like doubles,
+//! they can be unknown (or, for matrices, have unknown dimensions) and be checked by the linter; an [`AddonIota`]'s
+//! value is always known. The variants are there without this feature, so that files holding them can be read by any
+//! build; the feature adds the pack. The pack registers [`StringKind`], for strings written as addon iotas.
+//!
+//! The pack doesn't register the actions below with any patterns yet. MoreIotas mostly overloads Hex Casting's own
+//! patterns (e.g. arithmetic on matrices) rather than adding new ones, and which patterns it overloads, and the
+//! signatures of those it does add, haven't been checked against the addon; as with Hexical (see [`crate::addons`]), a
+//! guessed table would report hexes as casting differently from the game. Until then, the actions can be registered by
+//! a pack of a server's own.
+//!
+//! [`AddonIota`]: super::AddonIota
+
+use itertools::Either::{Left, Right};
+
+use crate::actions::{invalid_type, DOUBLE, LIST};
+use crate::interval::Interval;
+use crate::simulator::{matrix_dimensions, ActionError, ConstLenAction, Iota, IotaList, IotaType, OutputType};
+
+use super::{Addon, AddonRegistry, IotaKind};
+
+const STRING: &[IotaType] = &[IotaType::String];
+const MATRIX: &[IotaType] = &[IotaType::Matrix];
+
+pub struct MoreIotas;
+
+impl Addon for MoreIotas {
+	fn name(&self) -> &'static str { "moreiotas" }
+
+	fn register(&self, registry: &mut AddonRegistry) {
+		registry.iota_kind(StringKind);
+	}
+}
+
+/// A string of text, as an addon iota.
+pub struct StringKind;
+
+impl IotaKind for StringKind {
+	fn name(&self) -> &'static str { "moreiotas:string" }
+
+	fn display(&self, data: &str) -> String { format!("{:?}", data) }
+
+	/// Reads a string in double quotes, with the same escapes as JSON.
+	fn parse(&self, text: &str) -> Option<String> { serde_json::from_str::<String>(text.trim()).ok() }
+}
+
+/// Concatenation Distillation; joins two strings.
+pub struct Concat;
+
+impl ConstLenAction for Concat {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![STRING, STRING] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(STRING)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::String(Some(s0)), Iota::String(Some(s1))) => Ok(vec![format!("{}{}", s0, s1).into()]),
+				(Iota::String(_), Iota::String(_)) => Ok(vec![Iota::String(None)]),
+				_ => Err(invalid_type(iotas, &[STRING, STRING])),
+			}
+		]
+	}
+}
+
+/// Separation Distillation; splits a string at every occurrence of a separator, or into its characters if the separator
+/// is empty.
+pub struct Split;
+
+impl ConstLenAction for Split {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![STRING, STRING] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(LIST)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match (&iotas[0], &iotas[1]) {
+				(Iota::String(Some(string)), Iota::String(Some(separator))) => {
+					let parts: Vec<Iota> = if separator.is_empty() {
+						string.chars().map(|c| c.to_string().into()).collect()
+					} else {
+						string.split(separator.as_str()).map(|part| part.to_string().into()).collect()
+					};
+					Ok(vec![parts.into()])
+				},
+				(Iota::String(_), Iota::String(_)) => Ok(vec![IotaList::unknown(None, Some(Iota::String(None))).into()]),
+				_ => Err(invalid_type(iotas, &[STRING, STRING])),
+			}
+		]
+	}
+}
+
+/// Scrivener's Purification; writes a number as a string, the way it is shown on the stack.
+pub struct NumberToString;
+
+impl ConstLenAction for NumberToString {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![DOUBLE] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(STRING)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Double(Left(_)) => Ok(vec![iotas[0].to_string().into()]),
+				Iota::Double(Right(_)) => Ok(vec![Iota::String(None)]),
+				_ => Err(invalid_type(iotas, &[DOUBLE])),
+			}
+		]
+	}
+}
+
+/// The numbers of rows and columns of a matrix, where they are known, or None if the iota isn't a matrix.
+fn dimensions(iota: &Iota) -> Option<[Option<usize>; 2]> {
+	match iota {
+		Iota::Matrix(matrix) => Some(matrix_dimensions(matrix)),
+		_ => None,
+	}
+}
+
+/// Adds a branch that mishaps with [`ActionError::WrongDimensions`] if `may_mishap`.
+fn or_wrong_dimensions(result: Vec<Iota>, may_mishap: bool, argument: usize) -> Vec<Result<Vec<Iota>, ActionError>> {
+	let mut results = vec![Ok(result)];
+	if may_mishap {
+		results.push(Err(ActionError::WrongDimensions { argument }));
+	}
+	results
+}
+
+/// Multiplicative Distillation for matrices; multiplies an n×m matrix by an m×p one.
+pub struct MatrixMul;
+
+impl ConstLenAction for MatrixMul {
+	fn len() -> usize { 2 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![MATRIX, MATRIX] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(MATRIX)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match (&iotas[0], &iotas[1]) {
+			(Iota::Matrix(Left(m0)), Iota::Matrix(Left(m1))) => vec![m0.mul(m1).map(|product| vec![product.into()]).ok_or(ActionError::WrongDimensions { argument: 0 })],
+			_ => match (dimensions(&iotas[0]), dimensions(&iotas[1])) {
+				(Some([rows, inner0]), Some([inner1, columns])) => match (inner0, inner1) {
+					(Some(inner0), Some(inner1)) if inner0 != inner1 => vec![Err(ActionError::WrongDimensions { argument: 0 })],
+					_ => or_wrong_dimensions(vec![Iota::Matrix(Right((rows, columns)))], inner0.is_none() || inner1.is_none(), 0),
+				},
+				_ => vec![Err(invalid_type(iotas, &[MATRIX, MATRIX]))],
+			},
+		}
+	}
+}
+
+/// Transposition Purification; swaps the rows and columns of a matrix.
+pub struct Transpose;
+
+impl ConstLenAction for Transpose {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![MATRIX] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(MATRIX)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![
+			match &iotas[0] {
+				Iota::Matrix(Left(matrix)) => Ok(vec![matrix.transpose().into()]),
+				Iota::Matrix(Right((rows, columns))) => Ok(vec![Iota::Matrix(Right((*columns, *rows)))]),
+				_ => Err(invalid_type(iotas, &[MATRIX])),
+			}
+		]
+	}
+}
+
+/// Whether a matrix with the dimensions is square: Some(true) or Some(false) if that's known, None if it isn't.
+fn is_square(dimensions: [Option<usize>; 2]) -> Option<bool> {
+	match dimensions {
+		[Some(rows), Some(columns)] => Some(rows == columns),
+		_ => None,
+	}
+}
+
+/// Determinant Purification; the determinant of a square matrix.
+pub struct Determinant;
+
+impl ConstLenAction for Determinant {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![MATRIX] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(DOUBLE)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match &iotas[0] {
+			Iota::Matrix(Left(matrix)) => vec![matrix.determinant().map(|d| vec![d.into()]).ok_or(ActionError::WrongDimensions { argument: 0 })],
+			Iota::Matrix(matrix) => match is_square(matrix_dimensions(matrix)) {
+				Some(false) => vec![Err(ActionError::WrongDimensions { argument: 0 })],
+				square => or_wrong_dimensions(vec![Interval::unbounded().into()], square.is_none(), 0),
+			},
+			_ => vec![Err(invalid_type(iotas, &[MATRIX]))],
+		}
+	}
+}
+
+/// Inversion Purification; the inverse of a square matrix, mishapping as if dividing by zero if it has none.
+pub struct Inverse;
+
+impl ConstLenAction for Inverse {
+	fn len() -> usize { 1 }
+
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![MATRIX] }
+
+	fn output_types(&self) -> Option<Vec<OutputType>> { Some(vec![OutputType::Types(MATRIX)]) }
+
+	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		match &iotas[0] {
+			Iota::Matrix(Left(matrix)) if matrix.rows() != matrix.columns() => vec![Err(ActionError::WrongDimensions { argument: 0 })],
+			Iota::Matrix(Left(matrix)) => vec![matrix.inverse().map(|inverse| vec![inverse.into()]).ok_or(ActionError::DivByZero)],
+			Iota::Matrix(matrix) => match (is_square(matrix_dimensions(matrix)), matrix_dimensions(matrix)) {
+				(Some(false), _) => vec![Err(ActionError::WrongDimensions { argument: 0 })],
+				(square, [rows, columns]) => {
+					let size = rows.or(columns);
+					let mut results = or_wrong_dimensions(vec![Iota::Matrix(Right((size, size)))], square.is_none(), 0);
+					results.push(Err(ActionError::DivByZero));
+					results
+				},
+			},
+			_ => vec![Err(invalid_type(iotas, &[MATRIX]))],
+		}
+	}
+}
@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use itertools::Either::{self, Left, Right};
+
+use crate::hex_pattern::HexPattern;
+use crate::serialization::{from_json, to_json, FormatError};
+use crate::simulator::Iota;
+use crate::world::{block_pos, BlockPos};
+
+/// An iota stored in an Akashic Library, and the version of the library it was last written at.
+#[derive(Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Entry {
+	pub key: HexPattern,
+	pub value: Iota,
+	#[serde(default)]
+	pub version: u64
+}
+
+impl fmt::Debug for Entry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} => {} (version {})", self.key.angle_signature(), self.value, self.version)
+	}
+}
+
+/// An Akashic Library: iotas stored under patterns, which Akashic Read and Akashic Write read and write through the
+/// library's record. Keys are compared by their angle signatures, the way Hex Casting compares them, so a pattern drawn
+/// in any direction reads the same entry.
+///
+/// Every write bumps the library's version, and the entry written remembers it. A branch that writes to the library
+/// changes its own copy (see [`crate::world::Scene::library`]), so each branch counts its own writes.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "LibraryRepr", into = "LibraryRepr")]
+pub struct Library {
+	/// the block the library's record is in, or None if the simulator should accept any position as the record's.
+	pub record: Option<BlockPos>,
+	/// by the angle signatures of their keys.
+	entries: BTreeMap<String, Entry>,
+	version: u64
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct LibraryRepr {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	record: Option<BlockPos>,
+	#[serde(default)]
+	version: u64,
+	#[serde(default)]
+	entries: Vec<Entry>
+}
+
+impl From<LibraryRepr> for Library {
+	fn from(repr: LibraryRepr) -> Self {
+		let version = repr.entries.iter().map(|entry| entry.version).fold(repr.version, u64::max);
+		let entries = repr.entries.into_iter().map(|entry| (entry.key.angle_signature(), entry)).collect();
+		Library { record: repr.record, entries, version }
+	}
+}
+
+impl From<Library> for LibraryRepr {
+	fn from(library: Library) -> Self {
+		LibraryRepr { record: library.record, version: library.version, entries: library.entries.into_values().collect() }
+	}
+}
+
+impl Library {
+	/// Reads a library from a file written by [`Library::save`].
+	pub fn load(path: &Path) -> Result<Library, FormatError> {
+		let json = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+		from_json(&json)
+	}
+
+	/// Writes the library in the versioned JSON format of [`crate::serialization`].
+	pub fn save(&self, path: &Path) -> Result<(), FormatError> {
+		std::fs::write(path, to_json(self)).map_err(|error| FormatError::Io(error.to_string()))
+	}
+
+	/// How many writes the library has had.
+	pub fn version(&self) -> u64 { self.version }
+
+	pub fn get(&self, key: &HexPattern) -> Option<&Entry> { self.entries.get(&key.angle_signature()) }
+
+	/// Stores the iota under the key, replacing what was there, and bumps the version.
+	pub fn write(&mut self, key: HexPattern, value: Iota) {
+		self.version += 1;
+		self.entries.insert(key.angle_signature(), Entry { key, value, version: self.version });
+	}
+
+	/// Removes the entry stored under the key, returning it if there was one.
+	pub fn remove(&mut self, key: &HexPattern) -> Option<Entry> { self.entries.remove(&key.angle_signature()) }
+
+	/// Every entry, in the order of their keys' angle signatures.
+	pub fn entries(&self) -> impl Iterator<Item = &Entry> + '_ { self.entries.values() }
+
+	pub fn len(&self) -> usize { self.entries.len() }
+
+	pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+
+	/// The entries written since the library was at version `version`.
+	pub fn written_since(&self, version: u64) -> impl Iterator<Item = &Entry> + '_ {
+		self.entries().filter(move |entry| entry.version > version)
+	}
+
+	/// Whether the library's record is in the block at the position, or None if the position isn't known.
+	pub fn is_record_at(&self, position: &Either<(f64, f64, f64), bool>) -> Option<bool> {
+		match (self.record, position) {
+			(None, _) => Some(true),
+			(Some(record), Left(position)) => Some(block_pos(*position) == record),
+			(Some(_), Right(_)) => None,
+		}
+	}
+}
@@ -1,10 +1,32 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::rc::Rc;
+
 use eframe::emath;
 use eframe::epaint::CircleShape;
-use egui::{Rect, Color32, Shape};
+use egui::{Rect, Color32, Shape, Stroke};
+use egui::text::{LayoutJob, TextFormat};
 use itertools::join;
 
+use crate::akashic::{Entry, Library};
+use crate::circle::{Circle, CircleBlock, Direction, Wave};
+use crate::actions::registry::{pattern_name, run_hex_from};
+use crate::addons::with_registry;
+use crate::debugger::Debugger;
 use crate::hex_pattern::*;
-use crate::parsing::parse_to_list;
+use crate::history::StackChange;
+use crate::interval::Interval;
+use crate::items::Item;
+use crate::media::{describe_cost, describe_dust, DUST};
+use crate::numbers::{set_number_format, NumberFormat};
+use crate::parsing::{parse_to_hex_pattern, parse_to_list, pattern_spans};
+use crate::rendering::Renderable;
+use crate::serialization::{as_json, from_json, to_json};
+use crate::simulator::{Iota, Mishap, StackHolder, StackManager, StackState};
+use crate::stack_effect::{infer, introspection_blocks};
+use crate::type_check::check;
+use crate::version::{set_profile, GameVersion, Profile};
+use crate::world::{is_air, BlockPos, Blocks, Caster, Casting, Scene};
 
 const NODE_EMPTY_SQR_RADIUS: f32 = 0.5*0.5; // distance from current node inside which state shouldn't change.
 const NODE_SELECT_NEXT_SQR_RADIUS: f32 = 1.5*1.5; // distance from current node outside which should attempt to connect to next node.
@@ -29,6 +51,25 @@ pub struct HexeditApp {
 
 		pattern_text: String,
 		canonical_text: String,
+		/// how much media the hex may use before the cost is shown as a warning, in dust.
+		media_budget: f64,
+		version_panel: VersionPanel,
+		/// how numbers on the stack are displayed.
+		number_format: NumberFormat,
+		input_stacks: InputStacks,
+		scene_file: SceneFile,
+		caster_panel: CasterPanel,
+		#[serde(skip)]
+		block_view: BlockView,
+		library_browser: LibraryBrowser,
+		circle_view: CircleView,
+		/// the indices of the patterns the debugger pauses at.
+		breakpoints: BTreeSet<usize>,
+		#[serde(skip)]
+		debugger: Option<Debugger>,
+		/// the step of the debugger's history being looked at, if it isn't the latest one.
+		#[serde(skip)]
+		timeline: Option<usize>,
 		#[serde(skip)]
 		drawing_pattern: Option<HexPattern>,
 		#[serde(skip)]
@@ -47,6 +88,18 @@ impl Default for HexeditApp {
             are_drawing: false,
 						pattern_text: "HexPattern(aqweqad, NORTH_WEST)".to_string(),
 						canonical_text: "".to_string(),
+						media_budget: 10.0,
+						version_panel: VersionPanel::default(),
+						number_format: NumberFormat::default(),
+						input_stacks: InputStacks::default(),
+						scene_file: SceneFile::default(),
+						caster_panel: CasterPanel::default(),
+						block_view: BlockView::default(),
+						library_browser: LibraryBrowser::default(),
+						circle_view: CircleView::default(),
+						breakpoints: BTreeSet::new(),
+						debugger: None,
+						timeline: None,
 						drawing_pattern: None,
 						start_draw_node: None,
 						last_draw_node: None,
@@ -73,6 +126,1110 @@ impl HexeditApp {
     }
 }
 
+/// Named stacks the hex can be simulated from, instead of an empty stack.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct InputStacks {
+	#[serde(with = "as_json")]
+	stacks: BTreeMap<String, StackState>,
+	/// the name of the stack the hex is simulated from, or None for an empty stack.
+	selected: Option<String>,
+	#[serde(skip)]
+	name: String,
+	/// the JSON of the stack being edited.
+	#[serde(skip)]
+	json: String,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl InputStacks {
+	/// The stack with the given name, or an empty stack for None.
+	fn stack(&self, name: &Option<String>) -> StackState {
+		name.as_ref().and_then(|name| self.stacks.get(name)).cloned().unwrap_or_default()
+	}
+
+	/// The state the hex is simulated from.
+	fn input(&self) -> StackState { self.stack(&self.selected) }
+
+	/// Selects the stack and loads it into the editor.
+	fn select(&mut self, selected: Option<String>) {
+		self.json = to_json(&self.stack(&selected));
+		self.name = selected.clone().unwrap_or_default();
+		self.selected = selected;
+		self.error = None;
+	}
+
+	/// Shows the stacks to pick from, and an editor for the JSON of a stack that can be saved under a name.
+	fn show(&mut self, ui: &mut egui::Ui) {
+		if self.json.is_empty() {
+			self.json = to_json(&self.input());
+		}
+
+		let mut selected = self.selected.clone();
+
+		egui::ComboBox::from_label("Input stack")
+			.selected_text(selected.clone().unwrap_or_else(|| "Empty".to_string()))
+			.show_ui(ui, |ui| {
+				ui.selectable_value(&mut selected, None, "Empty");
+				for name in self.stacks.keys() {
+					ui.selectable_value(&mut selected, Some(name.clone()), name);
+				}
+			});
+
+		if selected != self.selected {
+			self.select(selected);
+		}
+
+		egui::CollapsingHeader::new("Edit input stacks").show(ui, |ui| {
+			ui.add(egui::TextEdit::multiline(&mut self.json).font(egui::TextStyle::Monospace).desired_rows(4));
+
+			ui.horizontal(|ui| {
+				ui.text_edit_singleline(&mut self.name);
+
+				if ui.add_enabled(!self.name.is_empty(), egui::Button::new("Save")).clicked() {
+					match from_json::<StackState>(&self.json) {
+						Ok(state) => {
+							self.stacks.insert(self.name.clone(), state);
+							self.select(Some(self.name.clone()));
+						},
+						Err(format_error) => self.error = Some(format_error.to_string()),
+					}
+				}
+
+				if ui.add_enabled(self.stacks.contains_key(&self.name), egui::Button::new("Delete")).clicked() {
+					self.stacks.remove(&self.name);
+					self.select(None);
+				}
+			});
+
+			if let Some(error) = &self.error {
+				ui.colored_label(ERROR_COLOUR, error);
+			}
+		});
+	}
+}
+
+/// The version of Hex Casting hexes are simulated in, along with the profile last loaded from a file, which is used
+/// instead of the version's built-in profile until another version is selected.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct VersionPanel {
+	version: GameVersion,
+	path: String,
+	#[serde(with = "as_json")]
+	profile: Option<Profile>,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl VersionPanel {
+	fn show(&mut self, ui: &mut egui::Ui) {
+		let mut version = self.profile.as_ref().map_or(self.version, |profile| profile.version);
+		egui::ComboBox::from_label("Hex Casting version")
+			.selected_text(version.to_string())
+			.show_ui(ui, |ui| {
+				for option in GameVersion::ALL {
+					ui.selectable_value(&mut version, option, option.to_string());
+				}
+			});
+		if version != self.profile.as_ref().map_or(self.version, |profile| profile.version) {
+			self.version = version;
+			self.profile = None;
+		}
+
+		ui.horizontal(|ui| {
+			ui.label("Profile file");
+			ui.text_edit_singleline(&mut self.path);
+
+			if ui.button("Load").clicked() {
+				match Profile::load(Path::new(&self.path)) {
+					Ok(profile) => {
+						self.profile = Some(profile);
+						self.error = None;
+					},
+					Err(format_error) => self.error = Some(format_error.to_string()),
+				}
+			}
+		});
+
+		match (&self.error, &self.profile) {
+			(Some(error), _) => { ui.colored_label(ERROR_COLOUR, error); },
+			(None, Some(profile)) => {
+				ui.label(format!(
+					"Loaded profile: {} missing patterns, {} changed costs.", profile.missing_patterns.len(), profile.costs.len()
+				));
+			},
+			(None, None) => {},
+		}
+
+		set_profile(self.profile.clone().unwrap_or_else(|| Profile::builtin(self.version)));
+	}
+}
+
+/// The scene file the hex is simulated in, and the scene last loaded from it.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct SceneFile {
+	path: String,
+	/// the voxel file the blocks of the scene are loaded from, replacing any blocks in the scene file.
+	blocks_path: String,
+	/// the scene last loaded, or None if no scene has been loaded.
+	#[serde(with = "as_json")]
+	scene: Option<Rc<Scene>>,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl SceneFile {
+	fn show(&mut self, ui: &mut egui::Ui) {
+		ui.horizontal(|ui| {
+			ui.label("Scene file");
+			ui.text_edit_singleline(&mut self.path);
+
+			if ui.button("Load").clicked() {
+				match Scene::load(Path::new(&self.path)) {
+					Ok(scene) => {
+						self.scene = Some(Rc::new(scene));
+						self.error = None;
+					},
+					Err(format_error) => self.error = Some(format_error.to_string()),
+				}
+			}
+		});
+
+		ui.horizontal(|ui| {
+			ui.label("Blocks file");
+			ui.text_edit_singleline(&mut self.blocks_path);
+
+			if ui.button("Load").clicked() {
+				match Blocks::load(Path::new(&self.blocks_path)) {
+					Ok(blocks) => {
+						Rc::make_mut(self.scene.get_or_insert_with(Default::default)).blocks = blocks;
+						self.error = None;
+					},
+					Err(format_error) => self.error = Some(format_error.to_string()),
+				}
+			}
+		});
+
+		match &self.error {
+			Some(error) => { ui.colored_label(ERROR_COLOUR, error); },
+			None => match &self.scene {
+				Some(scene) => {
+					ui.label(format!("{} entities and {} listed blocks in the scene.", scene.entities.len(), scene.blocks.positions().count()));
+				},
+				None => { ui.label("No scene loaded; nothing is known about the world."); },
+			},
+		}
+	}
+}
+
+/// The scene's caster: how they cast the hex, how much media they have, and their items.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct CasterPanel {
+	/// the file the item in the caster's other hand is loaded from.
+	held_path: String,
+	/// the file the trinket, cypher or artifact the hex is cast from is loaded from.
+	casting_path: String,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl CasterPanel {
+	/// Shows the caster, with the casting context, their media, and the items they hold. The scene is only changed when
+	/// something is edited, so the hex isn't re-cast in a new copy of it every frame.
+	fn show(&mut self, ui: &mut egui::Ui, pattern_text: &mut String, scene: &mut Option<Rc<Scene>>) {
+		egui::CollapsingHeader::new("Caster and items").show(ui, |ui| {
+			let caster = match scene.as_ref().and_then(|scene| scene.caster.as_ref()) {
+				Some(caster) => caster,
+				None => {
+					ui.label("No caster in the scene; targets and media aren't checked.");
+					if ui.button("Add a caster").clicked() {
+						caster_mut(scene);
+					}
+					return
+				},
+			};
+
+			let mut casting = caster.casting;
+			egui::ComboBox::from_label("Casting from")
+				.selected_text(match casting {
+					Casting::Staff => "Staff".to_string(),
+					Casting::Trinket => "Trinket, cypher or artifact".to_string(),
+					Casting::Circle { min, max } => format!("Spell circle ({} by {} by {} blocks)", max.0 - min.0, max.1 - min.1, max.2 - min.2),
+				})
+				.show_ui(ui, |ui| {
+					ui.selectable_value(&mut casting, Casting::Staff, "Staff");
+					ui.selectable_value(&mut casting, Casting::Trinket, "Trinket, cypher or artifact");
+				});
+
+			let mut media = caster.media.map(|media| media / DUST);
+			ui.horizontal(|ui| {
+				let mut known = media.is_some();
+				ui.checkbox(&mut known, "Media (dust)");
+				if known {
+					ui.add(egui::DragValue::new(media.get_or_insert(0.0)).speed(0.1).clamp_range(0.0..=f64::MAX));
+				} else {
+					media = None;
+				}
+			});
+
+			let mut use_hex = None;
+			for (label, item) in [("Held item", &caster.held_item), ("Casting item", &caster.casting_item)] {
+				match item {
+					Some(item) => {
+						ui.label(format!("{}: {}", label, item));
+						if !item.hex.is_empty() && ui.button("Use the item's hex").clicked() {
+							use_hex = Some(item.hex.iter().map(Renderable::canonical_text).collect::<Vec<_>>().join(",\n"));
+						}
+					},
+					None => { ui.label(format!("{}: unknown", label)); },
+				}
+			}
+
+			if casting != caster.casting || media != caster.media.map(|media| media / DUST) {
+				let caster = caster_mut(scene);
+				caster.casting = casting;
+				caster.media = media.map(|media| media * DUST);
+			}
+			if let Some(text) = use_hex {
+				*pattern_text = text;
+				caster_mut(scene).casting = Casting::Trinket;
+			}
+
+			for (label, path, casting_item) in [("Held item file", &mut self.held_path, false), ("Casting item file", &mut self.casting_path, true)] {
+				ui.horizontal(|ui| {
+					ui.label(label);
+					ui.text_edit_singleline(path);
+
+					if ui.button("Load").clicked() {
+						match Item::load(Path::new(path.as_str())) {
+							Ok(item) => {
+								let caster = caster_mut(scene);
+								*if casting_item { &mut caster.casting_item } else { &mut caster.held_item } = Some(item);
+								self.error = None;
+							},
+							Err(format_error) => self.error = Some(format_error.to_string()),
+						}
+					}
+				});
+			}
+
+			if let Some(error) = &self.error {
+				ui.colored_label(ERROR_COLOUR, error);
+			}
+		});
+	}
+
+	/// Shows what the hex leaves in the caster's items: the media left in the item it is cast from, and what each branch
+	/// leaves in the held item if it writes to it.
+	fn show_results(&self, ui: &mut egui::Ui, before: &Caster, stacks: &StackHolder) {
+		let charge = before.casting_item.as_ref().filter(|_| before.casting == Casting::Trinket).and_then(|item| item.media);
+		if let (Some(charge), Some(cost)) = (charge, stacks.cost()) {
+			let left = Interval::new((charge.media - cost.upper).max(0.0), (charge.media - cost.lower).max(0.0));
+			ui.label(format!("Charge left: {} of {}", describe_dust(&left), describe_dust(&Interval::point(charge.capacity))));
+		}
+
+		let mut held_after: Vec<String> = vec![];
+		for state in stacks.iter().filter_map(|result| result.as_ref().ok()) {
+			let held = state.scene().and_then(|scene| scene.caster.as_ref()).and_then(|caster| caster.held_item.as_ref());
+			if held != before.held_item.as_ref() {
+				let summary = held.map_or("unknown".to_string(), Item::to_string);
+				if !held_after.contains(&summary) {
+					held_after.push(summary);
+				}
+			}
+		}
+
+		for summary in held_after {
+			ui.label(format!("Held item after the hex: {}", summary));
+		}
+	}
+}
+
+/// The scene's caster, to be changed by the caster panel; adds a scene and a caster if there aren't any.
+fn caster_mut(scene: &mut Option<Rc<Scene>>) -> &mut Caster {
+	Rc::make_mut(scene.get_or_insert_with(Default::default)).caster.get_or_insert_with(Default::default)
+}
+
+/// How many pixels wide each block is in the circle view.
+const SLATE_SIZE: f32 = 40.0;
+/// How long the wave takes to travel from one block to the next in the circle view, in seconds.
+const WAVE_STEP: f64 = 0.25;
+const WAVE_COLOUR: Color32 = Color32::from_rgb(255, 200, 60);
+
+/// A spell circle the hex can be cast from, drawn one layer at a time, with the wave travelling around it animated.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct CircleView {
+	path: String,
+	/// the circle last loaded, or None if no circle has been loaded.
+	#[serde(with = "as_json")]
+	circle: Option<Circle>,
+	/// the height of the layer shown, or None for the impetus' layer.
+	#[serde(skip)]
+	layer: Option<i64>,
+	/// when the animation of the wave started, in seconds since the app started, or None if it isn't playing.
+	#[serde(skip)]
+	playing_since: Option<f64>,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl CircleView {
+	/// Shows the circle, and a button to replace the hex with the patterns it casts and cast it with the circle's ambit.
+	fn show(&mut self, ui: &mut egui::Ui, pattern_text: &mut String, scene: &mut Option<Rc<Scene>>) {
+		egui::CollapsingHeader::new("Spell circle").show(ui, |ui| {
+			ui.horizontal(|ui| {
+				ui.label("Circle file");
+				ui.text_edit_singleline(&mut self.path);
+
+				if ui.button("Load").clicked() {
+					match Circle::load(Path::new(&self.path)) {
+						Ok(circle) => {
+							self.circle = Some(circle);
+							self.layer = None;
+							self.error = None;
+						},
+						Err(format_error) => self.error = Some(format_error.to_string()),
+					}
+				}
+			});
+
+			if let Some(error) = &self.error {
+				ui.colored_label(ERROR_COLOUR, error);
+			}
+
+			let circle = match &self.circle {
+				Some(circle) => circle,
+				None => {
+					ui.label("No circle loaded.");
+					return
+				},
+			};
+
+			let wave = match circle.wave() {
+				Ok(wave) => wave,
+				Err(circle_error) => {
+					ui.colored_label(ERROR_COLOUR, circle_error.to_string());
+					return
+				},
+			};
+
+			ui.horizontal(|ui| {
+				match circle.hex_text() {
+					Ok(text) => {
+						ui.label(format!("Casts {} patterns over {} steps.", wave.slates.len(), wave.fronts.len()));
+						if ui.button("Cast from the circle").clicked() {
+							*pattern_text = text;
+							if let Some(caster) = scene.as_mut().and_then(|scene| Rc::make_mut(scene).caster.as_mut()) {
+								caster.casting = wave.casting();
+							}
+						}
+					},
+					Err(circle_error) => { ui.colored_label(ERROR_COLOUR, circle_error.to_string()); },
+				}
+
+				if ui.button("Play wave").clicked() {
+					self.playing_since = Some(ui.input().time);
+				}
+			});
+
+			show_circle_layer(ui, circle, &wave, &mut self.layer, &mut self.playing_since);
+		});
+	}
+}
+
+/// Draws one layer of the circle from above, with north at the top: slates with their patterns and the order they are cast
+/// in, and arrows for the way directrices and the impetus send the wave. While the wave is playing, the blocks it has
+/// just reached are highlighted.
+fn show_circle_layer(ui: &mut egui::Ui, circle: &Circle, wave: &Wave, layer: &mut Option<i64>, playing_since: &mut Option<f64>) {
+	let impetus = wave.fronts[0][0];
+	let positions: Vec<BlockPos> = circle.blocks().map(|(pos, _)| pos).collect();
+	let min = positions.iter().fold(impetus, |min, pos| (min.0.min(pos.0), min.1.min(pos.1), min.2.min(pos.2)));
+	let max = positions.iter().fold(impetus, |max, pos| (max.0.max(pos.0), max.1.max(pos.1), max.2.max(pos.2)));
+
+	let layer = layer.get_or_insert(impetus.1);
+	*layer = (*layer).clamp(min.1, max.1);
+	if min.1 < max.1 {
+		ui.add(egui::Slider::new(layer, min.1..=max.1).text("y"));
+	}
+	let y = *layer;
+
+	// the step of the wave being shown, if it is playing
+	let step = playing_since.map(|since| ((ui.input().time - since) / WAVE_STEP) as usize);
+	match step {
+		Some(step) if step < wave.fronts.len() => ui.ctx().request_repaint(),
+		_ => *playing_since = None,
+	}
+	let step = step.filter(|step| *step < wave.fronts.len());
+
+	let (width, depth) = ((max.0 - min.0 + 1) as f32, (max.2 - min.2 + 1) as f32);
+	let (response, painter) = ui.allocate_painter(egui::vec2(width * SLATE_SIZE, depth * SLATE_SIZE), egui::Sense::hover());
+	let origin = response.rect.min;
+
+	for (pos, block) in circle.blocks().filter(|(pos, _)| pos.1 == y) {
+		let min = origin + egui::vec2((pos.0 - min.0) as f32 * SLATE_SIZE, (pos.2 - min.2) as f32 * SLATE_SIZE);
+		let rect = Rect::from_min_size(min, egui::vec2(SLATE_SIZE, SLATE_SIZE)).shrink(1.0);
+
+		let colour = match block {
+			CircleBlock::Slate { .. } => Color32::from_gray(45),
+			CircleBlock::Directrix { .. } => Color32::from_rgb(70, 50, 90),
+			CircleBlock::Impetus { .. } => Color32::from_rgb(110, 60, 140),
+		};
+		painter.rect_filled(rect, 2.0, colour);
+
+		match step.map(|step| wave.fronts[step].contains(&pos)) {
+			Some(true) => { painter.rect_stroke(rect, 2.0, Stroke::new(3.0, WAVE_COLOUR)); },
+			_ if !wave.reached().any(|reached| reached == pos) => { painter.rect_stroke(rect, 2.0, Stroke::new(1.0, ERROR_COLOUR)); },
+			_ => {},
+		}
+
+		match block {
+			CircleBlock::Slate { pattern: Some(pattern), .. } => {
+				pattern.render_to_rect(ui, rect.shrink(4.0));
+				if let Some(i) = wave.slates.iter().position(|slate| *slate == pos) {
+					painter.text(rect.left_top(), egui::Align2::LEFT_TOP, i, egui::FontId::monospace(10.0), Color32::WHITE);
+				}
+			},
+			CircleBlock::Slate { pattern: None, .. } => {},
+			CircleBlock::Directrix { .. } | CircleBlock::Impetus { .. } => {
+				for exit in block.exits() {
+					draw_direction(&painter, rect, exit);
+				}
+			},
+		}
+	}
+
+	response.on_hover_text("Numbers are the order slates are cast in; blocks outlined in red aren't reached by the wave.");
+}
+
+/// Draws an arrow from the centre of the block towards the direction, or a dot (up) or cross (down) for the vertical
+/// directions, since the layer is seen from above.
+fn draw_direction(painter: &egui::Painter, rect: Rect, direction: Direction) {
+	let stroke = Stroke::new(2.0, WAVE_COLOUR);
+	let centre = rect.center();
+	let length = rect.width() * 0.35;
+
+	match direction {
+		Direction::Up => { painter.circle_filled(centre, 4.0, WAVE_COLOUR); },
+		Direction::Down => {
+			painter.line_segment([centre - egui::vec2(5.0, 5.0), centre + egui::vec2(5.0, 5.0)], stroke);
+			painter.line_segment([centre - egui::vec2(5.0, -5.0), centre + egui::vec2(5.0, -5.0)], stroke);
+		},
+		_ => {
+			let offset = direction.offset();
+			painter.arrow(centre, egui::vec2(offset.0 as f32, offset.2 as f32) * length, stroke);
+		},
+	}
+}
+
+/// How many pixels wide the rendered key of each entry in the library browser is.
+const KEY_SIZE: f32 = 36.0;
+
+/// A browser for the Akashic Library in the scene, where entries can be written and removed, and for the entries the
+/// simulated hex writes to it.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+struct LibraryBrowser {
+	/// the file the library is loaded from and saved to.
+	path: String,
+	/// the key being edited, as a pattern with its start direction, e.g. "HexPattern(qaq, EAST)".
+	#[serde(skip)]
+	key: String,
+	/// the value being edited, the way it is shown on the stack.
+	#[serde(skip)]
+	value: String,
+	/// the index of the branch whose writes are shown, among the branches that can still be simulated.
+	#[serde(skip)]
+	branch: usize,
+	#[serde(skip)]
+	error: Option<String>,
+}
+
+impl LibraryBrowser {
+	/// Shows the library in the scene, with an editor for its entries and the file it is loaded from.
+	fn show(&mut self, ui: &mut egui::Ui, scene: &mut Option<Rc<Scene>>) {
+		egui::CollapsingHeader::new("Akashic Library").show(ui, |ui| {
+			ui.horizontal(|ui| {
+				ui.label("Library file");
+				ui.text_edit_singleline(&mut self.path);
+
+				if ui.button("Load").clicked() {
+					match Library::load(Path::new(&self.path)) {
+						Ok(library) => {
+							Rc::make_mut(scene.get_or_insert_with(Default::default)).library = Some(library);
+							self.error = None;
+						},
+						Err(format_error) => self.error = Some(format_error.to_string()),
+					}
+				}
+
+				let library = scene.as_ref().and_then(|scene| scene.library.as_ref());
+				if ui.add_enabled(library.is_some(), egui::Button::new("Save")).clicked() {
+					if let Some(library) = library {
+						self.error = library.save(Path::new(&self.path)).err().map(|format_error| format_error.to_string());
+					}
+				}
+			});
+
+			let library = match scene.as_ref().and_then(|scene| scene.library.as_ref()) {
+				Some(library) => library,
+				None => {
+					ui.label("No library loaded; Akashic Read pushes iotas that aren't known.");
+					if ui.button("New library").clicked() {
+						Rc::make_mut(scene.get_or_insert_with(Default::default)).library = Some(Library::default());
+					}
+					self.show_error(ui);
+					return
+				},
+			};
+
+			match library.record {
+				Some(record) => ui.label(format!("Record at {:?}, version {}.", record, library.version())),
+				None => ui.label(format!("Record anywhere, version {}.", library.version())),
+			};
+
+			for entry in library.entries() {
+				if show_entry(ui, entry, None).clicked() {
+					self.key = entry.key.canonical_text();
+					self.value = entry.value.to_string();
+					self.error = None;
+				}
+			}
+
+			ui.horizontal(|ui| {
+				ui.label("Key");
+				ui.text_edit_singleline(&mut self.key);
+			});
+			ui.horizontal(|ui| {
+				ui.label("Value");
+				ui.text_edit_singleline(&mut self.value);
+			});
+
+			let key = parse_to_hex_pattern(&self.key).ok();
+			let stored = key.as_ref().map_or(false, |key| library.get(key).is_some());
+			ui.horizontal(|ui| {
+				if ui.add_enabled(key.is_some(), egui::Button::new("Write")).clicked() {
+					match (key.clone(), self.value.parse::<Iota>()) {
+						(Some(key), Ok(value)) => {
+							library_mut(scene).write(key, value);
+							self.error = None;
+						},
+						(_, Err(error)) => self.error = Some(error),
+						(None, _) => {},
+					}
+				}
+
+				if ui.add_enabled(stored, egui::Button::new("Remove")).clicked() {
+					if let Some(key) = &key {
+						library_mut(scene).remove(key);
+					}
+				}
+			});
+
+			self.show_error(ui);
+		});
+	}
+
+	fn show_error(&self, ui: &mut egui::Ui) {
+		if let Some(error) = &self.error {
+			ui.colored_label(ERROR_COLOUR, error);
+		}
+	}
+
+	/// Shows the entries each branch of the simulated hex wrote to the library, with the version they were written at.
+	fn show_writes(&mut self, ui: &mut egui::Ui, before: &Library, stacks: &StackHolder) {
+		let branches: Vec<&Library> = stacks.iter()
+			.filter_map(|result| result.as_ref().ok())
+			.map(|state| state.scene().and_then(|scene| scene.library.as_ref()).unwrap_or(before))
+			.collect();
+
+		if branches.iter().all(|after| after.version() == before.version()) {
+			return
+		}
+
+		egui::CollapsingHeader::new("Akashic writes").default_open(true).show(ui, |ui| {
+			if branches.len() > 1 {
+				ui.horizontal(|ui| {
+					ui.label("Branch");
+					ui.add(egui::DragValue::new(&mut self.branch).clamp_range(0..=branches.len() - 1));
+				});
+			}
+			self.branch = self.branch.min(branches.len() - 1);
+
+			let after = branches[self.branch];
+			if after.version() == before.version() {
+				ui.label("This branch doesn't write to the library.");
+			}
+			for entry in after.written_since(before.version()) {
+				show_entry(ui, entry, before.get(&entry.key).map(|entry| &entry.value));
+			}
+		});
+	}
+}
+
+/// The library in the scene, to be changed by the library browser.
+fn library_mut(scene: &mut Option<Rc<Scene>>) -> &mut Library {
+	Rc::make_mut(scene.get_or_insert_with(Default::default)).library.get_or_insert_with(Default::default)
+}
+
+/// Shows an entry of a library as its rendered key and its value, with the value it replaced if there was one.
+fn show_entry(ui: &mut egui::Ui, entry: &Entry, replaced: Option<&Iota>) -> egui::Response {
+	ui.horizontal(|ui| {
+		let (rect, response) = ui.allocate_exact_size(egui::vec2(KEY_SIZE, KEY_SIZE), egui::Sense::click());
+		ui.painter().rect_stroke(rect, 2.0, Stroke::new(1.0, Color32::from_gray(70)));
+		entry.key.render_to_rect(ui, rect.shrink(4.0));
+
+		match replaced {
+			Some(replaced) => ui.colored_label(PUSHED_COLOUR, format!("{} (was {})", entry.value, replaced)),
+			None => ui.label(entry.value.to_string()),
+		};
+		ui.weak(format!("version {}", entry.version));
+
+		response.on_hover_text(format!("{}; click to edit", describe_pattern(&entry.key)))
+	}).inner
+}
+
+/// A before and after view of one horizontal slice of the blocks a simulated hex changed.
+#[derive(Default)]
+struct BlockView {
+	/// the index of the branch whose blocks are shown, among the branches that can still be simulated.
+	branch: usize,
+	/// the height of the slice shown, or None for the lowest height a block changed at.
+	layer: Option<i64>,
+}
+
+/// How many pixels wide each block is in the slice view.
+const BLOCK_SIZE: f32 = 12.0;
+
+/// The colour a block is shown as; blocks without a colour of their own get one made from their name.
+fn block_colour(block: Option<&str>) -> Color32 {
+	match block {
+		None => Color32::from_gray(70),
+		Some(block) if is_air(block) => Color32::from_gray(20),
+		Some("water") => Color32::from_rgb(50, 90, 220),
+		Some("lava") => Color32::from_rgb(230, 110, 20),
+		Some("conjured_block" | "conjured_light") => Color32::from_rgb(150, 80, 220),
+		Some(block) => {
+			let hash = block.bytes().fold(2166136261_u32, |hash, byte| (hash ^ byte as u32).wrapping_mul(16777619));
+			let [r, g, b, _] = hash.to_le_bytes();
+			Color32::from_rgb(80 + r % 150, 80 + g % 150, 80 + b % 150)
+		},
+	}
+}
+
+fn describe_block(block: Option<&str>) -> &str { block.unwrap_or("unknown") }
+
+impl BlockView {
+	/// Shows the slice of the blocks before the hex was cast and after, with the blocks that changed outlined.
+	fn show(&mut self, ui: &mut egui::Ui, before: &Blocks, stacks: &StackHolder) {
+		let branches: Vec<&Blocks> = stacks.iter()
+			.filter_map(|result| result.as_ref().ok())
+			.map(|state| state.scene().map_or(before, |scene| &scene.blocks))
+			.collect();
+
+		if branches.iter().all(|after| after.changes(before).is_empty() && after.default == before.default) {
+			return
+		}
+
+		egui::CollapsingHeader::new("Blocks changed").default_open(true).show(ui, |ui| {
+			if branches.len() > 1 {
+				ui.horizontal(|ui| {
+					ui.label("Branch");
+					ui.add(egui::DragValue::new(&mut self.branch).clamp_range(0..=branches.len() - 1));
+				});
+			}
+			self.branch = self.branch.min(branches.len() - 1);
+
+			let after = branches[self.branch];
+			let changes = after.changes(before);
+			if after.default != before.default {
+				ui.colored_label(WARNING_COLOUR, "Blocks that weren't listed may have changed anywhere the hex could reach.");
+			}
+			if changes.is_empty() {
+				ui.label("No listed blocks changed in this branch.");
+				return
+			}
+
+			let min = changes.iter().fold(changes[0], |min, pos| (min.0.min(pos.0), min.1.min(pos.1), min.2.min(pos.2)));
+			let max = changes.iter().fold(changes[0], |max, pos| (max.0.max(pos.0), max.1.max(pos.1), max.2.max(pos.2)));
+			let layer = self.layer.get_or_insert(min.1);
+			*layer = (*layer).clamp(min.1, max.1);
+
+			if min.1 < max.1 {
+				ui.add(egui::Slider::new(layer, min.1..=max.1).text("y"));
+			} else {
+				ui.label(format!("y = {}", layer));
+			}
+
+			// the region shown is the changed blocks, with a border of one block around them
+			let (x0, z0, width, depth) = (min.0 - 1, min.2 - 1, max.0 - min.0 + 3, max.2 - min.2 + 3);
+			let y = *layer;
+
+			ui.horizontal(|ui| {
+				for (heading, blocks) in [("Before", before), ("After", after)] {
+					ui.vertical(|ui| {
+						ui.label(heading);
+						let (rect, response) = ui.allocate_exact_size(egui::vec2(width as f32, depth as f32) * BLOCK_SIZE, egui::Sense::hover());
+
+						for dx in 0..width {
+							for dz in 0..depth {
+								let pos: BlockPos = (x0 + dx, y, z0 + dz);
+								let cell = Rect::from_min_size(rect.min + egui::vec2(dx as f32, dz as f32) * BLOCK_SIZE, egui::Vec2::splat(BLOCK_SIZE));
+								ui.painter().rect_filled(cell.shrink(0.5), 0.0, block_colour(blocks.get(pos)));
+								if changes.contains(&pos) {
+									ui.painter().rect_stroke(cell.shrink(1.0), 0.0, Stroke::new(1.5, WARNING_COLOUR));
+								}
+							}
+						}
+
+						if let Some(hover) = response.hover_pos() {
+							let offset = (hover - rect.min) / BLOCK_SIZE;
+							let pos: BlockPos = (x0 + offset.x as i64, y, z0 + offset.y as i64);
+							response.on_hover_text(format!(
+								"({}, {}, {}): {} → {}",
+								pos.0, pos.1, pos.2, describe_block(before.get(pos)), describe_block(after.get(pos))
+							));
+						}
+					});
+				}
+			});
+		});
+	}
+}
+
+const WARNING_COLOUR: Color32 = Color32::from_rgb(220, 160, 0);
+const ERROR_COLOUR: Color32 = Color32::from_rgb(230, 70, 70);
+const BREAKPOINT_COLOUR: Color32 = Color32::from_rgba_premultiplied(120, 30, 30, 120);
+const PUSHED_COLOUR: Color32 = Color32::from_rgb(90, 190, 90);
+const PAUSED_COLOUR: Color32 = Color32::from_rgba_premultiplied(120, 110, 0, 140);
+
+/// Reads each line of the text as a pattern, returning the index of the first line that isn't a pattern if there is one.
+fn parse_patterns(pattern_text: &str) -> Result<Vec<HexPattern>, usize> {
+	let renderables = parse_to_list(pattern_text).map_err(|_| 0_usize)?;
+
+	renderables.iter()
+		.enumerate()
+		.map(|(i, renderable)| renderable.as_any().downcast_ref::<HexPattern>().cloned().ok_or(i))
+		.collect()
+}
+
+/// Shows how much media the simulated hex could cost, with a warning if that could be more than the budget (in dust),
+/// and any mishaps that might happen depending on unknown values.
+fn show_cost(ui: &mut egui::Ui, result: &Result<StackManager, usize>, media_budget: f64) {
+	let stack_manager = match result {
+		Ok(stack_manager) => stack_manager,
+		Err(i) => {
+			ui.label(format!("Cost unknown; pattern {} can't be simulated.", i));
+			return
+		}
+	};
+
+	let mut possible_mishaps: Vec<&Mishap> = vec![];
+	for state in stack_manager.stacks().iter().filter_map(|result| result.as_ref().ok()) {
+		for mishap in state.possible_mishaps() {
+			if !possible_mishaps.contains(&mishap) {
+				possible_mishaps.push(mishap);
+			}
+		}
+	}
+
+	for mishap in possible_mishaps {
+		ui.colored_label(WARNING_COLOUR, format!("Possible {}", mishap));
+	}
+
+	match stack_manager.stacks().cost() {
+		Some(cost) => {
+			ui.label(format!("Cost: {}", describe_cost(&cost)));
+			if cost.lower > media_budget * DUST {
+				ui.colored_label(WARNING_COLOUR, "Costs more than the media budget!");
+			} else if cost.upper > media_budget * DUST {
+				ui.colored_label(WARNING_COLOUR, "May cost more than the media budget.");
+			}
+		},
+		None => { ui.label("Cost unknown; the simulator gave up on every branch."); }
+	}
+}
+
+/// Shows what each branch of the simulated hex would do to the world, in the order the spells are cast.
+fn show_effects(ui: &mut egui::Ui, stacks: &StackHolder) {
+	let branches: Vec<&StackState> = stacks.iter().filter_map(|result| result.as_ref().ok()).collect();
+	if branches.iter().all(|state| state.effects().is_empty()) {
+		return
+	}
+
+	egui::CollapsingHeader::new("Effects").default_open(true).show(ui, |ui| {
+		for (i, state) in branches.iter().enumerate() {
+			if branches.len() > 1 {
+				ui.label(format!("Branch {}:", i));
+			}
+			if state.effects().is_empty() {
+				ui.label("No effects.");
+			}
+			for effect in state.effects() {
+				ui.label(effect.to_string());
+			}
+		}
+	});
+}
+
+/// Shows the debugger's controls, a timeline of the steps it has taken, and the branches after the step being looked at:
+/// each one's stack (with how the step changed it), ravenmind and the frames it is casting. The debugger is started on
+/// the hex from the input state, and stopped if the hex is edited.
+fn show_debugger(
+	ui: &mut egui::Ui,
+	debugger: &mut Option<Debugger>,
+	timeline: &mut Option<usize>,
+	patterns: &[HexPattern],
+	input: StackState,
+	breakpoints: &BTreeSet<usize>
+) {
+	if debugger.as_ref().map_or(false, |debugger| debugger.patterns() != patterns) {
+		*debugger = None;
+	}
+
+	egui::CollapsingHeader::new("Debugger").default_open(true).show(ui, |ui| {
+		let running = match debugger {
+			Some(running) => running,
+			None => {
+				*timeline = None;
+				ui.horizontal(|ui| {
+					if ui.button("Debug").clicked() {
+						*debugger = Some(Debugger::new(patterns.to_vec(), input.clone()));
+					}
+					if ui.button("Debug to breakpoint").clicked() {
+						let mut started = Debugger::new(patterns.to_vec(), input.clone());
+						if !breakpoints.contains(&0) {
+							started.resume(breakpoints);
+						}
+						*debugger = Some(started);
+					}
+				});
+				ui.label(format!("{} breakpoints; click a pattern in the gallery, or alt-click one in the editor.", breakpoints.len()));
+				return
+			}
+		};
+
+		let mut stop = false;
+		ui.horizontal_wrapped(|ui| {
+			ui.add_enabled_ui(!running.is_finished(), |ui| {
+				if ui.button("Step into").clicked() {
+					running.step_into();
+					*timeline = None;
+				}
+				if ui.button("Step over").clicked() {
+					running.step_over();
+					*timeline = None;
+				}
+				if ui.button("Continue").clicked() {
+					running.resume(breakpoints);
+					*timeline = None;
+				}
+			});
+			if ui.button("Restart").clicked() {
+				*running = Debugger::new(patterns.to_vec(), input.clone());
+				*timeline = None;
+			}
+			stop = ui.button("Stop").clicked();
+		});
+
+		match running.unknown_pattern() {
+			Some(i) => { ui.colored_label(ERROR_COLOUR, format!("Stopped; pattern {} can't be simulated.", i)); },
+			None if running.is_finished() => { ui.label("Finished."); },
+			None => {
+				let position = running.position();
+				ui.label(format!("Paused at pattern {} ({})", position, describe_pattern(&patterns[position])));
+			},
+		}
+		ui.label(format!("Escape depth: {}", running.escape_depth()));
+
+		let latest = running.history().len() - 1;
+		let mut shown = timeline.map_or(latest, |step| step.min(latest));
+		if latest > 0 {
+			ui.add(egui::Slider::new(&mut shown, 0..=latest).text("Step"));
+		}
+		*timeline = (shown < latest).then(|| shown);
+
+		if shown < latest {
+			ui.horizontal(|ui| {
+				ui.label(format!("Looking at step {} of {}.", shown, latest));
+				if ui.button("Rewind to here").clicked() {
+					running.rewind(shown);
+					*timeline = None;
+				}
+			});
+		}
+
+		let snapshot = match running.history().get(shown) {
+			Some(snapshot) => snapshot,
+			None => return,
+		};
+		match snapshot.pattern_index() {
+			Some(i) => { ui.label(format!("Produced by pattern {} ({})", i, describe_pattern(&patterns[i]))); },
+			None => { ui.label("The state the hex is cast from."); },
+		}
+
+		let changes = running.history().changes(shown);
+		let branches = snapshot.stacks();
+		for (i, result) in branches.iter().enumerate() {
+			if branches.len() > 1 {
+				ui.label(format!("Branch {}:", i));
+			}
+			for change in changes.get(i).into_iter().flatten().flatten() {
+				let colour = match change {
+					StackChange::Pushed { .. } => PUSHED_COLOUR,
+					StackChange::Popped { .. } => ERROR_COLOUR,
+					StackChange::Changed { .. } => WARNING_COLOUR,
+				};
+				ui.colored_label(colour, change.to_string());
+			}
+
+			let state = match result {
+				Ok(state) => state,
+				Err(action_error) => {
+					ui.colored_label(WARNING_COLOUR, format!("Gave up: {}", action_error));
+					continue
+				}
+			};
+
+			if let Some(mishap) = state.mishaps().last() {
+				ui.colored_label(ERROR_COLOUR, mishap.to_string());
+			}
+			if state.stack().is_empty() {
+				ui.label("Empty stack.");
+			} else {
+				ui.monospace(state.to_string());
+			}
+			if let Some(ravenmind) = state.ravenmind() {
+				ui.label(format!("Ravenmind: {}", ravenmind));
+			}
+			for frame in state.frames().iter().rev() {
+				ui.label(frame.to_string());
+			}
+		}
+
+		if stop {
+			*debugger = None;
+		}
+	});
+}
+
+/// Lays out the pattern text with the patterns that have diagnostics underlined and coloured as errors, breakpoints
+/// highlighted, and the pattern the debugger is paused at (if any) highlighted more strongly.
+fn layout_diagnostics(
+	ui: &egui::Ui,
+	text: &str,
+	wrap_width: f32,
+	diagnostics: &[Mishap],
+	breakpoints: &BTreeSet<usize>,
+	paused_at: Option<usize>
+) -> std::sync::Arc<egui::Galley> {
+	let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+	let normal = TextFormat::simple(font_id, ui.visuals().text_color());
+
+	let mut job = LayoutJob::default();
+	let mut end = 0;
+
+	for (i, span) in pattern_spans(text).into_iter().enumerate() {
+		let is_error = diagnostics.iter().any(|mishap| mishap.pattern_index == i);
+		let background = if paused_at == Some(i) {
+			PAUSED_COLOUR
+		} else if breakpoints.contains(&i) {
+			BREAKPOINT_COLOUR
+		} else {
+			Color32::TRANSPARENT
+		};
+
+		if (is_error || background != Color32::TRANSPARENT) && span.start >= end && span.end <= text.len() {
+			let mut format = TextFormat { background, ..normal.clone() };
+			if is_error {
+				format.color = ERROR_COLOUR;
+				format.underline = Stroke::new(1.0, ERROR_COLOUR);
+			}
+
+			job.append(&text[end..span.start], 0.0, normal.clone());
+			job.append(&text[span.clone()], 0.0, format);
+			end = span.end;
+		}
+	}
+
+	job.append(&text[end..], 0.0, normal);
+	job.wrap.max_width = wrap_width;
+	ui.fonts().layout_job(job)
+}
+
+/// Shows the stack effect of each Introspection block, flagging blocks whose effect depends on runtime values.
+fn show_stack_effects(ui: &mut egui::Ui, patterns: &[HexPattern]) {
+	let blocks = match introspection_blocks(patterns) {
+		Ok(blocks) => blocks,
+		Err(effect_error) => {
+			ui.colored_label(WARNING_COLOUR, format!("Blocks: {}", effect_error));
+			return
+		}
+	};
+
+	for block in blocks {
+		let start = block.start - 1;
+		match infer(patterns, block) {
+			Ok(effect) => { ui.label(format!("Block at pattern {}: {}", start, effect)); },
+			Err(effect_error) if effect_error.is_dynamic() => {
+				ui.colored_label(WARNING_COLOUR, format!("Block at pattern {}: {}", start, effect_error));
+			},
+			Err(effect_error) => { ui.label(format!("Block at pattern {}: {}", start, effect_error)); },
+		}
+	}
+}
+
+/// The pattern to highlight as the one the debugger is at: the pattern that produced the step being looked at in the
+/// timeline, or else the pattern the debugger is paused at.
+fn highlighted_pattern(debugger: &Option<Debugger>, timeline: Option<usize>) -> Option<usize> {
+	let debugger = debugger.as_ref()?;
+	match timeline {
+		Some(step) => debugger.history().get(step)?.pattern_index(),
+		None => (!debugger.is_finished()).then(|| debugger.position()),
+	}
+}
+
+/// The pattern's name if the simulator knows it, or else its angle signature.
+fn describe_pattern(pattern: &HexPattern) -> String { pattern_name(pattern).unwrap_or_else(|| pattern.angle_signature()) }
+
+fn toggle(breakpoints: &mut BTreeSet<usize>, i: usize) {
+	if !breakpoints.remove(&i) {
+		breakpoints.insert(i);
+	}
+}
+
+/// Shows every pattern in a grid. Clicking a pattern toggles a breakpoint on it; breakpoints are outlined, and the pattern
+/// the debugger is paused at is highlighted.
+fn show_gallery(ui: &mut egui::Ui, renderables: &[Box<dyn Renderable>], breakpoints: &mut BTreeSet<usize>, paused_at: Option<usize>) {
+	let available = ui.available_rect_before_wrap();
+	let columns = (renderables.len() as f32).sqrt().ceil().max(1.0);
+	let rows = (renderables.len() as f32 / columns).ceil().max(1.0);
+	let size = f32::min(available.width() / columns, available.height() / rows);
+
+	for (i, renderable) in renderables.iter().enumerate() {
+		let min = available.min + egui::vec2((i as f32 % columns).floor() * size, (i as f32 / columns).floor() * size);
+		let rect = Rect::from_min_size(min, egui::vec2(size, size));
+		let response = ui.allocate_rect(rect, egui::Sense::click());
+
+		if paused_at == Some(i) {
+			ui.painter().rect_filled(rect.shrink(2.0), 4.0, PAUSED_COLOUR);
+		}
+		if breakpoints.contains(&i) {
+			ui.painter().rect_stroke(rect.shrink(2.0), 4.0, Stroke::new(2.0, ERROR_COLOUR));
+		}
+		renderable.render_to_rect(ui, rect.shrink(8.0));
+
+		let name = renderable.as_any().downcast_ref::<HexPattern>().map_or("not a pattern".to_string(), describe_pattern);
+		if response.on_hover_text(format!("Pattern {} ({}); click to toggle a breakpoint", i, name)).clicked() {
+			toggle(breakpoints, i);
+		}
+	}
+}
+
 impl eframe::App for HexeditApp {
 	/// Called by the frame work to save state before shutdown.
 	fn save(&mut self, storage: &mut dyn eframe::Storage) {
@@ -87,6 +1244,18 @@ impl eframe::App for HexeditApp {
 			are_drawing,
 			pattern_text,
 			canonical_text,
+			media_budget,
+			version_panel,
+			number_format,
+			input_stacks,
+			scene_file,
+			caster_panel,
+			block_view,
+			library_browser,
+			circle_view,
+			breakpoints,
+			debugger,
+			timeline,
 			drawing_pattern,
 			start_draw_node,
 			last_draw_node,
@@ -124,16 +1293,89 @@ impl eframe::App for HexeditApp {
 				}
 			}
 
+			ui.horizontal(|ui| {
+				ui.label("Media budget (dust)");
+				ui.add(egui::DragValue::new(media_budget).speed(0.1).clamp_range(0.0..=f64::MAX));
+			});
+			version_panel.show(ui);
+
+			let mut game_format = *number_format == NumberFormat::Game;
+			ui.checkbox(&mut game_format, "Show numbers as the game does");
+			*number_format = if game_format { NumberFormat::Game } else { NumberFormat::Exact };
+			set_number_format(*number_format);
+			with_registry(|registry| if !registry.addons().is_empty() {
+				ui.label(format!("Addons: {}", registry.addons().join(", ")));
+			});
+			input_stacks.show(ui);
+			scene_file.show(ui);
+			caster_panel.show(ui, pattern_text, &mut scene_file.scene);
+			library_browser.show(ui, &mut scene_file.scene);
+			circle_view.show(ui, pattern_text, &mut scene_file.scene);
+			let mut diagnostics = vec![];
+
+			match parse_patterns(pattern_text) {
+				Ok(patterns) => {
+					let mut input = input_stacks.input();
+					input.set_scene(scene_file.scene.clone());
+					let result = run_hex_from(&patterns, input.clone());
+					show_cost(ui, &result, *media_budget);
+					if let Ok(stack_manager) = &result {
+						show_effects(ui, stack_manager.stacks());
+						if let Some(scene) = &scene_file.scene {
+							if let Some(caster) = &scene.caster {
+								caster_panel.show_results(ui, caster, stack_manager.stacks());
+							}
+							block_view.show(ui, &scene.blocks, stack_manager.stacks());
+							if let Some(library) = &scene.library {
+								library_browser.show_writes(ui, library, stack_manager.stacks());
+							}
+						}
+					}
+					show_stack_effects(ui, &patterns);
+					show_debugger(ui, debugger, timeline, &patterns, input, breakpoints);
+
+					diagnostics = check(&patterns);
+					for mishap in &diagnostics {
+						ui.colored_label(ERROR_COLOUR, mishap.to_string());
+					}
+				},
+				Err(i) => {
+					ui.label(format!("Pattern {} couldn't be read.", i));
+					*debugger = None;
+				},
+			}
+			let paused_at = highlighted_pattern(debugger, *timeline);
+
 			let mut canonical_str = canonical_text.as_str();
+			let mut layouter = |ui: &egui::Ui, text: &str, wrap_width: f32| {
+				layout_diagnostics(ui, text, wrap_width, &diagnostics, breakpoints, paused_at)
+			};
+
+			let mut text_edit = egui::TextEdit::multiline(if *show_canonical { &mut canonical_str } else { pattern_text })
+				.font(egui::TextStyle::Monospace) // for cursor height
+				.code_editor()
+				.desired_rows(93)
+				.lock_focus(true)
+				.desired_width(f32::INFINITY);
+
+			if !*show_canonical {
+				text_edit = text_edit.layouter(&mut layouter);
+			}
 
-			ui.add(
-	egui::TextEdit::multiline(if *show_canonical { &mut canonical_str } else { pattern_text })
-					.font(egui::TextStyle::Monospace) // for cursor height
-					.code_editor()
-					.desired_rows(93)
-					.lock_focus(true)
-					.desired_width(f32::INFINITY)
-			);
+			let output = text_edit.show(ui);
+			if !*show_canonical && output.response.clicked() && ui.input().modifiers.alt {
+				// alt-clicking a pattern toggles a breakpoint on it.
+				if let Some(pos) = output.response.interact_pointer_pos() {
+					let cursor = output.galley.cursor_from_pos(pos - output.text_draw_pos);
+					let byte = pattern_text.char_indices().nth(cursor.ccursor.index).map_or(pattern_text.len(), |(byte, _)| byte);
+					if let Some(i) = pattern_spans(pattern_text).iter().position(|span| span.start <= byte && byte <= span.end) {
+						toggle(breakpoints, i);
+					}
+				}
+			}
+			if !diagnostics.is_empty() && !*show_canonical {
+				output.response.on_hover_text(join(diagnostics.iter().map(Mishap::to_string), "\n"));
+			}
 		});
 
 		if *are_drawing {
@@ -294,10 +1536,9 @@ impl eframe::App for HexeditApp {
 		// The central panel the region left after adding TopPanel's and SidePanel's
 			egui::warn_if_debug_build(ui);
 
-			let (_id, rect) = ui.allocate_space(ui.available_size());
-
-			if let Ok(result) = parse_to_list(pattern_text.as_str()) {
-				result[0].render_to_rect(ui, rect)
+			if let Ok(renderables) = parse_to_list(pattern_text.as_str()) {
+				let paused_at = highlighted_pattern(debugger, *timeline);
+				show_gallery(ui, &renderables, breakpoints, paused_at);
 			}
 		});
 	}
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+
+use crate::hex_pattern::HexPattern;
+use crate::rendering::Renderable;
+use crate::serialization::{from_json, FormatError};
+use crate::world::{BlockPos, Casting};
+
+/// The six directions blocks can face, in the order Minecraft lists them, which is the order the wave tries them in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+	Down,
+	Up,
+	North,
+	South,
+	West,
+	East
+}
+
+impl Direction {
+	pub const ALL: [Direction; 6] = [Direction::Down, Direction::Up, Direction::North, Direction::South, Direction::West, Direction::East];
+
+	/// The offset to the next block in this direction; north is towards -z, and east towards +x.
+	pub fn offset(&self) -> BlockPos {
+		match self {
+			Direction::Down => (0, -1, 0),
+			Direction::Up => (0, 1, 0),
+			Direction::North => (0, 0, -1),
+			Direction::South => (0, 0, 1),
+			Direction::West => (-1, 0, 0),
+			Direction::East => (1, 0, 0),
+		}
+	}
+
+	pub fn opposite(&self) -> Direction {
+		match self {
+			Direction::Down => Direction::Up,
+			Direction::Up => Direction::Down,
+			Direction::North => Direction::South,
+			Direction::South => Direction::North,
+			Direction::West => Direction::East,
+			Direction::East => Direction::West,
+		}
+	}
+
+	/// Whether the directions are along the same axis (e.g. up and down).
+	pub fn is_parallel(&self, other: Direction) -> bool { *self == other || *self == other.opposite() }
+
+	/// The block next to the position in this direction.
+	pub fn step(&self, pos: BlockPos) -> BlockPos {
+		let offset = self.offset();
+		(pos.0 + offset.0, pos.1 + offset.1, pos.2 + offset.2)
+	}
+}
+
+/// A block a spell circle is built from.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CircleBlock {
+	/// A slate, with the pattern inscribed on it if there is one; blank slates carry the wave without casting anything.
+	/// The wave travels along the surface the slate is attached to, so a slate on the floor (attached down) passes it
+	/// north, south, west and east.
+	Slate {
+		#[serde(default, with = "crate::serialization::pattern_text", skip_serializing_if = "Option::is_none")]
+		pattern: Option<HexPattern>,
+		#[serde(default = "down")]
+		attached: Direction
+	},
+	/// A Mason's Directrix, which sends the wave out of its front, or out of its back if it is powered by redstone.
+	Directrix {
+		facing: Direction,
+		#[serde(default)]
+		powered: bool
+	},
+	/// The impetus that starts the circle; the wave leaves through its front, and the circle is closed if the wave
+	/// comes back to it.
+	Impetus { facing: Direction }
+}
+
+fn down() -> Direction { Direction::Down }
+
+impl CircleBlock {
+	/// The directions the wave can leave the block in.
+	pub fn exits(&self) -> Vec<Direction> {
+		match self {
+			CircleBlock::Slate { attached, .. } => Direction::ALL.iter().copied().filter(|exit| !exit.is_parallel(*attached)).collect(),
+			CircleBlock::Directrix { facing, powered } => vec![if *powered { facing.opposite() } else { *facing }],
+			CircleBlock::Impetus { facing } => vec![*facing],
+		}
+	}
+
+	/// Whether the wave can enter the block while travelling in the direction.
+	pub fn can_enter(&self, travelling: Direction) -> bool {
+		match self {
+			CircleBlock::Slate { attached, .. } => !travelling.is_parallel(*attached),
+			// neither can be entered through the face the wave leaves by
+			CircleBlock::Directrix { .. } | CircleBlock::Impetus { .. } => !self.exits().contains(&travelling.opposite()),
+		}
+	}
+}
+
+/// A spell circle: slates, directrices and an impetus, by position.
+///
+/// Written in JSON as `{"blocks": [{"at": [0, 0, 0], "kind": "impetus", "facing": "east"}, {"at": [1, 0, 0], "kind":
+/// "slate", "pattern": "HexPattern(qaq, EAST)"}]}`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "CircleRepr", into = "CircleRepr")]
+pub struct Circle {
+	blocks: BTreeMap<BlockPos, CircleBlock>
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CircleRepr {
+	#[serde(default)]
+	blocks: Vec<PlacedBlock>
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PlacedBlock {
+	at: BlockPos,
+	#[serde(flatten)]
+	block: CircleBlock
+}
+
+impl From<CircleRepr> for Circle {
+	fn from(repr: CircleRepr) -> Self { Circle { blocks: repr.blocks.into_iter().map(|placed| (placed.at, placed.block)).collect() } }
+}
+
+impl From<Circle> for CircleRepr {
+	fn from(circle: Circle) -> Self {
+		CircleRepr { blocks: circle.blocks.into_iter().map(|(at, block)| PlacedBlock { at, block }).collect() }
+	}
+}
+
+/// Why a circle can't be cast.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CircleError {
+	/// The circle has no impetus to start it.
+	NoImpetus,
+	/// The circle has more than one impetus, at these positions; load circles with one impetus each.
+	TooManyImpetuses(Vec<BlockPos>),
+	/// The wave never came back to the impetus; it stopped at these positions.
+	NotClosed { stopped_at: Vec<BlockPos> }
+}
+
+impl fmt::Display for CircleError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			CircleError::NoImpetus => write!(f, "the circle has no impetus"),
+			CircleError::TooManyImpetuses(positions) => write!(f, "the circle has {} impetuses, at {:?}", positions.len(), positions),
+			CircleError::NotClosed { stopped_at } => write!(f, "the circle isn't closed; the wave stopped at {:?}", stopped_at),
+		}
+	}
+}
+
+/// How the wave travelled around a circle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Wave {
+	/// the blocks the wave reached on each step, starting with the impetus.
+	pub fronts: Vec<Vec<BlockPos>>,
+	/// the slates with patterns the wave reached, in the order they are cast.
+	pub slates: Vec<BlockPos>,
+	/// whether the wave came back to the impetus; the circle only casts if it did.
+	pub closed: bool
+}
+
+impl Wave {
+	/// Every block the wave reached.
+	pub fn reached(&self) -> impl Iterator<Item = BlockPos> + '_ { self.fronts.iter().flatten().copied() }
+
+	/// The ambit of the circle: the box enclosing every block the wave reached.
+	pub fn casting(&self) -> Casting {
+		let first = self.fronts.first().and_then(|front| front.first()).copied().unwrap_or((0, 0, 0));
+		let (min, max) = self.reached().fold((first, first), |(min, max), pos| {
+			((min.0.min(pos.0), min.1.min(pos.1), min.2.min(pos.2)), (max.0.max(pos.0), max.1.max(pos.1), max.2.max(pos.2)))
+		});
+
+		// the box covers whole blocks, so it runs to the far side of the block at `max`
+		Casting::Circle {
+			min: (min.0 as f64, min.1 as f64, min.2 as f64),
+			max: ((max.0 + 1) as f64, (max.1 + 1) as f64, (max.2 + 1) as f64)
+		}
+	}
+}
+
+impl Circle {
+	/// Reads a circle from a file written in the versioned JSON format of [`crate::serialization`].
+	pub fn load(path: &Path) -> Result<Circle, FormatError> {
+		let json = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+		from_json(&json)
+	}
+
+	pub fn get(&self, pos: BlockPos) -> Option<&CircleBlock> { self.blocks.get(&pos) }
+
+	/// Places the block, replacing any block at the position.
+	pub fn set(&mut self, pos: BlockPos, block: CircleBlock) { self.blocks.insert(pos, block); }
+
+	pub fn remove(&mut self, pos: BlockPos) -> Option<CircleBlock> { self.blocks.remove(&pos) }
+
+	/// Every block, in order of position.
+	pub fn blocks(&self) -> impl Iterator<Item = (BlockPos, &CircleBlock)> + '_ { self.blocks.iter().map(|(pos, block)| (*pos, block)) }
+
+	pub fn len(&self) -> usize { self.blocks.len() }
+
+	pub fn is_empty(&self) -> bool { self.blocks.is_empty() }
+
+	/// The position of the circle's impetus.
+	pub fn impetus(&self) -> Result<BlockPos, CircleError> {
+		let impetuses: Vec<BlockPos> = self.blocks()
+			.filter(|(_, block)| matches!(block, CircleBlock::Impetus { .. }))
+			.map(|(pos, _)| pos)
+			.collect();
+
+		match impetuses.as_slice() {
+			[] => Err(CircleError::NoImpetus),
+			[impetus] => Ok(*impetus),
+			_ => Err(CircleError::TooManyImpetuses(impetuses)),
+		}
+	}
+
+	/// Sends a wave from the impetus, the way Hex Casting does: on each step, the wave leaves every block it has just
+	/// reached through each of the block's exits, into the blocks there that it can enter and hasn't reached before.
+	/// Slates are cast in the order the wave reaches them.
+	pub fn wave(&self) -> Result<Wave, CircleError> {
+		let impetus = self.impetus()?;
+		let mut reached = BTreeSet::from([impetus]);
+		let mut fronts = vec![vec![impetus]];
+		let mut slates = vec![];
+		let mut closed = false;
+
+		loop {
+			let mut next = vec![];
+			for &pos in fronts.last().into_iter().flatten() {
+				for exit in self.blocks[&pos].exits() {
+					let target = exit.step(pos);
+					let block = match self.blocks.get(&target) {
+						Some(block) if block.can_enter(exit) => block,
+						_ => continue,
+					};
+
+					if target == impetus {
+						closed = true;
+					} else if reached.insert(target) {
+						if let CircleBlock::Slate { pattern: Some(_), .. } = block {
+							slates.push(target);
+						}
+						next.push(target);
+					}
+				}
+			}
+
+			if next.is_empty() {
+				break
+			}
+			fronts.push(next);
+		}
+
+		Ok(Wave { fronts, slates, closed })
+	}
+
+	/// The patterns the circle casts, in order, or why it can't be cast.
+	pub fn patterns(&self) -> Result<Vec<HexPattern>, CircleError> {
+		let wave = self.wave()?;
+		if !wave.closed {
+			return Err(CircleError::NotClosed { stopped_at: wave.fronts.last().cloned().unwrap_or_default() })
+		}
+
+		Ok(wave.slates.iter().filter_map(|pos| match &self.blocks[pos] {
+			CircleBlock::Slate { pattern, .. } => pattern.clone(),
+			_ => None,
+		}).collect())
+	}
+
+	/// The patterns the circle casts, written the way hexes are, one per line.
+	pub fn hex_text(&self) -> Result<String, CircleError> {
+		Ok(self.patterns()?.iter().map(Renderable::canonical_text).collect::<Vec<_>>().join(",\n"))
+	}
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeSet;
+
+use crate::actions::registry::next_action;
+use crate::hex_pattern::HexPattern;
+use crate::history::History;
+use crate::simulator::{Action, ActionError, Frame, StackHolder, StackManager, StackState};
+use crate::stack_effect::{block_end, INTROSPECTION, RETROSPECTION};
+
+/// How many steps the branches can spend casting frames before the simulator gives up on them (when running) or
+/// pauses (when resuming), in case a hex casts itself forever.
+pub const STEP_LIMIT: usize = 100_000;
+
+/// Finishes every frame the branch has nothing left to cast in, starting the next iteration of any Thoth's Gambit it
+/// comes back to, so that the innermost frame (if there is one) has a pattern to cast next.
+fn settle(mut state: StackState) -> StackState {
+	while let Some(frame) = state.pop_frame() {
+		match frame {
+			Frame::Evaluate { patterns, next } if next < patterns.len() => {
+				state.push_frame(Frame::Evaluate { patterns, next });
+				break
+			},
+			Frame::Evaluate { .. } => {},
+			Frame::ForEach { code, data, next, base, mut acc } => {
+				// the frame is only on top before the first element, or once the code has been cast on an element.
+				if next > 0 {
+					acc.extend(state.stack().iter().cloned());
+				}
+
+				match data.get(next).cloned() {
+					Some(element) => {
+						let mut stack = base.clone();
+						stack.push(element);
+						state.set_stack(stack);
+
+						let patterns = code.clone();
+						state.push_frame(Frame::ForEach { code, data, next: next + 1, base, acc });
+						state.push_frame(Frame::Evaluate { patterns, next: 0 });
+					},
+					None => {
+						let mut stack = base;
+						stack.push(acc.into());
+						state.set_stack(stack);
+					},
+				}
+			},
+		}
+	}
+
+	state
+}
+
+/// Casts the next action in the branch's innermost frame. Mishaps are recorded at `pattern_index`, the pattern of the hex
+/// that pushed the frames.
+fn step_frame(mut state: StackState, pattern_index: usize) -> StackHolder {
+	let (patterns, next) = match state.pop_frame() {
+		Some(Frame::Evaluate { patterns, next }) => (patterns, next),
+		Some(frame) => {
+			state.push_frame(frame);
+			return StackHolder::single_state(settle(state))
+		},
+		None => return StackHolder::single_state(state),
+	};
+
+	// a frame with nothing left to cast is finished before its last action, so a hex that casts itself with Hermes'
+	// Gambit doesn't build up frames.
+	let (action, after) = next_action(&patterns, next);
+	if after < patterns.len() {
+		state.push_frame(Frame::Evaluate { patterns, next: after });
+	}
+
+	match action {
+		Some(action) => {
+			let mut results = StackHolder::single_state(state);
+			results.apply_action(action, pattern_index);
+			StackHolder::new(results.iter().cloned().map(|result| result.map(settle)).collect())
+		},
+		None => StackHolder::single(Err(ActionError::Indeterminate)),
+	}
+}
+
+/// Casts every frame the branches are casting to the end, recording mishaps at `pattern_index`, the pattern of the hex
+/// that pushed them. Gives up on any branch that spends too many steps casting frames.
+pub(crate) fn cast_frames(stack_manager: &mut StackManager, pattern_index: usize) {
+	stack_manager.step_branches(|state| StackHolder::single_state(settle(state)));
+
+	for _ in 0..STEP_LIMIT {
+		if stack_manager.stacks().iter().filter_map(|result| result.as_ref().ok()).all(|state| state.frames().is_empty()) {
+			return
+		}
+		stack_manager.step_branches(|state| {
+			if state.frames().is_empty() { StackHolder::single_state(state) } else { step_frame(state, pattern_index) }
+		});
+	}
+
+	stack_manager.step_branches(|state| {
+		if state.frames().is_empty() { StackHolder::single_state(state) } else { StackHolder::single(Err(ActionError::TooManySteps)) }
+	});
+}
+
+/// Casts a hex one pattern at a time, so the branches can be looked at between patterns. Patterns inside an
+/// Introspection block are stepped through one at a time too, though they are only pushed as a list at the end of the
+/// block. Hermes' and Thoth's Gambits push frames (see [`Frame`]) that each branch casts before the rest of the hex.
+pub struct Debugger {
+	patterns: Vec<HexPattern>,
+	stack_manager: StackManager,
+	progress: Progress,
+	/// the branches after each step, along with how far through the hex the debugger was.
+	history: History,
+	progress_history: Vec<Progress>
+}
+
+/// How far through the hex the debugger is.
+#[derive(Clone, Copy)]
+struct Progress {
+	/// the index of the next pattern of the hex to cast.
+	cursor: usize,
+	/// the Introspection block the cursor is in, as the indices of its Introspection and Retrospection.
+	block: Option<(usize, usize)>,
+	/// the index of the pattern of the hex that pushed the frames the branches are casting.
+	frames_from: usize,
+	/// how many steps have been spent casting frames since the branches last had none.
+	frame_steps: usize,
+	/// the index of the pattern the simulator doesn't know, once the cursor has reached it.
+	unknown: Option<usize>
+}
+
+impl Debugger {
+	/// A debugger paused before the first pattern of the hex, cast from the input state.
+	pub fn new(patterns: Vec<HexPattern>, input: StackState) -> Debugger {
+		let stack_manager = StackManager::new(StackHolder::single_state(input));
+		let mut history = History::default();
+		history.record(None, stack_manager.stacks());
+
+		Debugger {
+			patterns,
+			stack_manager,
+			progress: Progress { cursor: 0, block: None, frames_from: 0, frame_steps: 0, unknown: None },
+			history,
+			progress_history: vec![]
+		}
+	}
+
+	pub fn patterns(&self) -> &[HexPattern] { &self.patterns }
+
+	pub fn stacks(&self) -> &StackHolder { self.stack_manager.stacks() }
+
+	pub fn into_stack_manager(self) -> StackManager { self.stack_manager }
+
+	/// The branches after each step the debugger has taken (but not those taken by [`Debugger::run`]), starting with the
+	/// state the hex was cast from.
+	pub fn history(&self) -> &History { &self.history }
+
+	/// Goes back to how the debugger was after the `i`th step in its history, forgetting every step after it.
+	pub fn rewind(&mut self, i: usize) {
+		if let Some(snapshot) = self.history.get(i) {
+			self.stack_manager = StackManager::new(snapshot.stacks());
+			self.progress = if i == 0 {
+				Progress { cursor: 0, block: None, frames_from: 0, frame_steps: 0, unknown: None }
+			} else {
+				self.progress_history[i - 1]
+			};
+			self.history.truncate(i + 1);
+			self.progress_history.truncate(i);
+		}
+	}
+
+	/// The index of the pattern of the hex being cast: the next one, or the one whose frames the branches are casting.
+	pub fn position(&self) -> usize { if self.frame_depth() > 0 { self.progress.frames_from } else { self.progress.cursor } }
+
+	/// The index of the pattern the simulator doesn't know, if casting stopped there.
+	pub fn unknown_pattern(&self) -> Option<usize> { self.progress.unknown }
+
+	/// Whether every pattern has been cast, or casting can't carry on.
+	pub fn is_finished(&self) -> bool {
+		self.progress.unknown.is_some() || (self.progress.cursor >= self.patterns.len() && self.progress.block.is_none() && self.frame_depth() == 0)
+	}
+
+	/// The most frames any branch is casting.
+	pub fn frame_depth(&self) -> usize {
+		self.stacks().iter().filter_map(|result| result.as_ref().ok()).map(|state| state.frames().len()).max().unwrap_or(0)
+	}
+
+	/// How many Introspection blocks are open at the cursor.
+	pub fn escape_depth(&self) -> usize {
+		let start = match self.progress.block {
+			Some((start, _)) => start,
+			None => return 0,
+		};
+
+		self.patterns[start..self.progress.cursor].iter().fold(0, |depth, pattern| match pattern.angle_signature().as_str() {
+			INTROSPECTION => depth + 1,
+			RETROSPECTION => depth - 1,
+			_ => depth,
+		})
+	}
+
+	/// Casts the action as the pattern at `pattern_index`, then carries on from the pattern at `next`.
+	fn apply(&mut self, action: Box<dyn Action>, pattern_index: usize, next: usize) {
+		self.stack_manager.apply_action_at(action, pattern_index);
+		self.progress.cursor = next;
+		self.progress.frames_from = pattern_index;
+
+		if self.frame_depth() > 0 {
+			self.stack_manager.step_branches(|state| StackHolder::single_state(settle(state)));
+		}
+	}
+
+	/// Casts one pattern: the next one in the innermost frame of every branch casting frames, or else the next pattern of
+	/// the hex. If `escape` is false, an Introspection block is cast in one step instead of one pattern at a time.
+	fn step(&mut self, escape: bool) {
+		if self.is_finished() {
+			return
+		}
+
+		if self.frame_depth() > 0 {
+			let pattern_index = self.progress.frames_from;
+			self.progress.frame_steps += 1;
+			self.stack_manager.step_branches(|state| {
+				if state.frames().is_empty() { StackHolder::single_state(state) } else { step_frame(state, pattern_index) }
+			});
+			return
+		}
+		self.progress.frame_steps = 0;
+
+		match self.progress.block {
+			Some((_, end)) if self.progress.cursor < end => self.progress.cursor += 1,
+			Some((start, _)) => {
+				self.progress.block = None;
+				if let (Some(action), next) = next_action(&self.patterns, start) {
+					self.apply(action, start, next);
+				}
+			},
+			None if escape && self.patterns[self.progress.cursor].angle_signature() == INTROSPECTION => match block_end(&self.patterns, self.progress.cursor) {
+				Ok(end) => {
+					self.progress.block = Some((self.progress.cursor, end));
+					self.progress.cursor += 1;
+				},
+				Err(_) => self.progress.unknown = Some(self.progress.cursor),
+			},
+			None => match next_action(&self.patterns, self.progress.cursor) {
+				(Some(action), next) => self.apply(action, self.progress.cursor, next),
+				(None, _) => self.progress.unknown = Some(self.progress.cursor),
+			},
+		}
+	}
+
+	/// Steps, then records the branches in the history along with the pattern of the hex that was cast.
+	fn step_recorded(&mut self) {
+		if self.is_finished() {
+			return
+		}
+
+		let pattern_index = self.position();
+		self.step(true);
+		self.history.record(Some(pattern_index), self.stack_manager.stacks());
+		self.progress_history.push(self.progress);
+	}
+
+	/// Casts the next pattern, stepping into any frames it pushes.
+	pub fn step_into(&mut self) { self.step_recorded() }
+
+	/// Casts the next pattern, along with every pattern in any frames it pushes.
+	pub fn step_over(&mut self) {
+		let depth = self.frame_depth();
+		self.step_recorded();
+
+		for _ in 0..STEP_LIMIT {
+			if self.frame_depth() <= depth || self.is_finished() {
+				break
+			}
+			self.step_recorded();
+		}
+	}
+
+	/// Casts patterns until the cursor reaches a breakpoint (the index of a pattern of the hex) or the hex is finished.
+	/// Pauses early if the branches spend too many steps casting frames.
+	pub fn resume(&mut self, breakpoints: &BTreeSet<usize>) {
+		self.step_recorded();
+
+		for _ in 0..STEP_LIMIT {
+			if self.is_finished() || (self.frame_depth() == 0 && breakpoints.contains(&self.progress.cursor)) {
+				break
+			}
+			self.step_recorded();
+		}
+	}
+
+	/// Casts the rest of the hex, giving up on any branch that spends too many steps casting frames.
+	pub fn run(&mut self) {
+		while !self.is_finished() {
+			if self.progress.frame_steps >= STEP_LIMIT {
+				self.stack_manager.step_branches(|state| {
+					if state.frames().is_empty() { StackHolder::single_state(state) } else { StackHolder::single(Err(ActionError::TooManySteps)) }
+				});
+			}
+			self.step(false);
+		}
+	}
+}
@@ -11,7 +11,7 @@ pub enum HexError {
 	InvalidString
 }
 
-#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Clone)]
+#[derive(serde::Deserialize, serde::Serialize, Debug, PartialEq, Eq, Hash, Clone)]
 pub struct HexPattern {
 	pub start_dir: HexAbsoluteDir,
 	pub pattern_vec: Vec<HexDir>
@@ -50,6 +50,11 @@ impl HexPattern {
 		}
 	}
 	
+	/// The pattern's angles as a string like "waaw", which identifies it regardless of which way it was drawn.
+	pub fn angle_signature(&self) -> String {
+		self.pattern_vec.iter().map(HexDir::to_char).collect()
+	}
+
 	pub fn hex_pattern(start_dir: HexAbsoluteDir, pattern_vec: Vec<HexDir>) -> Result<HexPattern, HexError> {
 		let pattern = HexPattern { start_dir, pattern_vec };
 		
@@ -77,7 +82,7 @@ impl HexPattern {
 	}
 }
 
-#[derive(serde::Deserialize, serde::Serialize, PartialEq, Debug, Clone, Copy)]
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub enum HexDir {
 		A,
 		Q,
@@ -99,9 +104,19 @@ impl HexDir {
 
 		return (prev_coord + new_dir.coord_offset(), new_dir);
 	}
+
+	pub fn to_char(&self) -> char {
+		match self {
+			HexDir::A => 'a',
+			HexDir::Q => 'q',
+			HexDir::W => 'w',
+			HexDir::E => 'e',
+			HexDir::D => 'd',
+		}
+	}
 }
 
-#[derive(serde::Deserialize, serde::Serialize, PartialEq, ToPrimitive, FromPrimitive, Clone, Copy, Debug)]
+#[derive(serde::Deserialize, serde::Serialize, PartialEq, Eq, Hash, ToPrimitive, FromPrimitive, Clone, Copy, Debug)]
 pub enum HexAbsoluteDir {
 	East,
 	SouthEast,
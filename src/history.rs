@@ -0,0 +1,246 @@
+use std::fmt::{self, Display};
+use std::rc::Rc;
+
+use crate::simulator::{ActionError, Iota, StackHolder, StackState};
+
+struct Node {
+	iota: Iota,
+	below: Option<Rc<Node>>
+}
+
+/// A stack that can't be changed, sharing the iotas at its bottom with the stacks it was recorded after. Recording a
+/// step only stores the iotas the step pushed or changed, so long histories stay cheap.
+#[derive(Clone, Default)]
+pub struct SharedStack {
+	top: Option<Rc<Node>>,
+	len: usize
+}
+
+impl SharedStack {
+	/// Records the stack, sharing as many iotas at its bottom as it has in common with the previous stack.
+	pub fn record(previous: &SharedStack, stack: &[Iota]) -> SharedStack { SharedStack::record_onto(&mut previous.nodes(), stack) }
+
+	/// Records the stack after the one whose nodes (bottom first) are `nodes`, which are updated to the new stack's. Only
+	/// the iotas above the ones the stacks have in common are cloned.
+	fn record_onto(nodes: &mut Vec<Rc<Node>>, stack: &[Iota]) -> SharedStack {
+		let common = nodes.iter().zip(stack).take_while(|(node, iota)| node.iota == **iota).count();
+		nodes.truncate(common);
+
+		for iota in &stack[common..] {
+			let below = nodes.last().cloned();
+			nodes.push(Rc::new(Node { iota: iota.clone(), below }));
+		}
+
+		SharedStack { top: nodes.last().cloned(), len: nodes.len() }
+	}
+
+	/// The nodes of the stack, bottom first.
+	fn nodes(&self) -> Vec<Rc<Node>> {
+		let mut nodes = Vec::with_capacity(self.len);
+		let mut node = self.top.clone();
+		while let Some(inner) = node {
+			node = inner.below.clone();
+			nodes.push(inner);
+		}
+		nodes.reverse();
+		nodes
+	}
+
+	pub fn len(&self) -> usize { self.len }
+
+	pub fn is_empty(&self) -> bool { self.len == 0 }
+
+	/// The iotas of the stack, bottom first.
+	pub fn to_vec(&self) -> Vec<Iota> {
+		let mut stack = Vec::with_capacity(self.len);
+		let mut node = self.top.as_deref();
+		while let Some(inner) = node {
+			stack.push(inner.iota.clone());
+			node = inner.below.as_deref();
+		}
+		stack.reverse();
+		stack
+	}
+
+	/// How many iotas at the bottom of the two stacks are the same stored iotas, rather than equal copies.
+	pub fn shared_with(&self, other: &SharedStack) -> usize {
+		let len = usize::min(self.len, other.len);
+		match (self.bottom(len).top, other.bottom(len).top) {
+			(Some(mut a), Some(mut b)) => {
+				let mut depth = len;
+				while !Rc::ptr_eq(&a, &b) {
+					depth -= 1;
+					match (a.below.clone(), b.below.clone()) {
+						(Some(below_a), Some(below_b)) => { a = below_a; b = below_b; },
+						_ => return 0,
+					}
+				}
+				depth
+			},
+			_ => 0,
+		}
+	}
+
+	/// The bottom `len` iotas of the stack.
+	fn bottom(&self, len: usize) -> SharedStack {
+		let mut top = self.top.clone();
+		for _ in len..self.len {
+			top = top.and_then(|node| node.below.clone());
+		}
+		SharedStack { top, len: usize::min(len, self.len) }
+	}
+}
+
+/// A branch as it was at some point in the history: its stack, and the rest of its state (with an empty stack), which is
+/// shared with the previous point if the step didn't change it.
+#[derive(Clone)]
+pub struct RecordedBranch {
+	stack: SharedStack,
+	rest: Rc<StackState>
+}
+
+impl RecordedBranch {
+	pub fn stack(&self) -> &SharedStack { &self.stack }
+
+	/// Restores the branch's state.
+	pub fn state(&self) -> StackState { self.rest.with_stack(self.stack.to_vec()) }
+}
+
+/// The branches as they were after one step of a hex.
+pub struct Snapshot {
+	pattern_index: Option<usize>,
+	branches: Vec<Result<RecordedBranch, ActionError>>
+}
+
+impl Snapshot {
+	/// The index of the pattern of the hex whose step produced the branches, or None for the state the hex was cast from.
+	pub fn pattern_index(&self) -> Option<usize> { self.pattern_index }
+
+	pub fn branches(&self) -> &[Result<RecordedBranch, ActionError>] { &self.branches }
+
+	/// Restores the branches.
+	pub fn stacks(&self) -> StackHolder {
+		StackHolder::new(self.branches.iter().map(|branch| branch.as_ref().map(RecordedBranch::state).map_err(Clone::clone)).collect())
+	}
+}
+
+/// The branches of a hex after each step it has been cast for, oldest first.
+#[derive(Default)]
+pub struct History {
+	snapshots: Vec<Snapshot>,
+	/// the nodes (bottom first) of each branch's stack in the last snapshot, so that recording the next step doesn't
+	/// have to walk them; empty if the snapshots have been truncated since.
+	last_nodes: Vec<Vec<Rc<Node>>>
+}
+
+impl History {
+	/// Records the branches after a step of the pattern at `pattern_index`. Each branch shares what it can with the branch
+	/// at the same index in the previous snapshot.
+	pub fn record(&mut self, pattern_index: Option<usize>, stacks: &StackHolder) {
+		let previous = self.snapshots.last().map_or(&[][..], |snapshot| &snapshot.branches[..]);
+		if self.last_nodes.len() != previous.len() {
+			self.last_nodes = previous.iter().map(|branch| branch.as_ref().map_or(vec![], |branch| branch.stack.nodes())).collect();
+		}
+		self.last_nodes.resize(stacks.iter().count(), vec![]);
+
+		let branches = stacks.iter().zip(&mut self.last_nodes).enumerate().map(|(i, (result, nodes))| {
+			let state = match result {
+				Ok(state) => state,
+				Err(action_error) => {
+					nodes.clear();
+					return Err(*action_error)
+				},
+			};
+			let previous = previous.get(i).and_then(|branch| branch.as_ref().ok());
+
+			let rest = state.with_stack(vec![]);
+			let rest = match previous {
+				Some(previous) if *previous.rest == rest => previous.rest.clone(),
+				_ => Rc::new(rest),
+			};
+			let stack = SharedStack::record_onto(nodes, state.stack());
+
+			Ok(RecordedBranch { stack, rest })
+		}).collect();
+
+		self.snapshots.push(Snapshot { pattern_index, branches });
+	}
+
+	pub fn len(&self) -> usize { self.snapshots.len() }
+
+	pub fn is_empty(&self) -> bool { self.snapshots.is_empty() }
+
+	pub fn get(&self, i: usize) -> Option<&Snapshot> { self.snapshots.get(i) }
+
+	pub fn last(&self) -> Option<&Snapshot> { self.snapshots.last() }
+
+	/// Forgets every snapshot after the first `len`.
+	pub fn truncate(&mut self, len: usize) {
+		if len < self.snapshots.len() {
+			self.snapshots.truncate(len);
+			self.last_nodes.clear();
+		}
+	}
+
+	/// How each branch's stack changed in the step that produced snapshot `i`, compared with the branch at the same index
+	/// before it. None for branches that aren't in both snapshots, or that the simulator gave up on.
+	pub fn changes(&self, i: usize) -> Vec<Option<Vec<StackChange>>> {
+		let (before, after) = match (i.checked_sub(1).and_then(|j| self.get(j)), self.get(i)) {
+			(Some(before), Some(after)) => (before, after),
+			(None, Some(after)) => return vec![None; after.branches.len()],
+			_ => return vec![],
+		};
+
+		after.branches.iter().enumerate().map(|(j, branch)| match (before.branches.get(j), branch) {
+			(Some(Ok(before)), Ok(after)) => {
+				let (before_nodes, after_nodes) = (before.stack.nodes(), after.stack.nodes());
+				let before_iotas: Vec<&Iota> = before_nodes.iter().map(|node| &node.iota).collect();
+				let after_iotas: Vec<&Iota> = after_nodes.iter().map(|node| &node.iota).collect();
+				Some(diff_above(&before_iotas, &after_iotas, before.stack.shared_with(&after.stack)))
+			},
+			_ => None,
+		}).collect()
+	}
+}
+
+/// A difference between two stacks. Indices count from the bottom of the stack.
+#[derive(Clone, PartialEq)]
+pub enum StackChange {
+	Popped { index: usize, iota: Iota },
+	Pushed { index: usize, iota: Iota },
+	Changed { index: usize, before: Iota, after: Iota }
+}
+
+impl Display for StackChange {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			StackChange::Popped { index, iota } => write!(f, "popped {} from {}", iota, index),
+			StackChange::Pushed { index, iota } => write!(f, "pushed {} to {}", iota, index),
+			StackChange::Changed { index, before, after } => write!(f, "changed {} to {} at {}", before, after, index),
+		}
+	}
+}
+
+/// How the stack went from `before` to `after`: the iotas above the ones they have in common at the bottom were popped
+/// and new ones pushed, or, if as many were pushed as were popped, changed in place. Popped iotas are listed top first.
+pub fn diff(before: &[Iota], after: &[Iota]) -> Vec<StackChange> {
+	let before: Vec<&Iota> = before.iter().collect();
+	let after: Vec<&Iota> = after.iter().collect();
+	diff_above(&before, &after, 0)
+}
+
+/// Like [`diff`], for stacks whose bottom `shared` iotas are known to be the same.
+fn diff_above(before: &[&Iota], after: &[&Iota], shared: usize) -> Vec<StackChange> {
+	let common = shared + before[shared..].iter().zip(&after[shared..]).take_while(|(a, b)| a == b).count();
+
+	if before.len() == after.len() {
+		return (common..after.len())
+			.filter(|&index| before[index] != after[index])
+			.map(|index| StackChange::Changed { index, before: before[index].clone(), after: after[index].clone() })
+			.collect()
+	}
+
+	let popped = (common..before.len()).rev().map(|index| StackChange::Popped { index, iota: before[index].clone() });
+	let pushed = (common..after.len()).map(|index| StackChange::Pushed { index, iota: after[index].clone() });
+	popped.chain(pushed).collect()
+}
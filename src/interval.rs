@@ -0,0 +1,215 @@
+use std::fmt;
+
+use itertools::Either::{self, Left, Right};
+
+use crate::numbers;
+
+/// The bounds on a double whose exact value isn't known. The bounds are inclusive, and may be infinite.
+/// If `integer` is true the value is guaranteed to be a whole number.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Interval {
+	#[serde(with = "crate::serialization::float")]
+	pub lower: f64,
+	#[serde(with = "crate::serialization::float")]
+	pub upper: f64,
+	pub integer: bool
+}
+
+impl Default for Interval {
+	fn default() -> Self { Interval::unbounded() }
+}
+
+impl Interval {
+	/// An interval that could hold any double.
+	pub fn unbounded() -> Interval { Interval { lower: f64::NEG_INFINITY, upper: f64::INFINITY, integer: false } }
+
+	pub fn new(lower: f64, upper: f64) -> Interval { Interval { lower, upper, integer: false } }
+
+	/// An interval of whole numbers; the bounds are rounded inwards to the nearest whole number.
+	pub fn integer(lower: f64, upper: f64) -> Interval { Interval { lower: lower.ceil(), upper: upper.floor(), integer: true } }
+
+	/// The interval containing only `d`.
+	pub fn point(d: f64) -> Interval { Interval { lower: d, upper: d, integer: d.fract() == 0.0 } }
+
+	pub fn is_unbounded(&self) -> bool { self.lower == f64::NEG_INFINITY && self.upper == f64::INFINITY && !self.integer }
+
+	pub fn contains(&self, d: f64) -> bool { self.lower <= d && d <= self.upper && (!self.integer || d.fract() == 0.0) }
+
+	pub fn contains_zero(&self) -> bool { self.lower <= 0.0 && 0.0 <= self.upper }
+
+	/// Returns true if the interval could hold a value within `tolerance` of zero.
+	pub fn may_be_zero(&self, tolerance: f64) -> bool { self.lower < tolerance && self.upper > -tolerance }
+
+	/// The smallest interval containing both intervals.
+	pub fn hull(&self, other: &Interval) -> Interval {
+		Interval { lower: f64::min(self.lower, other.lower), upper: f64::max(self.upper, other.upper), integer: self.integer && other.integer }
+	}
+
+	/// Turns the interval into a double, which is known if the interval only holds one value.
+	pub fn into_double(self) -> Either<f64, Interval> {
+		if self.lower == self.upper && self.lower.is_finite() {
+			Left(self.lower)
+		} else {
+			Right(self)
+		}
+	}
+
+	/// Converts a double that may or may not be known into an interval.
+	pub fn from_double(d: &Either<f64, Interval>) -> Interval {
+		match d {
+			Left(d) => Interval::point(*d),
+			Right(interval) => *interval,
+		}
+	}
+
+	pub fn add(&self, other: &Interval) -> Interval {
+		Interval { lower: self.lower + other.lower, upper: self.upper + other.upper, integer: self.integer && other.integer }.fix_nan()
+	}
+
+	pub fn neg(&self) -> Interval {
+		Interval { lower: -self.upper, upper: -self.lower, integer: self.integer }
+	}
+
+	pub fn sub(&self, other: &Interval) -> Interval { self.add(&other.neg()) }
+
+	pub fn mul(&self, other: &Interval) -> Interval {
+		let products = [
+			mul_bound(self.lower, other.lower),
+			mul_bound(self.lower, other.upper),
+			mul_bound(self.upper, other.lower),
+			mul_bound(self.upper, other.upper)
+		];
+
+		Interval {
+			lower: products.iter().cloned().fold(f64::INFINITY, f64::min),
+			upper: products.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+			integer: self.integer && other.integer
+		}
+	}
+
+	/// Divides by the other interval, ignoring the case where the divisor is zero (that case is a mishap, and should be
+	/// handled by the caller using [`Interval::contains_zero`]).
+	pub fn div(&self, other: &Interval) -> Interval {
+		if other.lower > 0.0 || other.upper < 0.0 {
+			self.mul(&Interval::new(1.0 / other.upper, 1.0 / other.lower))
+		} else if other.lower == 0.0 && other.upper > 0.0 {
+			self.mul(&Interval::new(1.0 / other.upper, f64::INFINITY))
+		} else if other.upper == 0.0 && other.lower < 0.0 {
+			self.mul(&Interval::new(f64::NEG_INFINITY, 1.0 / other.lower))
+		} else {
+			Interval::unbounded()
+		}
+	}
+
+	pub fn abs(&self) -> Interval {
+		if self.lower >= 0.0 {
+			*self
+		} else if self.upper <= 0.0 {
+			self.neg()
+		} else {
+			Interval { lower: 0.0, upper: f64::max(-self.lower, self.upper), integer: self.integer }
+		}
+	}
+
+	/// Rounds both bounds down, as [`numbers::floor`] does.
+	pub fn floor(&self) -> Interval { Interval { lower: numbers::floor(self.lower), upper: numbers::floor(self.upper), integer: true } }
+
+	/// Rounds both bounds up, as [`numbers::ceil`] does.
+	pub fn ceil(&self) -> Interval { Interval { lower: numbers::ceil(self.lower), upper: numbers::ceil(self.upper), integer: true } }
+
+	/// The remainder after dividing by the other interval, with the sign of the dividend (see [`numbers::modulo`]).
+	pub fn rem(&self, other: &Interval) -> Interval {
+		let max_divisor = other.abs().upper;
+		let integer = self.integer && other.integer;
+
+		if self.lower == self.upper && other.lower == other.upper {
+			let remainder = numbers::modulo(self.lower, other.lower);
+			Interval { lower: remainder, upper: remainder, integer }
+		} else if self.lower >= 0.0 {
+			if self.upper < other.abs().lower {
+				*self
+			} else {
+				Interval { lower: 0.0, upper: f64::min(self.upper, max_divisor), integer }
+			}
+		} else if self.upper <= 0.0 {
+			Interval { lower: f64::max(self.lower, -max_divisor), upper: 0.0, integer }
+		} else {
+			Interval { lower: f64::max(self.lower, -max_divisor), upper: f64::min(self.upper, max_divisor), integer }
+		}
+	}
+
+	pub fn pow(&self, other: &Interval) -> Interval {
+		// only track the common case of a non-negative base; anything else could be almost anything (or NaN).
+		if self.lower < 0.0 {
+			return Interval::unbounded()
+		}
+
+		let powers = [
+			self.lower.powf(other.lower),
+			self.lower.powf(other.upper),
+			self.upper.powf(other.lower),
+			self.upper.powf(other.upper)
+		];
+
+		if powers.iter().any(|p| p.is_nan()) {
+			return Interval::unbounded()
+		}
+
+		Interval {
+			lower: powers.iter().cloned().fold(f64::INFINITY, f64::min),
+			upper: powers.iter().cloned().fold(f64::NEG_INFINITY, f64::max),
+			integer: self.integer && other.integer && other.lower >= 0.0
+		}
+	}
+
+	/// Infinite bounds can produce NaN (e.g. inf - inf); those bounds are widened back out to infinity.
+	fn fix_nan(self) -> Interval {
+		Interval {
+			lower: if self.lower.is_nan() { f64::NEG_INFINITY } else { self.lower },
+			upper: if self.upper.is_nan() { f64::INFINITY } else { self.upper },
+			integer: self.integer
+		}
+	}
+}
+
+/// Multiplies two bounds, treating 0 * infinity as 0 since the bound that is 0 is reached exactly.
+fn mul_bound(b0: f64, b1: f64) -> f64 {
+	if b0 == 0.0 || b1 == 0.0 { 0.0 } else { b0 * b1 }
+}
+
+impl fmt::Display for Interval {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.integer {
+			write!(f, "integer in [{}, {}]", self.lower, self.upper)
+		} else {
+			write!(f, "in [{}, {}]", self.lower, self.upper)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn floor_is_integer() {
+		let floored = Interval::new(0.0, 3.5).floor();
+		assert_eq!(floored, Interval::integer(0.0, 3.0));
+	}
+
+	#[test]
+	fn mul_with_infinite_bounds() {
+		let product = Interval::new(0.0, 2.0).mul(&Interval::new(1.0, f64::INFINITY));
+		assert_eq!(product, Interval::new(0.0, f64::INFINITY));
+		assert_eq!(Interval::point(0.0).mul(&Interval::unbounded()).into_double(), Left(0.0));
+	}
+
+	#[test]
+	fn div_by_positive() {
+		let quotient = Interval::new(2.0, 4.0).div(&Interval::new(1.0, 2.0));
+		assert_eq!(quotient, Interval::new(1.0, 4.0));
+		assert!(Interval::new(-1.0, 1.0).may_be_zero(0.0001));
+		assert!(!Interval::new(1.0, 2.0).may_be_zero(0.0001));
+		assert!(Interval::new(0.0, 2.0).contains_zero());
+	}
+}
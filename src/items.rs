@@ -0,0 +1,201 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::hex_pattern::HexPattern;
+use crate::interval::Interval;
+use crate::media::describe_dust;
+use crate::serialization::{from_json, FormatError};
+use crate::simulator::Iota;
+
+/// The most pages a spellbook has, as in Hex Casting.
+pub const SPELLBOOK_PAGES: usize = 64;
+
+/// The kinds of item a hex can read, write or be cast from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemKind {
+	/// Holds one iota.
+	Focus,
+	/// Holds an iota on each of its pages, one of which is selected.
+	Spellbook,
+	/// Holds a hex and media; can be recharged.
+	Trinket,
+	/// Holds a hex and media; can't be recharged, and breaks when its media runs out.
+	Cypher,
+	/// Holds a hex and media; can be recharged, and draws on the caster's media once its own runs out.
+	Artifact
+}
+
+impl ItemKind {
+	pub const ALL: [ItemKind; 5] = [ItemKind::Focus, ItemKind::Spellbook, ItemKind::Trinket, ItemKind::Cypher, ItemKind::Artifact];
+
+	/// Whether items of this kind hold iotas, which Scribe's Reflection and Scribe's Gambit read and write.
+	pub fn holds_iotas(&self) -> bool { matches!(self, ItemKind::Focus | ItemKind::Spellbook) }
+
+	/// Whether items of this kind hold a hex, which is cast when the item is used.
+	pub fn holds_hex(&self) -> bool { !self.holds_iotas() }
+}
+
+impl fmt::Display for ItemKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ItemKind::Focus => write!(f, "focus"),
+			ItemKind::Spellbook => write!(f, "spellbook"),
+			ItemKind::Trinket => write!(f, "trinket"),
+			ItemKind::Cypher => write!(f, "cypher"),
+			ItemKind::Artifact => write!(f, "artifact"),
+		}
+	}
+}
+
+/// A focus or a spellbook page: the iota written to it, if any, and whether it has been sealed with wax so that it can't
+/// be written to again.
+#[derive(Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Page {
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub iota: Option<Iota>,
+	#[serde(skip_serializing_if = "std::ops::Not::not")]
+	pub sealed: bool
+}
+
+impl fmt::Debug for Page {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.iota {
+			Some(iota) => write!(f, "{}", iota)?,
+			None => write!(f, "empty")?,
+		}
+		if self.sealed {
+			write!(f, " (sealed)")?;
+		}
+		Ok(())
+	}
+}
+
+/// The media stored in an item that casts a hex, in media units (see [`crate::media`]).
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Charge {
+	pub media: f64,
+	pub capacity: f64
+}
+
+/// Why an item can't be read or written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemError {
+	/// The item doesn't hold iotas.
+	NotIotaHolder(ItemKind),
+	/// The selected page is sealed.
+	Sealed,
+	/// The item has no page at the selected index.
+	NoPage(usize)
+}
+
+impl fmt::Display for ItemError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ItemError::NotIotaHolder(kind) => write!(f, "a {} doesn't hold iotas", kind),
+			ItemError::Sealed => write!(f, "the page is sealed"),
+			ItemError::NoPage(page) => write!(f, "there is no page {}", page + 1),
+		}
+	}
+}
+
+/// An item the caster holds or casts from.
+///
+/// Written in JSON as e.g. `{"kind": "focus", "pages": [{"iota": {"type": "text", "text": "(0, 1, 0)"}}]}`, or `{"kind": "trinket", "hex":
+/// ["HexPattern(qaq, EAST)"], "media": {"media": 100000, "capacity": 100000}}`. A spellbook's `page` is the index of the
+/// selected page.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Item {
+	pub kind: ItemKind,
+	/// the hex the item casts, if it holds one.
+	#[serde(default, with = "crate::serialization::hex_text", skip_serializing_if = "Vec::is_empty")]
+	pub hex: Vec<HexPattern>,
+	/// the item's media, or None if it isn't known.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub media: Option<Charge>,
+	/// the pages of a spellbook, or the single page of a focus.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub pages: Vec<Page>,
+	#[serde(default)]
+	pub page: usize
+}
+
+impl Item {
+	/// An empty item of the kind: a blank focus or spellbook, or an item with no hex and unknown media.
+	pub fn new(kind: ItemKind) -> Item { Item { kind, hex: vec![], media: None, pages: vec![], page: 0 } }
+
+	/// Reads an item from a file written in the versioned JSON format of [`crate::serialization`].
+	pub fn load(path: &Path) -> Result<Item, FormatError> {
+		let json = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+		from_json(&json)
+	}
+
+	/// The selected page; a focus only has the one. A page past the end of the item's pages is blank.
+	fn selected(&self) -> Result<Option<&Page>, ItemError> {
+		match self.kind {
+			ItemKind::Focus => Ok(self.pages.first()),
+			ItemKind::Spellbook if self.page < SPELLBOOK_PAGES => Ok(self.pages.get(self.page)),
+			ItemKind::Spellbook => Err(ItemError::NoPage(self.page)),
+			kind => Err(ItemError::NotIotaHolder(kind)),
+		}
+	}
+
+	/// The iota on the selected page, which is Null if nothing has been written there.
+	pub fn read(&self) -> Result<Iota, ItemError> {
+		Ok(self.selected()?.and_then(|page| page.iota.clone()).unwrap_or(Iota::Widget))
+	}
+
+	/// Writes the iota to the selected page, unless the page is sealed.
+	pub fn write(&mut self, iota: Iota) -> Result<(), ItemError> {
+		if self.selected()?.map_or(false, |page| page.sealed) {
+			return Err(ItemError::Sealed)
+		}
+
+		let index = if self.kind == ItemKind::Focus { 0 } else { self.page };
+		if self.pages.len() <= index {
+			self.pages.resize(index + 1, Page::default());
+		}
+		self.pages[index].iota = Some(iota);
+		Ok(())
+	}
+
+	/// Whether Scribe's Reflection can read the item.
+	pub fn readable(&self) -> bool { self.selected().is_ok() }
+
+	/// Whether Scribe's Gambit can write to the item.
+	pub fn writable(&self) -> bool { matches!(self.selected(), Ok(page) if !page.map_or(false, |page| page.sealed)) }
+
+	/// Selects a spellbook page, by its index.
+	pub fn select_page(&mut self, page: usize) -> Result<(), ItemError> {
+		match self.kind {
+			ItemKind::Spellbook if page < SPELLBOOK_PAGES => {
+				self.page = page;
+				Ok(())
+			},
+			ItemKind::Spellbook => Err(ItemError::NoPage(page)),
+			kind => Err(ItemError::NotIotaHolder(kind)),
+		}
+	}
+
+	/// How much media the item has left after spending `cost`, if its media is known; the charge can't go below zero.
+	pub fn media_after(&self, cost: f64) -> Option<f64> { self.media.map(|charge| (charge.media - cost).max(0.0)) }
+}
+
+impl fmt::Display for Item {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.kind {
+			ItemKind::Focus => write!(f, "focus: {:?}", self.pages.first().cloned().unwrap_or_default()),
+			ItemKind::Spellbook => write!(f, "spellbook, page {}: {:?}", self.page + 1, self.pages.get(self.page).cloned().unwrap_or_default()),
+			kind => {
+				write!(f, "{} with {} {}, ", kind, self.hex.len(), if self.hex.len() == 1 { "pattern" } else { "patterns" })?;
+				match self.media {
+					Some(charge) => write!(
+						f, "{} of {}", describe_dust(&Interval::point(charge.media)), describe_dust(&Interval::point(charge.capacity))
+					),
+					None => write!(f, "media unknown"),
+				}
+			},
+		}
+	}
+}
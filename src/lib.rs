@@ -1,9 +1,25 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
 mod app;
+pub mod akashic;
+pub mod circle;
+pub mod debugger;
 pub mod hex_pattern;
+pub mod history;
+pub mod interval;
+pub mod items;
+pub mod matrix;
+pub mod media;
+pub mod numbers;
 mod parsing;
 mod rendering;
+pub mod serialization;
 pub mod simulator;
+pub mod stack_effect;
+pub mod symbolic;
+pub mod type_check;
+pub mod version;
+pub mod world;
 pub mod actions;
+pub mod addons;
 pub use app::HexeditApp;
\ No newline at end of file
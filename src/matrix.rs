@@ -0,0 +1,163 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::numbers::format_double;
+
+/// Determinants closer to zero than this are treated as zero when inverting, so that nearly singular matrices aren't
+/// inverted into huge values.
+const SINGULAR: f64 = 1e-10;
+
+/// A matrix of doubles with at least one row and column, as added by MoreIotas.
+#[derive(Clone, Debug)]
+pub struct Matrix {
+	rows: usize,
+	columns: usize,
+	/// the values in row-major order.
+	values: Vec<f64>
+}
+
+impl Matrix {
+	/// A matrix with the values in row-major order, or None if there isn't a value for every entry.
+	pub fn new(rows: usize, columns: usize, values: Vec<f64>) -> Option<Matrix> {
+		(rows > 0 && columns > 0 && values.len() == rows * columns).then(|| Matrix { rows, columns, values })
+	}
+
+	/// A matrix from its rows, or None if there are no rows, or they aren't all the same (non-zero) length.
+	pub fn from_rows(rows: &[Vec<f64>]) -> Option<Matrix> {
+		let columns = rows.first()?.len();
+		if rows.iter().any(|row| row.len() != columns) {
+			return None
+		}
+		Matrix::new(rows.len(), columns, rows.concat())
+	}
+
+	pub fn identity(size: usize) -> Matrix {
+		let values = (0..size * size).map(|i| if i / size == i % size { 1.0 } else { 0.0 }).collect();
+		Matrix { rows: size, columns: size, values }
+	}
+
+	pub fn rows(&self) -> usize { self.rows }
+
+	pub fn columns(&self) -> usize { self.columns }
+
+	pub fn dimensions(&self) -> (usize, usize) { (self.rows, self.columns) }
+
+	pub fn values(&self) -> &[f64] { &self.values }
+
+	pub fn get(&self, row: usize, column: usize) -> f64 { self.values[row * self.columns + column] }
+
+	/// The rows of the matrix, top first.
+	pub fn to_rows(&self) -> Vec<Vec<f64>> { self.values.chunks(self.columns).map(<[f64]>::to_vec).collect() }
+
+	pub fn transpose(&self) -> Matrix {
+		let values = (0..self.columns).flat_map(|column| (0..self.rows).map(move |row| self.get(row, column))).collect();
+		Matrix { rows: self.columns, columns: self.rows, values }
+	}
+
+	/// The product of the matrices, or None if this one doesn't have as many columns as the other has rows.
+	pub fn mul(&self, other: &Matrix) -> Option<Matrix> {
+		if self.columns != other.rows {
+			return None
+		}
+
+		let values = (0..self.rows).flat_map(|row| (0..other.columns).map(move |column| {
+			(0..self.columns).map(|k| self.get(row, k) * other.get(k, column)).sum()
+		})).collect();
+		Some(Matrix { rows: self.rows, columns: other.columns, values })
+	}
+
+	/// The determinant, or None if the matrix isn't square.
+	pub fn determinant(&self) -> Option<f64> {
+		if self.rows != self.columns {
+			return None
+		}
+
+		let (_, determinant) = self.eliminate();
+		Some(determinant)
+	}
+
+	/// The inverse, or None if the matrix isn't square or is singular.
+	pub fn inverse(&self) -> Option<Matrix> {
+		if self.rows != self.columns {
+			return None
+		}
+
+		let (inverse, determinant) = self.eliminate();
+		(determinant.abs() > SINGULAR).then(|| inverse)
+	}
+
+	/// Gauss-Jordan elimination with partial pivoting on a square matrix; returns what the identity becomes (the
+	/// inverse, if the matrix isn't singular), and the determinant.
+	fn eliminate(&self) -> (Matrix, f64) {
+		let size = self.rows;
+		let mut left = self.clone();
+		let mut right = Matrix::identity(size);
+		let mut determinant = 1.0;
+
+		for column in 0..size {
+			let magnitude = |row: usize| left.get(row, column).abs();
+			let pivot = (column..size)
+				.max_by(|&a, &b| magnitude(a).partial_cmp(&magnitude(b)).unwrap_or(std::cmp::Ordering::Equal))
+				.unwrap_or(column);
+			if left.get(pivot, column).abs() <= SINGULAR {
+				return (right, 0.0)
+			}
+			if pivot != column {
+				left.swap_rows(pivot, column);
+				right.swap_rows(pivot, column);
+				determinant = -determinant;
+			}
+
+			let scale = left.get(column, column);
+			determinant *= scale;
+			for k in 0..size {
+				left.values[column * size + k] /= scale;
+				right.values[column * size + k] /= scale;
+			}
+
+			for row in (0..size).filter(|&row| row != column) {
+				let factor = left.get(row, column);
+				for k in 0..size {
+					left.values[row * size + k] -= factor * left.get(column, k);
+					right.values[row * size + k] -= factor * right.get(column, k);
+				}
+			}
+		}
+
+		(right, determinant)
+	}
+
+	fn swap_rows(&mut self, a: usize, b: usize) {
+		for k in 0..self.columns {
+			self.values.swap(a * self.columns + k, b * self.columns + k);
+		}
+	}
+}
+
+/// Displayed as its rows, separated by semicolons, e.g. "Matrix[1, 2; 3, 4]".
+impl fmt::Display for Matrix {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let rows: Vec<String> = self.values.chunks(self.columns)
+			.map(|row| row.iter().copied().map(format_double).collect::<Vec<_>>().join(", "))
+			.collect();
+		write!(f, "Matrix[{}]", rows.join("; "))
+	}
+}
+
+/// Reads a matrix the way it is displayed; the "Matrix" before the brackets is optional.
+impl FromStr for Matrix {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let s = s.trim();
+		let inner = s.strip_prefix("Matrix").unwrap_or(s).trim()
+			.strip_prefix('[').and_then(|s| s.strip_suffix(']'))
+			.ok_or_else(|| format!("{:?} isn't written as [a, b; c, d]", s))?;
+
+		let rows = inner.split(';').map(|row| row.split(',').map(|value| {
+			value.trim().parse::<f64>().map_err(|_| format!("{:?} isn't a number", value.trim()))
+		}).collect()).collect::<Result<Vec<Vec<f64>>, String>>()?;
+
+		Matrix::from_rows(&rows).ok_or_else(|| "every row of a matrix must have the same number of values".to_string())
+	}
+}
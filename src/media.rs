@@ -0,0 +1,52 @@
+use crate::interval::Interval;
+
+/// How many media units one amethyst dust is worth; costs are counted in media units.
+pub const DUST: f64 = 10000.0;
+pub const SHARD: f64 = 5.0 * DUST;
+pub const CHARGED_AMETHYST: f64 = 10.0 * DUST;
+
+/// Formats an amount with at most two decimal places, dropping trailing zeros.
+fn format_amount(amount: f64) -> String {
+	if amount.is_infinite() {
+		return "∞".to_string()
+	}
+
+	let formatted = format!("{:.2}", amount);
+	formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn format_range(lower: f64, upper: f64, unit: f64) -> String {
+	if lower == upper {
+		format_amount(lower / unit)
+	} else {
+		format!("{}-{}", format_amount(lower / unit), format_amount(upper / unit))
+	}
+}
+
+/// Describes a cost in dust alone, e.g. "0.5 dust" or "1-2 dust".
+pub fn describe_dust(cost: &Interval) -> String { format!("{} dust", format_range(cost.lower, cost.upper, DUST)) }
+
+/// Describes a cost in dust, amethyst shards and charged amethyst, e.g. "5 dust (1 shard, 0.5 charged amethyst)".
+/// If the cost isn't known exactly, each amount is given as a range.
+pub fn describe_cost(cost: &Interval) -> String {
+	let shards = format_range(cost.lower, cost.upper, SHARD);
+	format!(
+		"{} dust ({} {}, {} charged amethyst)",
+		format_range(cost.lower, cost.upper, DUST),
+		shards,
+		if shards == "1" { "shard" } else { "shards" },
+		format_range(cost.lower, cost.upper, CHARGED_AMETHYST)
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn describe_costs() {
+		assert_eq!(describe_cost(&Interval::point(5.0 * DUST)), "5 dust (1 shard, 0.5 charged amethyst)");
+		assert_eq!(describe_cost(&Interval::new(0.0, 1.5 * DUST)), "0-1.5 dust (0-0.3 shards, 0-0.15 charged amethyst)");
+		assert_eq!(describe_cost(&Interval::new(DUST, f64::INFINITY)), "1-∞ dust (0.2-∞ shards, 0.1-∞ charged amethyst)");
+	}
+}
@@ -0,0 +1,124 @@
+//! How Hex Casting treats numbers: which doubles count as equal, how they are rounded, and how the game's stack display
+//! writes them. Everything in the simulator that compares, rounds or displays a double goes through here, so that it
+//! behaves the way the game does in one place.
+
+use std::cell::Cell;
+
+/// Hex Casting compares doubles (and the components of vectors) as equal if they are within this distance of each other.
+pub const TOLERANCE: f64 = 0.0001;
+
+/// Vectors shorter than this are normalised to zero, as Minecraft does.
+pub const NORMALIZE_EPSILON: f64 = 1.0e-4;
+
+/// Whether Hex Casting counts the doubles as equal.
+pub fn tolerates(d0: f64, d1: f64) -> bool { (d0 - d1).abs() < TOLERANCE }
+
+/// Whether Hex Casting counts the vectors as equal; each component is compared on its own.
+pub fn vec_tolerates(v0: (f64, f64, f64), v1: (f64, f64, f64)) -> bool {
+	tolerates(v0.0, v1.0) && tolerates(v0.1, v1.1) && tolerates(v0.2, v1.2)
+}
+
+/// Whether Hex Casting counts the double as zero, e.g. when deciding if it is truthy.
+pub fn is_zero(d: f64) -> bool { tolerates(d, 0.0) }
+
+/// Rounds to the nearest whole number, with halves rounded up (towards positive infinity) as Java's `Math.round` does.
+pub fn round(d: f64) -> f64 { (d + 0.5).floor() }
+
+/// Rounds down, as Floor Purification does. Values just below a whole number aren't snapped to it, even if they are
+/// displayed as that number.
+pub fn floor(d: f64) -> f64 { d.floor() }
+
+/// Rounds up, as Ceiling Purification does. Values just above a whole number aren't snapped to it.
+pub fn ceil(d: f64) -> f64 { d.ceil() }
+
+/// The remainder of dividing by `d1`, with the sign of the dividend, as Modulus Distillation does (Java's `%`).
+pub fn modulo(d0: f64, d1: f64) -> f64 { d0 % d1 }
+
+/// The unit vector along the vector, or zero for a vector too short to have a direction, as Minecraft's
+/// `Vec3.normalize` does.
+pub fn normalize(v: (f64, f64, f64)) -> (f64, f64, f64) {
+	let length = (v.0 * v.0 + v.1 * v.1 + v.2 * v.2).sqrt();
+	if length < NORMALIZE_EPSILON {
+		(0.0, 0.0, 0.0)
+	} else {
+		(v.0 / length, v.1 / length, v.2 / length)
+	}
+}
+
+/// How doubles are written when iotas are displayed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+	/// As short as possible while still reading back as the same double, e.g. "63" or "0.1".
+	Exact,
+	/// With two decimal places, the way the game's stack display writes them, e.g. "63.00" or "0.10".
+	Game
+}
+
+impl Default for NumberFormat {
+	fn default() -> Self { NumberFormat::Exact }
+}
+
+thread_local! {
+	/// The format iotas are displayed in.
+	static FORMAT: Cell<NumberFormat> = Cell::new(NumberFormat::default());
+}
+
+/// The format doubles are displayed in on this thread.
+pub fn number_format() -> NumberFormat { FORMAT.with(Cell::get) }
+
+/// Selects the format doubles are displayed in on this thread.
+pub fn set_number_format(format: NumberFormat) { FORMAT.with(|current| current.set(format)) }
+
+/// Writes the double in the selected format (see [`number_format`]).
+pub fn format_double(d: f64) -> String {
+	match number_format() {
+		NumberFormat::Exact => d.to_string(),
+		NumberFormat::Game => format_game(d),
+	}
+}
+
+/// Writes the double the way Java's `String.format("%.2f", d)` does. Java rounds the shortest decimal digits that read
+/// back as the double (those of `Double.toString`), with halves rounded away from zero, rather than the double's exact
+/// binary value; so 1.005 is written "1.01", though the double is slightly below 1.005.
+pub fn format_game(d: f64) -> String {
+	if d.is_nan() {
+		return "NaN".to_string()
+	}
+	if d.is_infinite() {
+		return if d > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+	}
+
+	// the shortest digits, e.g. "1.005e0", as digits with the decimal point after the first `point` of them.
+	let scientific = format!("{:e}", d.abs());
+	let (mantissa, exponent) = scientific.split_once('e').unwrap();
+	let mut digits: Vec<u8> = mantissa.bytes().filter(u8::is_ascii_digit).map(|digit| digit - b'0').collect();
+	let mut point = exponent.parse::<i32>().unwrap() + 1;
+
+	if point < 1 {
+		digits.splice(0..0, std::iter::repeat(0).take((1 - point) as usize));
+		point = 1;
+	}
+	let mut point = point as usize;
+	digits.resize(digits.len().max(point + 3), 0);
+
+	let round_up = digits[point + 2] >= 5;
+	digits.truncate(point + 2);
+	if round_up {
+		match digits.iter().rposition(|digit| *digit < 9) {
+			Some(i) => {
+				digits[i] += 1;
+				digits[i + 1..].iter_mut().for_each(|digit| *digit = 0);
+			},
+			None => {
+				digits.iter_mut().for_each(|digit| *digit = 0);
+				digits.insert(0, 1);
+				point += 1;
+			},
+		}
+	}
+
+	let whole = digits[..point].iter().skip_while(|digit| **digit == 0).map(|digit| (b'0' + digit) as char).collect::<String>();
+	let fraction = digits[point..].iter().map(|digit| (b'0' + digit) as char).collect::<String>();
+	format!("{}{}.{}", if d.is_sign_negative() { "-" } else { "" }, if whole.is_empty() { "0" } else { &whole }, fraction)
+}
@@ -1,16 +1,23 @@
+use std::ops::Range;
+
 use regex::Regex;
 
 use crate::{hex_pattern::*, rendering::Renderable};
+use crate::matrix::Matrix;
+use crate::simulator::Iota;
 
 // #[derive(Debug)]
 // pub struct ParseError {
 // 	errors_on: Vec<u32>
 // }
 
-pub fn parse_to_list(string: &str) -> Result<Vec<Box<dyn Renderable>>, HexError> {
+/// Uppercases the string and works out what separates its patterns; if it's written as HexPattern(...) entries, commas
+/// inside the brackets are replaced with ';' so that only the commas between entries separate them.
+/// Each char is replaced by one of the same length, so byte positions in the result match those in the string.
+fn normalise(string: &str) -> (String, char) {
 	let mut str = string.to_string().to_ascii_uppercase();
 
-	let separated = if str.contains("HEX_PATTERN") || str.contains("HEXPATTERN") {
+	if str.contains("HEX_PATTERN") || str.contains("HEXPATTERN") {
 		// assume that it uses ',' to separate patterns, and that all are HE
 		let mut indent = 0;
 		str = str.chars().map(|c| {
@@ -27,11 +34,33 @@ pub fn parse_to_list(string: &str) -> Result<Vec<Box<dyn Renderable>>, HexError>
 				_ => c
 			}
 		}).collect();
-		str.split(',')
+		(str, ',')
 	} else {
 		// assume that it uses '\n' to separate patterns
-		str.split('\n')
-	};
+		(str, '\n')
+	}
+}
+
+/// Returns the byte range of each entry in the string, in the same order as [`parse_to_list`] returns them.
+pub fn pattern_spans(string: &str) -> Vec<Range<usize>> {
+	let (str, separator) = normalise(string);
+	let mut spans = vec![];
+	let mut start = 0;
+
+	for (i, c) in str.char_indices() {
+		if c == separator {
+			spans.push(start..i);
+			start = i + c.len_utf8();
+		}
+	}
+	spans.push(start..str.len());
+
+	spans
+}
+
+pub fn parse_to_list(string: &str) -> Result<Vec<Box<dyn Renderable>>, HexError> {
+	let (str, separator) = normalise(string);
+	let separated = str.split(separator);
 
 	let out = separated.map(|entry| -> Box<dyn Renderable> {
 		if let Ok(parsed_pattern) = parse_to_hex_pattern(entry) {
@@ -94,6 +123,78 @@ pub fn parse_to_hex_pattern(string: &str) -> Result<HexPattern, HexError> {
 	return HexPattern::hex_pattern(start_dir.unwrap(), angles.unwrap_or(vec![]))
 }
 
+/// Splits the text at each `separator` that isn't inside brackets or a string.
+fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+	let mut parts = vec![];
+	let mut depth = 0;
+	let mut in_string = false;
+	let mut escaped = false;
+	let mut start = 0;
+
+	for (i, c) in text.char_indices() {
+		if in_string {
+			match c {
+				_ if escaped => escaped = false,
+				'\\' => escaped = true,
+				'"' => in_string = false,
+				_ => {},
+			}
+			continue
+		}
+
+		match c {
+			'"' => in_string = true,
+			'(' | '[' => depth += 1,
+			')' | ']' => depth -= 1,
+			_ if c == separator && depth == 0 => {
+				parts.push(&text[start..i]);
+				start = i + c.len_utf8();
+			},
+			_ => {},
+		}
+	}
+	parts.push(&text[start..]);
+
+	parts
+}
+
+/// Reads an iota written the way it is displayed (see [`Iota`]'s `Display`), e.g. `[1, (0, 1, 0), "text"]`. Only known
+/// values can be read.
+pub(crate) fn parse_iota(text: &str) -> Result<Iota, String> {
+	let text = text.trim();
+
+	if text.starts_with('"') {
+		return serde_json::from_str::<String>(text).map(Iota::from).map_err(|_| format!("{} isn't a string", text))
+	}
+	if text.starts_with("Matrix") {
+		return text.parse::<Matrix>().map(Iota::from)
+	}
+	if text.to_ascii_uppercase().starts_with("HEXPATTERN") {
+		return parse_to_hex_pattern(text).map(Iota::Pattern).map_err(|_| format!("{} isn't a pattern", text))
+	}
+	if let Some(inner) = text.strip_prefix('[').and_then(|text| text.strip_suffix(']')) {
+		if inner.trim().is_empty() {
+			return Ok(Vec::<Iota>::new().into())
+		}
+		return split_top_level(inner, ',').into_iter().map(parse_iota).collect::<Result<Vec<Iota>, String>>().map(Iota::from)
+	}
+	if let Some(inner) = text.strip_prefix('(').and_then(|text| text.strip_suffix(')')) {
+		let components = inner.split(',').map(|component| component.trim().parse::<f64>()).collect::<Result<Vec<f64>, _>>();
+		return match components.as_deref() {
+			Ok([x, y, z]) => Ok((*x, *y, *z).into()),
+			_ => Err(format!("{} isn't a vector", text)),
+		}
+	}
+
+	match text {
+		"True" => Ok(true.into()),
+		"False" => Ok(false.into()),
+		"Null" => Ok(Iota::Widget),
+		"Garbage" => Ok(Iota::Garbage),
+		_ => text.parse::<f64>().map(Iota::from).map_err(|_| format!("{:?} isn't an iota", text)),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -164,7 +164,7 @@ impl Renderable for HexPattern {
 	}
 
 	fn canonical_text(&self) -> String {
-		let angles_str: String = self.pattern_vec.iter().map(|d| { hex_dir_char(d) }).collect();
+		let angles_str = self.angle_signature();
 		let start_dir_str = hex_absolute_dir_str(self.start_dir);
 		format!("HexPattern({angles_str}, {start_dir_str})")
 	}
@@ -174,16 +174,6 @@ impl Renderable for HexPattern {
 	}
 }
 
-fn hex_dir_char(dir: &HexDir) -> char {
-	match dir {
-    HexDir::A => 'a',
-    HexDir::Q => 'q',
-    HexDir::W => 'w',
-    HexDir::E => 'e',
-    HexDir::D => 'd',
-	}
-}
-
 fn hex_absolute_dir_str<'a>(dir: HexAbsoluteDir) -> &'a str {
 	match dir {
     HexAbsoluteDir::East => "EAST",
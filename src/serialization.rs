@@ -0,0 +1,327 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fmt;
+
+use itertools::Either::{Left, Right};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::de::DeserializeOwned;
+
+use crate::actions::TYPE_LISTS;
+use crate::addons::{with_registry, AddonIota};
+use crate::hex_pattern::HexPattern;
+use crate::interval::Interval;
+use crate::matrix::Matrix;
+use crate::simulator::{Iota, IotaEntity, IotaList, IotaType};
+use crate::symbolic::Expr;
+
+/// The version of the JSON shape written by [`to_json`]. Bump this whenever the shape of any serialized type changes,
+/// so that files written by older versions are rejected instead of being read wrongly.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// What every JSON file holds: the format version, and the value itself.
+#[derive(Serialize, Deserialize)]
+struct Versioned<T> {
+	version: u32,
+	data: T
+}
+
+/// Why a JSON file couldn't be read.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatError {
+	/// The file isn't valid JSON, or doesn't have the shape of the value being read.
+	Json(String),
+	/// The file was written with a format version this version of hexedit can't read.
+	UnsupportedVersion(u32),
+	/// The file couldn't be read.
+	Io(String),
+	/// A file in a text format (like the one for blocks) had a mistake on the line, counting from 1.
+	Syntax { line: usize, message: String }
+}
+
+impl fmt::Display for FormatError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			FormatError::Json(error) => write!(f, "invalid JSON: {}", error),
+			FormatError::UnsupportedVersion(version) => {
+				write!(f, "format version {} isn't supported (expected version {})", version, FORMAT_VERSION)
+			},
+			FormatError::Io(error) => write!(f, "couldn't read the file: {}", error),
+			FormatError::Syntax { line, message } => write!(f, "line {}: {}", line, message),
+		}
+	}
+}
+
+impl From<serde_json::Error> for FormatError {
+	fn from(error: serde_json::Error) -> Self { FormatError::Json(error.to_string()) }
+}
+
+/// Writes the value as pretty-printed JSON, tagged with the current [`FORMAT_VERSION`].
+pub fn to_json<T: Serialize>(value: &T) -> String {
+	serde_json::to_string_pretty(&Versioned { version: FORMAT_VERSION, data: value }).expect("simulator values are always serializable")
+}
+
+/// Reads a value written by [`to_json`].
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T, FormatError> {
+	let versioned: Versioned<serde_json::Value> = serde_json::from_str(json)?;
+
+	if versioned.version != FORMAT_VERSION {
+		return Err(FormatError::UnsupportedVersion(versioned.version))
+	}
+
+	Ok(serde_json::from_value(versioned.data)?)
+}
+
+/// Stores a value as a string holding its versioned JSON, for formats other than JSON (like the one the app state is
+/// persisted in) that can't represent every shape JSON can.
+pub mod as_json {
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+	use serde::de::DeserializeOwned;
+
+	pub fn serialize<S: Serializer, T: Serialize>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&super::to_json(value))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>, T: DeserializeOwned>(deserializer: D) -> Result<T, D::Error> {
+		let json = String::deserialize(deserializer)?;
+		super::from_json(&json).map_err(serde::de::Error::custom)
+	}
+}
+
+/// Serializes doubles that JSON can't represent (infinities and NaN) as the strings "inf", "-inf" and "nan".
+pub(crate) mod float {
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	#[derive(serde::Deserialize)]
+	#[serde(untagged)]
+	enum Float {
+		Number(f64),
+		Named(String)
+	}
+
+	pub fn serialize<S: Serializer>(d: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+		if d.is_nan() {
+			serializer.serialize_str("nan")
+		} else if *d == f64::INFINITY {
+			serializer.serialize_str("inf")
+		} else if *d == f64::NEG_INFINITY {
+			serializer.serialize_str("-inf")
+		} else {
+			serializer.serialize_f64(*d)
+		}
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+		match Float::deserialize(deserializer)? {
+			Float::Number(d) => Ok(d),
+			Float::Named(name) => match name.as_str() {
+				"nan" => Ok(f64::NAN),
+				"inf" => Ok(f64::INFINITY),
+				"-inf" => Ok(f64::NEG_INFINITY),
+				_ => Err(serde::de::Error::custom(format!("expected a number, \"inf\", \"-inf\" or \"nan\", found \"{}\"", name))),
+			},
+		}
+	}
+}
+
+/// Serializes an optional pattern as it is written in hexes, e.g. "HexPattern(qaq, EAST)".
+pub(crate) mod pattern_text {
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	use crate::hex_pattern::HexPattern;
+	use crate::parsing::parse_to_hex_pattern;
+	use crate::rendering::Renderable;
+
+	pub fn serialize<S: Serializer>(pattern: &Option<HexPattern>, serializer: S) -> Result<S::Ok, S::Error> {
+		match pattern {
+			Some(pattern) => serializer.serialize_str(&pattern.canonical_text()),
+			None => serializer.serialize_none(),
+		}
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<HexPattern>, D::Error> {
+		Option::<String>::deserialize(deserializer)?.map(|text| parse(&text)).transpose()
+	}
+
+	pub(super) fn parse<E: serde::de::Error>(text: &str) -> Result<HexPattern, E> {
+		parse_to_hex_pattern(text).map_err(|_| E::custom(format!("\"{}\" isn't a pattern with a start direction", text)))
+	}
+}
+
+/// Serializes a hex as a list of its patterns, each written as in [`pattern_text`].
+pub(crate) mod hex_text {
+	use serde::{Deserialize, Deserializer, Serializer};
+
+	use crate::hex_pattern::HexPattern;
+	use crate::rendering::Renderable;
+
+	pub fn serialize<S: Serializer>(hex: &[HexPattern], serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_seq(hex.iter().map(Renderable::canonical_text))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<HexPattern>, D::Error> {
+		Vec::<String>::deserialize(deserializer)?.iter().map(|text| super::pattern_text::parse(text)).collect()
+	}
+}
+
+/// Serializes a set in sorted order, so the same set is always written the same way.
+pub(crate) fn sorted<S: Serializer, T: Ord + Serialize>(set: &HashSet<T>, serializer: S) -> Result<S::Ok, S::Error> {
+	set.iter().collect::<BTreeSet<&T>>().serialize(serializer)
+}
+
+/// Deserializes a list of types as the `&'static` slice [`crate::simulator::ActionError::InvalidType`] holds; either a
+/// single type, every type, or one of the lists actions expect (see [`crate::actions::TYPE_LISTS`]). Other lists are
+/// rejected, since no action could have expected them.
+pub(crate) fn static_types<'de, D: Deserializer<'de>>(deserializer: D) -> Result<&'static [IotaType], D::Error> {
+	let types = Vec::<IotaType>::deserialize(deserializer)?;
+
+	match types.as_slice() {
+		[single] => Ok(single.only()),
+		_ if types == IotaType::ANY => Ok(IotaType::ANY),
+		_ => TYPE_LISTS.iter().find(|known| **known == types.as_slice()).copied()
+			.ok_or_else(|| serde::de::Error::custom(format!("no action expects the types {:?}", types)))
+	}
+}
+
+/// The JSON shape of an iota. Unknown values are separate variants from known ones, so that a known value never
+/// needs to be checked for bounds.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum IotaRepr {
+	Pattern(HexPattern),
+	Double { #[serde(with = "float")] value: f64 },
+	UnknownDouble { bounds: Interval },
+	Bool { value: bool },
+	UnknownBool,
+	Vec {
+		#[serde(with = "float")] x: f64,
+		#[serde(with = "float")] y: f64,
+		#[serde(with = "float")] z: f64
+	},
+	UnknownVec { in_range: bool },
+	Widget,
+	Garbage,
+	List { items: Vec<Iota> },
+	UnknownList {
+		len: Option<usize>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		element: Option<Box<Iota>>
+	},
+	Entity(IotaEntity),
+	/// An addon iota, written with its kind's [`crate::addons::IotaKind::serialize`] hook. When read, the value can be
+	/// given as `text` instead, which is read with the kind's parse hook.
+	Addon {
+		kind: String,
+		#[serde(default)]
+		value: serde_json::Value,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		text: Option<String>
+	},
+	String { value: String },
+	UnknownString,
+	/// a matrix, as its rows.
+	Matrix { rows: Vec<Vec<Float>> },
+	UnknownMatrix {
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		rows: Option<usize>,
+		#[serde(default, skip_serializing_if = "Option::is_none")]
+		columns: Option<usize>
+	},
+	Unknown,
+	Symbolic { expr: Expr, value: Box<Iota> },
+	/// Only read, never written; a known iota written as text, the way it is displayed.
+	Text { text: String }
+}
+
+/// A double written with [`float`], for doubles inside other values.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+pub(crate) struct Float(#[serde(with = "float")] f64);
+
+impl From<Iota> for IotaRepr {
+	fn from(iota: Iota) -> Self {
+		match iota {
+			Iota::Pattern(pattern) => IotaRepr::Pattern(pattern),
+			Iota::Double(Left(value)) => IotaRepr::Double { value },
+			Iota::Double(Right(bounds)) => IotaRepr::UnknownDouble { bounds },
+			Iota::Bool(Some(value)) => IotaRepr::Bool { value },
+			Iota::Bool(None) => IotaRepr::UnknownBool,
+			Iota::Vec(Left((x, y, z))) => IotaRepr::Vec { x, y, z },
+			Iota::Vec(Right(in_range)) => IotaRepr::UnknownVec { in_range },
+			Iota::Widget => IotaRepr::Widget,
+			Iota::Garbage => IotaRepr::Garbage,
+			Iota::List(IotaList(Left(items), _)) => IotaRepr::List { items },
+			Iota::List(IotaList(Right(len), element)) => IotaRepr::UnknownList { len, element },
+			Iota::Entity(entity) => IotaRepr::Entity(entity),
+			Iota::Addon(addon) => {
+				let value = match with_registry(|registry| registry.kind(&addon.kind)) {
+					Some(kind) => kind.serialize(&addon.data),
+					// the value of a kind that isn't registered is kept as the JSON it was read from.
+					None => serde_json::from_str(&addon.data).unwrap_or(serde_json::Value::String(addon.data)),
+				};
+				IotaRepr::Addon { kind: addon.kind, value, text: None }
+			},
+			Iota::String(Some(value)) => IotaRepr::String { value },
+			Iota::String(None) => IotaRepr::UnknownString,
+			Iota::Matrix(Left(matrix)) => IotaRepr::Matrix {
+				rows: matrix.to_rows().into_iter().map(|row| row.into_iter().map(Float).collect()).collect()
+			},
+			Iota::Matrix(Right((rows, columns))) => IotaRepr::UnknownMatrix { rows, columns },
+			Iota::Unknown => IotaRepr::Unknown,
+			Iota::Symbolic(expr, value) => IotaRepr::Symbolic { expr: (*expr).clone(), value },
+		}
+	}
+}
+
+impl TryFrom<IotaRepr> for Iota {
+	type Error = String;
+
+	fn try_from(repr: IotaRepr) -> Result<Self, Self::Error> {
+		Ok(match repr {
+			IotaRepr::Pattern(pattern) => Iota::Pattern(pattern),
+			IotaRepr::Double { value } => Iota::Double(Left(value)),
+			IotaRepr::UnknownDouble { bounds } => Iota::Double(Right(bounds)),
+			IotaRepr::Bool { value } => Iota::Bool(Some(value)),
+			IotaRepr::UnknownBool => Iota::Bool(None),
+			IotaRepr::Vec { x, y, z } => Iota::Vec(Left((x, y, z))),
+			IotaRepr::UnknownVec { in_range } => Iota::Vec(Right(in_range)),
+			IotaRepr::Widget => Iota::Widget,
+			IotaRepr::Garbage => Iota::Garbage,
+			IotaRepr::List { items } => Iota::List(IotaList::new(Left(items))),
+			IotaRepr::UnknownList { len, element } => Iota::List(IotaList::unknown(len, element.map(|element| *element))),
+			IotaRepr::Entity(entity) => Iota::Entity(entity),
+			IotaRepr::Addon { kind: name, value, text } => {
+				let data = match (with_registry(|registry| registry.kind(&name)), text) {
+					(Some(kind), Some(text)) => kind.parse(&text).ok_or_else(|| format!("{:?} isn't a {}", text, name))?,
+					(Some(kind), None) => kind.deserialize(&value).ok_or_else(|| format!("{} isn't a {}", value, name))?,
+					(None, Some(_)) => return Err(format!("{} can't be read from text without its addon", name)),
+					(None, None) => value.to_string(),
+				};
+				Iota::Addon(AddonIota { kind: name, data })
+			},
+			IotaRepr::String { value } => Iota::String(Some(value)),
+			IotaRepr::UnknownString => Iota::String(None),
+			IotaRepr::Matrix { rows } => {
+				let rows: Vec<Vec<f64>> = rows.into_iter().map(|row| row.into_iter().map(|Float(d)| d).collect()).collect();
+				Iota::Matrix(Left(Matrix::from_rows(&rows).ok_or("a matrix needs rows that are all the same, non-zero, length")?))
+			},
+			IotaRepr::UnknownMatrix { rows, columns } => Iota::Matrix(Right((rows, columns))),
+			IotaRepr::Unknown => Iota::Unknown,
+			IotaRepr::Text { text } => text.parse()?,
+			IotaRepr::Symbolic { value, .. } if matches!(*value, Iota::Symbolic(_, _)) => {
+				return Err("symbolic iotas can't be nested".to_string())
+			},
+			IotaRepr::Symbolic { expr, value } => Iota::Symbolic(expr.into(), value),
+		})
+	}
+}
+
+/// Lists are written the same way as list iotas.
+impl TryFrom<Iota> for IotaList {
+	type Error = String;
+
+	fn try_from(iota: Iota) -> Result<Self, Self::Error> {
+		match iota {
+			Iota::List(list) => Ok(list),
+			_ => Err(format!("expected a list, found {}", IotaType::of(&iota))),
+		}
+	}
+}
@@ -1,36 +1,87 @@
 use std::fmt::Display;
-use std::slice::SliceIndex;
-use std::{fmt, ops::Index};
+use std::fmt;
 use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
 
 use itertools::{Either, Either::Left, Either::Right};
 
+use crate::addons::AddonIota;
 use crate::hex_pattern::HexPattern;
+use crate::interval::Interval;
+use crate::matrix::Matrix;
+use crate::numbers::{format_double, is_zero, tolerates, vec_tolerates};
+use crate::symbolic::{Expr, Op};
+use crate::version::{profile, GameVersion};
+use crate::world::{Caster, Scene};
 
-#[derive(Clone)]
+/// Serialized as the tagged shape in [`crate::serialization`], e.g. `{"type": "double", "value": 2.0}`.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(into = "crate::serialization::IotaRepr", try_from = "crate::serialization::IotaRepr")]
 pub enum Iota {
 	Pattern(HexPattern),
-	// None is an unknown double.
-	Double(Option<f64>),
+	/// the left case is a known double, the right case is an unknown double along with what is known about its bounds.
+	Double(Either<f64, Interval>),
+	// None is an unknown boolean.
+	Bool(Option<bool>),
 	/// the left case is a known vector, the right case is an unknown vector with the boolean representing whether the vec is guaranteed in range.
 	Vec(Either<(f64, f64, f64), bool>),
 	Widget,
+	/// What Hex Casting pushes when an iota can't be represented, e.g. after some mishaps.
+	Garbage,
 	List(IotaList),
-	Entity(IotaEntity)
+	Entity(IotaEntity),
+	/// An iota of a kind added by an addon (see [`crate::addons`]).
+	Addon(AddonIota),
+	/// A string, from MoreIotas. None is an unknown string.
+	String(Option<String>),
+	/// A matrix, from MoreIotas. The right case is an unknown matrix, with its numbers of rows and columns if they are known.
+	Matrix(Either<Matrix, (Option<usize>, Option<usize>)>),
+	/// An iota that the simulator knows exists, but not what type it is (e.g. an element taken out of an unknown list).
+	Unknown,
+	/// A value along with the expression it was computed from, in terms of named unknown inputs. Only created in symbolic mode
+	/// (see [`StackState::set_symbolic`]), and never nested.
+	Symbolic(Rc<Expr>, Box<Iota>)
+}
+
+/// Reads an iota written the way it is displayed, e.g. `[1, (0, 1, 0), "text"]`. Only known values can be read.
+impl std::str::FromStr for Iota {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> { crate::parsing::parse_iota(s) }
 }
 
 impl From<f64> for Iota {
 	fn from(d: f64) -> Self {
-		Iota::Double(Some(d))
+		Iota::Double(Left(d))
 	}
 }
 
+/// None is an unknown double with no known bounds.
 impl From<Option<f64>> for Iota {
 	fn from(d: Option<f64>) -> Self {
+		Iota::Double(d.map_or(Right(Interval::unbounded()), Left))
+	}
+}
+
+impl From<Interval> for Iota {
+	fn from(interval: Interval) -> Self {
+		Iota::Double(interval.into_double())
+	}
+}
+
+impl From<Either<f64, Interval>> for Iota {
+	fn from(d: Either<f64, Interval>) -> Self {
 		Iota::Double(d)
 	}
 }
 
+impl From<bool> for Iota {
+	fn from(b: bool) -> Self {
+		Iota::Bool(Some(b))
+	}
+}
+
 impl From<(f64, f64, f64)> for Iota {
 	fn from(vec: (f64, f64, f64)) -> Self {
 		Iota::Vec(Left(vec))
@@ -43,19 +94,186 @@ impl From<Either<(f64, f64, f64), bool>> for Iota {
 	}
 }
 
-/// The left case is a known vector of iotas, the right how many elements the list could have.
-#[derive(Clone)]
-pub struct IotaList(Either<Vec<Iota>, Option<usize>>);
+impl From<String> for Iota {
+	fn from(string: String) -> Self {
+		Iota::String(Some(string))
+	}
+}
+
+impl From<Matrix> for Iota {
+	fn from(matrix: Matrix) -> Self {
+		Iota::Matrix(Left(matrix))
+	}
+}
+
+impl From<IotaList> for Iota {
+	fn from(list: IotaList) -> Self {
+		Iota::List(list)
+	}
+}
+
+impl From<Vec<Iota>> for Iota {
+	fn from(list: Vec<Iota>) -> Self {
+		Iota::List(IotaList::new(Left(list)))
+	}
+}
+
+pub use crate::numbers::TOLERANCE;
+
+impl Iota {
+	/// A named unknown input for symbolic mode; `value` is what is known about the input (e.g. `Iota::Double(Right(interval))`).
+	pub fn symbol(name: &str, value: Iota) -> Iota { Iota::Symbolic(Expr::var(name), Box::new(value.value().clone())) }
+
+	/// The iota without any symbolic expression attached to it.
+	pub fn value(&self) -> &Iota {
+		match self {
+			Iota::Symbolic(_, value) => value,
+			_ => self
+		}
+	}
+
+	/// The expression for this iota in symbolic mode; known iotas are constants.
+	pub fn expr(&self) -> Rc<Expr> {
+		match self {
+			Iota::Symbolic(expr, _) => expr.clone(),
+			Iota::Double(Left(d)) => Rc::new(Expr::Const(*d)),
+			_ => Rc::new(Expr::Literal(self.to_string()))
+		}
+	}
+
+	/// Returns true if nothing about the iota is unknown.
+	pub fn is_known(&self) -> bool {
+		match self {
+			Iota::Double(d) => d.is_left(),
+			Iota::Bool(b) => b.is_some(),
+			Iota::Vec(vec) => vec.is_left(),
+			Iota::List(IotaList(Left(list), _)) => list.iter().all(Iota::is_known),
+			Iota::String(string) => string.is_some(),
+			Iota::Matrix(matrix) => matrix.is_left(),
+			Iota::List(IotaList(Right(_), _)) | Iota::Unknown | Iota::Symbolic(_, _) => false,
+			Iota::Pattern(_) | Iota::Widget | Iota::Garbage | Iota::Entity(_) | Iota::Addon(_) => true,
+		}
+	}
+
+	/// Returns whether the two iotas would be considered equal by Hex Casting, or None if that depends on unknown values.
+	pub fn tolerates(&self, other: &Iota) -> Option<bool> {
+		match (self, other) {
+			(Iota::Symbolic(e0, _), Iota::Symbolic(e1, _)) if e0.simplify() == e1.simplify() => Some(true),
+			(Iota::Symbolic(_, value), _) => value.tolerates(other),
+			(_, Iota::Symbolic(_, value)) => self.tolerates(value),
+			(Iota::Unknown, _) | (_, Iota::Unknown) => None,
+			(Iota::Pattern(p0), Iota::Pattern(p1)) => Some(p0.pattern_vec == p1.pattern_vec),
+			(Iota::Double(Left(d0)), Iota::Double(Left(d1))) => Some(tolerates(*d0, *d1)),
+			(Iota::Double(d0), Iota::Double(d1)) => {
+				let (i0, i1) = (Interval::from_double(d0), Interval::from_double(d1));
+				if i0.lower - i1.upper >= TOLERANCE || i1.lower - i0.upper >= TOLERANCE { Some(false) } else { None }
+			},
+			(Iota::Vec(Left(v0)), Iota::Vec(Left(v1))) => Some(vec_tolerates(*v0, *v1)),
+			(Iota::Vec(_), Iota::Vec(_)) => None,
+			(Iota::Bool(b0), Iota::Bool(b1)) => match (b0, b1) {
+				(Some(b0), Some(b1)) => Some(b0 == b1),
+				_ => None,
+			},
+			(Iota::Widget, Iota::Widget) => Some(true),
+			(Iota::Garbage, Iota::Garbage) => Some(true),
+			(Iota::List(l0), Iota::List(l1)) => l0.tolerates(l1),
+			(Iota::Entity(e0), Iota::Entity(e1)) => Some(e0.uuid == e1.uuid),
+			(Iota::Addon(a0), Iota::Addon(a1)) => Some(a0 == a1),
+			(Iota::String(Some(s0)), Iota::String(Some(s1))) => Some(s0 == s1),
+			(Iota::String(_), Iota::String(_)) => None,
+			(Iota::Matrix(m0), Iota::Matrix(m1)) => {
+				if matrix_dimensions(m0).iter().zip(&matrix_dimensions(m1)).any(|pair| matches!(pair, (Some(d0), Some(d1)) if d0 != d1)) {
+					return Some(false)
+				}
+				match (m0, m1) {
+					(Left(m0), Left(m1)) => Some(m0.values().iter().zip(m1.values()).all(|(d0, d1)| tolerates(*d0, *d1))),
+					_ => None,
+				}
+			},
+			_ => Some(false)
+		}
+	}
+
+	/// Returns whether Hex Casting would treat the iota as true (see Augur's Purification), or None if that depends on unknown values.
+	pub fn truthy(&self) -> Option<bool> {
+		match self {
+			Iota::Bool(b) => *b,
+			Iota::Double(Left(d)) => Some(!is_zero(*d)),
+			Iota::Double(Right(interval)) => if interval.may_be_zero(TOLERANCE) { None } else { Some(true) },
+			Iota::Widget | Iota::Garbage => Some(false),
+			Iota::List(list) => list.is_empty().map(|empty| !empty),
+			Iota::Vec(Left(vec)) => Some(vec.0 != 0.0 || vec.1 != 0.0 || vec.2 != 0.0),
+			Iota::String(string) => string.as_ref().map(|string| !string.is_empty()),
+			Iota::Vec(Right(_)) | Iota::Unknown => None,
+			Iota::Pattern(_) | Iota::Entity(_) | Iota::Addon(_) | Iota::Matrix(_) => Some(true),
+			Iota::Symbolic(_, value) => value.truthy(),
+		}
+	}
+}
+
+/// The left case is a known vector of iotas, the right how many elements the list could have. For a list whose elements
+/// aren't known, the second field is what every element is known to be, if anything (e.g. an entity that is
+/// guaranteed to be a monster); it is always None for known lists, so lists are only built with [`IotaList::new`] and
+/// [`IotaList::unknown`].
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+#[serde(into = "Iota", try_from = "Iota")]
+pub struct IotaList(pub(crate) Either<Vec<Iota>, Option<usize>>, pub(crate) Option<Box<Iota>>);
 
 impl IotaList {
-	fn new(list: Either<Vec<Iota>, Option<usize>>) -> IotaList { IotaList(list) }
+	pub fn new(list: Either<Vec<Iota>, Option<usize>>) -> IotaList { IotaList(list, None) }
+
+	/// A list whose elements aren't known, except that each of them is approximated by `element`.
+	pub fn unknown(len: Option<usize>, element: Option<Iota>) -> IotaList { IotaList(Right(len), element.map(Box::new)) }
+
+	/// The same list with a different length, keeping what is known about its elements.
+	pub fn with_len(&self, len: Option<usize>) -> IotaList { IotaList(Right(len), self.1.clone()) }
+
+	/// What is known about the elements of a list whose elements aren't known.
+	pub fn element(&self) -> Iota { self.1.as_deref().cloned().unwrap_or(Iota::Unknown) }
+
+	/// The elements of the list, if they are known.
+	pub fn items(&self) -> Option<&[Iota]> { self.0.as_ref().left().map(Vec::as_slice) }
+
+	/// Returns the number of elements in the list, or None if that isn't known.
+	pub fn len(&self) -> Option<usize> {
+		match &self.0 {
+			Left(list) => Some(list.len()),
+			Right(length) => *length,
+		}
+	}
+
+	pub fn is_empty(&self) -> Option<bool> { self.len().map(|length| length == 0) }
+
+	fn tolerates(&self, other: &IotaList) -> Option<bool> {
+		match (&self.0, &other.0) {
+			(Left(l0), Left(l1)) => {
+				if l0.len() != l1.len() {
+					return Some(false)
+				}
+
+				l0.iter().zip(l1.iter()).try_fold(true, |acc, (i0, i1)| {
+					match i0.tolerates(i1) {
+						Some(false) => Err(()),
+						Some(true) => Ok(acc),
+						None => Ok(false),
+					}
+				}).map_or(Some(false), |all_known| if all_known { Some(true) } else { None })
+			},
+			_ => match (self.len(), other.len()) {
+				(Some(len0), Some(len1)) if len0 != len1 => Some(false),
+				_ => None
+			}
+		}
+	}
 }
 
-#[derive(Clone)]
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct IotaEntity {
 	name: String,
 	uuid: String,
+	#[serde(serialize_with = "crate::serialization::sorted")]
 	guaranteed_types: HashSet<EntityType>,
+	#[serde(serialize_with = "crate::serialization::sorted")]
 	possible_types: HashSet<EntityType>,
 	guaranteed_in_range: bool
 }
@@ -65,6 +283,44 @@ impl IotaEntity {
 		EntityBuilder { name: name.to_string(), uuid: name.to_string(), guaranteed_types: None, possible_types: None, guaranteed_in_range: None }
 	}
 
+	pub fn name(&self) -> &str { &self.name }
+
+	pub fn uuid(&self) -> &str { &self.uuid }
+
+	pub fn is_guaranteed_in_range(&self) -> bool { self.guaranteed_in_range }
+
+	/// An entity that nothing is known about except its type; if `is` is true it is guaranteed to be `entity_type`,
+	/// otherwise it is guaranteed not to be. Animals, monsters and players are living, so they are included or excluded
+	/// along with living entities.
+	pub fn of_type(name: &str, entity_type: EntityType, is: bool) -> IotaEntity {
+		let mut entity: IotaEntity = IotaEntity::new(name).set_guaranteed_in_range(false).into();
+
+		if is {
+			entity.add_guarenteed(entity_type);
+			if entity_type.is_living() {
+				entity.add_guarenteed(EntityType::Living);
+			}
+		} else {
+			entity.remove_possible(entity_type);
+			if entity_type == EntityType::Living {
+				EntityType::ALL.iter().filter(|e_type| e_type.is_living()).for_each(|e_type| { entity.remove_possible(*e_type); });
+			}
+		}
+
+		entity
+	}
+
+	/// Returns whether the entity is of the type, or None if that isn't known.
+	pub fn is_type(&self, entity_type: EntityType) -> Option<bool> {
+		if self.guaranteed_types.contains(&entity_type) {
+			Some(true)
+		} else if !self.possible_types.contains(&entity_type) {
+			Some(false)
+		} else {
+			None
+		}
+	}
+
 	/// returns true if adding this guaranteed type causes no exclusivity issues, false otherwise.
 	fn add_guarenteed(&mut self, guaranteed: EntityType) -> bool {
 		if self.guaranteed_types.iter().any(|e_type| e_type.mutually_exclusive(&guaranteed)) {
@@ -95,7 +351,7 @@ impl From<&mut EntityBuilder> for IotaEntity {
 			name: builder.name.clone(),
 			uuid: builder.uuid.clone(),
 			guaranteed_types: builder.guaranteed_types.clone().unwrap_or(HashSet::new()),
-			possible_types: builder.possible_types.clone().unwrap_or(HashSet::from_iter([EntityType::Animal, EntityType::Monster, EntityType::Item, EntityType::Player, EntityType::Living])),
+			possible_types: builder.possible_types.clone().unwrap_or(HashSet::from_iter(EntityType::ALL)),
 			guaranteed_in_range: builder.guaranteed_in_range.unwrap_or(true)
 		}
 	}
@@ -121,11 +377,7 @@ impl EntityBuilder {
 	}
 
 	pub fn add_guaranteed(&mut self, guaranteed: EntityType) -> &mut Self {
-		if let Some(guaranteed_types) = &mut self.guaranteed_types {
-    	guaranteed_types.insert(guaranteed);
-		} else {
-			self.guaranteed_types = Some(HashSet::new());
-		}
+		self.guaranteed_types.get_or_insert_with(HashSet::new).insert(guaranteed);
 
 		self
 	}
@@ -134,7 +386,7 @@ impl EntityBuilder {
 		if let Some(possible_types) = &mut self.possible_types {
     	possible_types.remove(&possible);
 		} else {
-			let mut possible_types = HashSet::from_iter([EntityType::Animal, EntityType::Monster, EntityType::Item, EntityType::Player, EntityType::Living]);
+			let mut possible_types = HashSet::from_iter(EntityType::ALL);
 			possible_types.remove(&possible);
 			self.possible_types = Some(possible_types);
 		}
@@ -150,7 +402,8 @@ impl EntityBuilder {
 }
 
 
-#[derive(PartialEq, Eq, Hash, Clone)]
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EntityType {
 	Animal,
 	Monster,
@@ -160,6 +413,21 @@ pub enum EntityType {
 }
 
 impl EntityType {
+	pub const ALL: [EntityType; 5] = [EntityType::Animal, EntityType::Monster, EntityType::Item, EntityType::Player, EntityType::Living];
+
+	pub fn name(&self) -> &'static str {
+		match self {
+			EntityType::Animal => "animal",
+			EntityType::Monster => "monster",
+			EntityType::Item => "item",
+			EntityType::Player => "player",
+			EntityType::Living => "living",
+		}
+	}
+
+	/// Whether every entity of this type is also living.
+	pub fn is_living(&self) -> bool { matches!(self, EntityType::Animal | EntityType::Monster | EntityType::Player | EntityType::Living) }
+
 	fn mutually_exclusive(&self, other: &EntityType) -> bool {
 		match &self {
 			EntityType::Animal => *other == EntityType::Monster || *other == EntityType::Player || *other == EntityType::Item,
@@ -171,22 +439,201 @@ impl EntityType {
 	}
 }
 
+/// Hashes a double so that doubles which are equal under [`f64_eq`] hash the same.
+pub(crate) fn hash_f64<H: Hasher>(d: f64, state: &mut H) {
+	let normalised = if d == 0.0 { 0.0 } else if d.is_nan() { f64::NAN } else { d };
+	normalised.to_bits().hash(state)
+}
+
+/// The numbers of rows and columns of a matrix iota, where they are known.
+pub(crate) fn matrix_dimensions(matrix: &Either<Matrix, (Option<usize>, Option<usize>)>) -> [Option<usize>; 2] {
+	match matrix {
+		Left(matrix) => [Some(matrix.rows()), Some(matrix.columns())],
+		Right((rows, columns)) => [*rows, *columns],
+	}
+}
+
+/// Structural equality for doubles, which (unlike `==`) treats NaN as equal to itself.
+pub(crate) fn f64_eq(d0: f64, d1: f64) -> bool { d0 == d1 || (d0.is_nan() && d1.is_nan()) }
+
+fn interval_eq(i0: &Interval, i1: &Interval) -> bool {
+	f64_eq(i0.lower, i1.lower) && f64_eq(i0.upper, i1.upper) && i0.integer == i1.integer
+}
+
+fn hash_interval<H: Hasher>(interval: &Interval, state: &mut H) {
+	hash_f64(interval.lower, state);
+	hash_f64(interval.upper, state);
+	interval.integer.hash(state);
+}
+
+/// Structural equality; two iotas are equal if they are exactly the same, including what is known about unknown values.
+/// See [`Iota::tolerates`] for the equality Hex Casting uses.
+impl PartialEq for Iota {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(Iota::Pattern(p0), Iota::Pattern(p1)) => p0 == p1,
+			(Iota::Double(Left(d0)), Iota::Double(Left(d1))) => f64_eq(*d0, *d1),
+			(Iota::Double(Right(i0)), Iota::Double(Right(i1))) => interval_eq(i0, i1),
+			(Iota::Bool(b0), Iota::Bool(b1)) => b0 == b1,
+			(Iota::Vec(Left(v0)), Iota::Vec(Left(v1))) => f64_eq(v0.0, v1.0) && f64_eq(v0.1, v1.1) && f64_eq(v0.2, v1.2),
+			(Iota::Vec(Right(r0)), Iota::Vec(Right(r1))) => r0 == r1,
+			(Iota::Widget, Iota::Widget) | (Iota::Garbage, Iota::Garbage) | (Iota::Unknown, Iota::Unknown) => true,
+			(Iota::List(l0), Iota::List(l1)) => l0 == l1,
+			(Iota::Entity(e0), Iota::Entity(e1)) => e0 == e1,
+			(Iota::Addon(a0), Iota::Addon(a1)) => a0 == a1,
+			(Iota::String(s0), Iota::String(s1)) => s0 == s1,
+			(Iota::Matrix(Left(m0)), Iota::Matrix(Left(m1))) => {
+				m0.dimensions() == m1.dimensions() && m0.values().iter().zip(m1.values()).all(|(d0, d1)| f64_eq(*d0, *d1))
+			},
+			(Iota::Matrix(Right(d0)), Iota::Matrix(Right(d1))) => d0 == d1,
+			(Iota::Symbolic(e0, v0), Iota::Symbolic(e1, v1)) => e0 == e1 && v0 == v1,
+			_ => false
+		}
+	}
+}
+
+impl Eq for Iota {}
+
+impl Hash for Iota {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		std::mem::discriminant(self).hash(state);
+		match self {
+			Iota::Pattern(pattern) => pattern.hash(state),
+			Iota::Double(Left(d)) => hash_f64(*d, state),
+			Iota::Double(Right(interval)) => hash_interval(interval, state),
+			Iota::Bool(b) => b.hash(state),
+			Iota::Vec(Left(vec)) => { hash_f64(vec.0, state); hash_f64(vec.1, state); hash_f64(vec.2, state) },
+			Iota::Vec(Right(in_range)) => in_range.hash(state),
+			Iota::List(list) => list.hash(state),
+			Iota::Entity(entity) => entity.hash(state),
+			Iota::Addon(addon) => addon.hash(state),
+			Iota::String(string) => string.hash(state),
+			Iota::Matrix(Left(matrix)) => { matrix.dimensions().hash(state); matrix.values().iter().for_each(|d| hash_f64(*d, state)) },
+			Iota::Matrix(Right(dimensions)) => dimensions.hash(state),
+			Iota::Symbolic(expr, value) => { expr.hash(state); value.hash(state) },
+			Iota::Widget | Iota::Garbage | Iota::Unknown => {},
+		}
+	}
+}
+
+impl PartialEq for IotaList {
+	fn eq(&self, other: &Self) -> bool {
+		match (&self.0, &other.0) {
+			(Left(l0), Left(l1)) => l0 == l1,
+			(Right(len0), Right(len1)) => len0 == len1 && self.1 == other.1,
+			_ => false
+		}
+	}
+}
+
+impl Eq for IotaList {}
+
+impl Hash for IotaList {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		match &self.0 {
+			Left(list) => { 0.hash(state); list.hash(state) },
+			Right(length) => { 1.hash(state); length.hash(state); self.1.hash(state) },
+		}
+	}
+}
+
+impl PartialEq for IotaEntity {
+	fn eq(&self, other: &Self) -> bool {
+		self.name == other.name && self.uuid == other.uuid && self.guaranteed_types == other.guaranteed_types &&
+			self.possible_types == other.possible_types && self.guaranteed_in_range == other.guaranteed_in_range
+	}
+}
+
+impl Eq for IotaEntity {}
+
+impl Hash for IotaEntity {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.name.hash(state);
+		self.uuid.hash(state);
+		// HashSets have no order, so hash them in sorted order.
+		let mut guaranteed_types: Vec<_> = self.guaranteed_types.iter().collect();
+		guaranteed_types.sort();
+		guaranteed_types.hash(state);
+		let mut possible_types: Vec<_> = self.possible_types.iter().collect();
+		possible_types.sort();
+		possible_types.hash(state);
+		self.guaranteed_in_range.hash(state);
+	}
+}
+
+impl Iota {
+	/// Returns an iota that approximates both iotas, widening any values that differ into unknown values, or None if the
+	/// iotas are too different to be approximated by one iota (e.g. they are different types).
+	pub fn join(&self, other: &Iota) -> Option<Iota> {
+		if self == other {
+			return Some(self.clone())
+		}
+
+		match (self, other) {
+			(Iota::Symbolic(e0, v0), Iota::Symbolic(e1, v1)) if e0 == e1 => v0.join(v1).map(|value| Iota::Symbolic(e0.clone(), Box::new(value))),
+			(Iota::Symbolic(_, value), _) => value.join(other),
+			(_, Iota::Symbolic(_, value)) => self.join(value),
+			(Iota::Unknown, _) | (_, Iota::Unknown) => Some(Iota::Unknown),
+			(Iota::Double(d0), Iota::Double(d1)) => Some(Interval::from_double(d0).hull(&Interval::from_double(d1)).into()),
+			(Iota::Bool(_), Iota::Bool(_)) => Some(Iota::Bool(None)),
+			(Iota::Vec(v0), Iota::Vec(v1)) => Some(Iota::Vec(Right(v0.right_or(false) && v1.right_or(false)))),
+			(Iota::String(_), Iota::String(_)) => Some(Iota::String(None)),
+			(Iota::Matrix(m0), Iota::Matrix(m1)) => {
+				let ([r0, c0], [r1, c1]) = (matrix_dimensions(m0), matrix_dimensions(m1));
+				Some(Iota::Matrix(Right((if r0 == r1 { r0 } else { None }, if c0 == c1 { c0 } else { None }))))
+			},
+			(Iota::List(IotaList(Left(l0), _)), Iota::List(IotaList(Left(l1), _))) if l0.len() == l1.len() => {
+				let joined: Option<Vec<Iota>> = l0.iter().zip(l1.iter()).map(|(i0, i1)| i0.join(i1)).collect();
+				Some(joined.map_or(IotaList::new(Right(Some(l0.len()))), |joined| IotaList::new(Left(joined))).into())
+			},
+			(Iota::List(l0), Iota::List(l1)) => {
+				let element = match (&l0.1, &l1.1) {
+					(Some(e0), Some(e1)) => e0.join(e1),
+					_ => None
+				};
+				Some(IotaList::unknown(if l0.len() == l1.len() { l0.len() } else { None }, element).into())
+			},
+			_ => None
+		}
+	}
+}
+
 impl fmt::Display for Iota {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match &self {
 			Iota::Pattern(pattern) => {
 				write!(f, "HexPattern({}, {:?})", pattern.pattern_vec.iter().fold("".to_string(), |acc, dir| {acc + &format!("{dir:?}")}), pattern.start_dir)
 			},
-			Iota::Double(d) => if let Some(d) = d { write!(f, "{}", d) } else { write!(f, "UNKOWN") },
+			Iota::Double(d) => match d {
+				Left(d) => write!(f, "{}", format_double(*d)),
+				Right(interval) => if interval.is_unbounded() { write!(f, "UNKOWN") } else { write!(f, "UNKOWN {}", interval) },
+			},
+			Iota::Bool(b) => match b {
+				Some(true) => write!(f, "True"),
+				Some(false) => write!(f, "False"),
+				None => write!(f, "UNKNOWN"),
+			},
 			Iota::Vec(vec) => {
 				match vec {
-						Left(vec) => write!(f, "({}, {}, {})", vec.0, vec.1, vec.2),
+						Left(vec) => write!(f, "({}, {}, {})", format_double(vec.0), format_double(vec.1), format_double(vec.2)),
 						Right(within_range) => write!(f, "(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: {})", within_range),
 				}
 			}
 			Iota::Widget => write!(f, "Null"),
+			Iota::Garbage => write!(f, "Garbage"),
 			Iota::List(iotas) => write!(f, "{}", iotas),
 			Iota::Entity(entity) => write!(f, "{}", entity.name),
+			Iota::Addon(addon) => write!(f, "{}", addon.display()),
+			Iota::String(Some(string)) => write!(f, "{:?}", string),
+			Iota::String(None) => write!(f, "UNKNOWN string"),
+			Iota::Matrix(Left(matrix)) => write!(f, "{}", matrix),
+			Iota::Matrix(Right((None, None))) => write!(f, "UNKNOWN matrix"),
+			Iota::Matrix(Right((rows, columns))) => {
+				let describe = |n: &Option<usize>| n.map_or("?".to_string(), |n| n.to_string());
+				write!(f, "UNKNOWN {}×{} matrix", describe(rows), describe(columns))
+			},
+			Iota::Unknown => write!(f, "UNKNOWN"),
+			Iota::Symbolic(expr, _) => write!(f, "{}", expr.simplify()),
 		}
 	}
 }
@@ -212,39 +659,335 @@ impl fmt::Display for IotaList {
 				comma_separated.push_str(&list[list.len() - 1].to_string());
 				write!(f, "[{}]", comma_separated)
 			},
-    	Right(length) => write!(f, "[UNKOWN, len={}]", length.map_or("UNKOWN".to_string(), |length| length.to_string())),
+    	Right(length) => {
+				write!(f, "[UNKOWN, len={}", length.map_or("UNKOWN".to_string(), |length| length.to_string()))?;
+				match &self.1 {
+					Some(element) => write!(f, ", each {}]", element),
+					None => write!(f, "]"),
+				}
+			},
 		}
 	}
 }
 
 /// Stores all the possible stacks that could have been reached at this point.
-/// each element of stacks is a possible stack, with the result being Ok if no mishaps have occurred, and being Err if one has.
-/// The Vec<Iota> in the Pair is the stack, and the Option<Iota> is the 
-#[derive(Default)]
+/// each element of stacks is a possible stack, with the result being Ok if it can still be simulated (including after a
+/// mishap, see [`StackState::mishaps`]), and being Err if the simulator gave up on it.
+/// Serialized as the list of branches, each either `{"Ok": state}` or `{"Err": error}`.
+#[derive(Default, serde::Serialize, serde::Deserialize)]
 pub struct StackHolder(Vec<Result<StackState, ActionError>>);
 
 impl StackHolder {
-	fn iter(&self) -> std::slice::Iter<'_, Result<StackState, ActionError>> { self.0.iter() }
-	fn append(&mut self, result: &mut StackHolder) { self.0.append(&mut result.0) }
-	fn len(&self) -> usize { self.0.len() }
+	pub fn iter(&self) -> std::slice::Iter<'_, Result<StackState, ActionError>> { self.0.iter() }
+	pub fn len(&self) -> usize { self.0.len() }
+	pub fn is_empty(&self) -> bool { self.0.is_empty() }
 
-	pub fn apply_action(&mut self, action: Box<dyn Action>) {
+	/// Applies the action, the pattern at `pattern_index` in the hex, to every stack that can still be simulated, keeping
+	/// the others as they are. If the action mishaps, the mishap's effect is applied and the branch carries on from there;
+	/// otherwise the action's media cost in the selected version (see [`crate::version::Profile::cost`]) is added to the
+	/// branch, and its effect on the world recorded.
+	pub fn apply_action(&mut self, action: Box<dyn Action>, pattern_index: usize) {
 		let mut result_stacks: StackHolder = StackHolder::default();
 
-		for stack in self.iter() {
-			if let Ok(stack) = stack { result_stacks.append(&mut action.apply(stack)) }
+		for stack in self.0.drain(..) {
+			match stack {
+				Ok(stack) => {
+					let might_be_out_of_ambit = match check_ambit(&*action, &stack) {
+						Ok(argument) => argument,
+						Err(action_error) => {
+							result_stacks.0.push(Ok(stack.mishap(Mishap { pattern_index, action: action.name(), error: action_error })));
+							continue
+						}
+					};
+
+					let cost = profile().cost(&action.name(), action.cost(&stack));
+					let might_run_out = match check_media(&cost, &stack) {
+						Ok(might_run_out) => might_run_out,
+						Err(action_error) => {
+							result_stacks.0.push(Ok(stack.mishap(Mishap { pattern_index, action: action.name(), error: action_error })));
+							continue
+						}
+					};
+
+					let effect = action.effect(&stack).map(|effect| SpellEffect { pattern_index, cost, ..effect });
+					result_stacks.0.extend(action.apply(&stack).0.into_iter().map(|result| match result {
+						Ok(mut state) => {
+							state.add_cost(&cost);
+							if let Some(effect) = &effect {
+								state.effects.push(effect.clone());
+							}
+							if let Some(argument) = might_be_out_of_ambit {
+								state.flag(Mishap { pattern_index, action: action.name(), error: ActionError::OutOfAmbit { argument } });
+							}
+							if might_run_out {
+								state.flag(Mishap { pattern_index, action: action.name(), error: ActionError::NotEnoughMedia });
+							}
+							Ok(state)
+						},
+						Err(action_error) if action_error.is_mishap() => {
+							Ok(stack.mishap(Mishap { pattern_index, action: action.name(), error: action_error }))
+						},
+						Err(action_error) => Err(action_error)
+					}))
+				},
+				Err(action_error) => result_stacks.0.push(Err(action_error)),
+			}
+		}
+
+		*self = result_stacks;
+	}
+
+	/// Removes any branches that are identical to an earlier branch.
+	pub fn deduplicate(&mut self) {
+		let mut seen = HashSet::new();
+		self.0.retain(|result| seen.insert(result.clone()));
+	}
+
+	/// Merges branches that can be approximated by a single branch (see [`StackState::join`]) into that branch.
+	/// If `only_unknown` is true, only branches that differ in unknown values are merged.
+	pub fn widen(&mut self, only_unknown: bool) {
+		let mut widened: Vec<Result<StackState, ActionError>> = vec![];
+
+		'results: for result in self.0.drain(..) {
+			if let Ok(state) = &result {
+				for existing in widened.iter_mut() {
+					if let Ok(existing_state) = existing {
+						if let Some(joined) = existing_state.join(state, only_unknown) {
+							*existing_state = joined;
+							continue 'results;
+						}
+					}
+				}
+			}
+
+			widened.push(result);
+		}
+
+		self.0 = widened;
+	}
+
+	/// Keeps the number of branches at or below `cap`; identical branches are merged, then branches that differ only in
+	/// unknown values, then (only if there are too many branches) any branches that can be approximated by one branch. If there are still too many, the
+	/// extra branches are replaced by an [`ActionError::TooManyPossibilities`] branch.
+	pub fn limit(&mut self, cap: usize) {
+		self.deduplicate();
+		self.widen(true);
+
+		if self.len() > cap {
+			self.widen(false);
+		}
+
+		if self.len() > cap {
+			self.0.truncate(cap.saturating_sub(1));
+			self.0.push(Err(ActionError::TooManyPossibilities));
 		}
 	}
 
+	/// The least and most media any branch that can still be simulated could have spent, or None if there are no such branches.
+	pub fn cost(&self) -> Option<Interval> {
+		self.0.iter().filter_map(|result| result.as_ref().ok()).map(StackState::cost).reduce(|c0, c1| c0.hull(&c1))
+	}
+
 	pub fn single_state(state: StackState) -> StackHolder { StackHolder(vec![Ok(state)]) }
 	pub fn single(result: Result<StackState, ActionError>) -> StackHolder { StackHolder(vec![result]) }
 	pub fn new(results: Vec<Result<StackState, ActionError>>) -> StackHolder { StackHolder(results) }
 }
 
-#[derive(Default)]
+/// Checks the action's targets against the caster's ambit, returning the mishap if a target is known to be out of it,
+/// or the first target that might be out of it if there is one. Without a scene, nothing is checked.
+fn check_ambit(action: &dyn Action, state: &StackState) -> Result<Option<usize>, ActionError> {
+	let scene = match state.scene() {
+		Some(scene) => scene,
+		None => return Ok(None),
+	};
+
+	let mut might_be_out = None;
+
+	for argument in action.targets() {
+		let target = match state.stack.len().checked_sub(argument + 1) {
+			Some(index) => &state.stack[index],
+			None => continue,
+		};
+
+		match scene.in_ambit(target) {
+			Some(true) => {},
+			Some(false) => return Err(ActionError::OutOfAmbit { argument }),
+			None => { might_be_out.get_or_insert(argument); },
+		}
+	}
+
+	Ok(might_be_out)
+}
+
+/// Checks the media the branch will have spent after paying `cost` against the media the caster has, returning the
+/// mishap if it is known to be too much, or whether it might be. Without a caster or a known amount of media, nothing is
+/// checked.
+fn check_media(cost: &Interval, state: &StackState) -> Result<bool, ActionError> {
+	let available = match state.scene().and_then(|scene| scene.caster.as_ref()).and_then(Caster::available_media) {
+		Some(available) => available,
+		None => return Ok(false),
+	};
+
+	let spent = state.cost.add(cost);
+	if spent.lower > available {
+		return Err(ActionError::NotEnoughMedia)
+	}
+
+	Ok(spent.upper > available)
+}
+
+/// How many branches a StackManager keeps by default before approximating them.
+pub const DEFAULT_BRANCH_CAP: usize = 64;
+
+/// The most iotas an action builds at once; actions that would build more give up with [`ActionError::Indeterminate`]
+/// rather than allocating them, since the app simulates the hex again every frame.
+pub const MAX_STACK_LEN: usize = 4096;
+
+/// Steps a StackHolder through a hex one action at a time.
+pub struct StackManager {
+	stacks: StackHolder,
+	branch_cap: usize,
+	/// The index in the hex of the next pattern to be applied.
+	pattern_index: usize
+}
+
+impl Default for StackManager {
+	fn default() -> Self { StackManager::new(StackHolder::default()) }
+}
+
+impl StackManager {
+	pub fn new(stacks: StackHolder) -> StackManager { StackManager { stacks, branch_cap: DEFAULT_BRANCH_CAP, pattern_index: 0 } }
+
+	/// Sets the most branches the manager will keep track of; see [`StackHolder::limit`].
+	pub fn set_branch_cap(&mut self, branch_cap: usize) { self.branch_cap = usize::max(branch_cap, 1) }
+
+	pub fn stacks(&self) -> &StackHolder { &self.stacks }
+
+	/// Applies the action to every branch, then merges any branches that can be merged without losing information,
+	/// approximating further if there are more branches than the branch cap.
+	pub fn apply_action(&mut self, action: Box<dyn Action>) {
+		self.share_branch_cap();
+		self.stacks.apply_action(action, self.pattern_index);
+		self.stacks.limit(self.branch_cap);
+		self.pattern_index += 1;
+	}
+
+	/// Applies the action as the pattern at `pattern_index` in the hex, for when some patterns aren't cast as actions
+	/// (e.g. those inside an Introspection block).
+	pub fn apply_action_at(&mut self, action: Box<dyn Action>, pattern_index: usize) {
+		self.pattern_index = pattern_index;
+		self.apply_action(action);
+	}
+
+	/// Replaces every branch that can still be simulated with the branches `step` returns for it, then merges branches
+	/// the way [`StackManager::apply_action`] does. For stepping branches separately, e.g. through their own frames.
+	pub fn step_branches(&mut self, mut step: impl FnMut(StackState) -> StackHolder) {
+		self.share_branch_cap();
+		let mut stepped = StackHolder::default();
+
+		for result in self.stacks.0.drain(..) {
+			match result {
+				Ok(state) => stepped.0.extend(step(state).0),
+				Err(action_error) => stepped.0.push(Err(action_error)),
+			}
+		}
+
+		self.stacks = stepped;
+		self.stacks.limit(self.branch_cap);
+	}
+
+	/// Tells every branch the manager's branch cap, for the actions about to be applied to them.
+	fn share_branch_cap(&mut self) {
+		for state in self.stacks.0.iter_mut().filter_map(|result| result.as_mut().ok()) {
+			state.branch_cap = self.branch_cap;
+		}
+	}
+}
+
+/// What a branch still has to cast before carrying on with the rest of the hex, pushed by Hermes' and Thoth's Gambits.
+#[derive(Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Frame {
+	/// Casting the patterns in order; `next` is the index of the next one to cast.
+	Evaluate { patterns: Vec<HexPattern>, next: usize },
+	/// Casting `code` once for each element of `data`, each time on `base` with the element pushed on top, and
+	/// collecting the stacks left behind into `acc`. `next` is the index of the next element.
+	ForEach { code: Vec<HexPattern>, data: Vec<Iota>, next: usize, base: Vec<Iota>, acc: Vec<Iota> }
+}
+
+/// Describes how far through the frame the branch is, e.g. "Thoth's Gambit: element 2 of 3, 4 iotas collected".
+impl Display for Frame {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Frame::Evaluate { patterns, next } => write!(f, "Hermes' Gambit: pattern {} of {}", next, patterns.len()),
+			Frame::ForEach { data, next, acc, .. } => {
+				write!(f, "Thoth's Gambit: element {} of {}, {} iotas collected", next, data.len(), acc.len())
+			},
+		}
+	}
+}
+
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct StackState {
 	stack: Vec<Iota>,
-	ravenmind: Option<Iota>
+	ravenmind: Option<Iota>,
+	symbolic: bool,
+	/// true if this state was widened from several branches, so it may describe stacks that can't actually be reached.
+	approximate: bool,
+	/// every mishap that happened on the way to this state, in order.
+	mishaps: Vec<Mishap>,
+	/// mishaps that might have happened on the way to this state, depending on values that aren't known (e.g. a target
+	/// that might be out of ambit), in order. The state is the one reached if they didn't happen.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	possible_mishaps: Vec<Mishap>,
+	/// every change spells made to the world on the way to this state, in order.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	effects: Vec<SpellEffect>,
+	/// the frames the branch is casting, outermost first.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	frames: Vec<Frame>,
+	/// the bounds on how much media has been spent to reach this state, in media units (see [`crate::media`]).
+	cost: Interval,
+	/// the world the hex is cast in, if one has been loaded. Spells that change blocks change it for their branch only.
+	/// It isn't serialized, since it is loaded from a scene file.
+	#[serde(skip)]
+	scene: Option<Rc<Scene>>,
+	/// the branch cap of the StackManager casting the branch (see [`StackManager::set_branch_cap`]), for actions that
+	/// give up rather than make more branches than it keeps.
+	#[serde(skip, default = "default_branch_cap")]
+	branch_cap: usize
+}
+
+fn default_branch_cap() -> usize { DEFAULT_BRANCH_CAP }
+
+impl Default for StackState {
+	fn default() -> Self { StackState::new(vec![], None) }
+}
+
+/// Structural equality, ignoring whether the states are approximate.
+impl PartialEq for StackState {
+	fn eq(&self, other: &Self) -> bool {
+		self.stack == other.stack && self.ravenmind == other.ravenmind && self.symbolic == other.symbolic && self.mishaps == other.mishaps &&
+			self.possible_mishaps == other.possible_mishaps && self.effects == other.effects && self.frames == other.frames &&
+			interval_eq(&self.cost, &other.cost) &&
+			self.same_scene(other)
+	}
+}
+
+impl Eq for StackState {}
+
+/// Doesn't hash the scene, which usually is the same for every branch.
+impl Hash for StackState {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.stack.hash(state);
+		self.ravenmind.hash(state);
+		self.symbolic.hash(state);
+		self.mishaps.hash(state);
+		self.possible_mishaps.hash(state);
+		self.effects.hash(state);
+		self.frames.hash(state);
+		hash_interval(&self.cost, state);
+	}
 }
 
 impl Display for StackState {
@@ -269,21 +1012,463 @@ impl Display for StackState {
 }
 
 impl StackState {
-	pub fn new(stack: Vec<Iota>, ravenmind: Option<Iota>) -> StackState { StackState { stack, ravenmind } }
+	pub fn new(stack: Vec<Iota>, ravenmind: Option<Iota>) -> StackState {
+		StackState {
+			stack, ravenmind, symbolic: false, approximate: false, mishaps: vec![], possible_mishaps: vec![], effects: vec![],
+			frames: vec![], cost: Interval::point(0.0), scene: None, branch_cap: DEFAULT_BRANCH_CAP
+		}
+	}
+
+	pub fn stack(&self) -> &[Iota] { &self.stack }
+
+	pub fn ravenmind(&self) -> Option<&Iota> { self.ravenmind.as_ref() }
+
+	/// Returns a copy of this state with the stack replaced, keeping the ravenmind.
+	pub fn with_stack(&self, stack: Vec<Iota>) -> StackState { StackState { stack, ..self.clone() } }
+
+	pub fn set_stack(&mut self, stack: Vec<Iota>) { self.stack = stack }
+
+	/// In symbolic mode, the results of actions on symbolic iotas (see [`Iota::symbol`]) keep track of the expression they were computed from.
+	pub fn set_symbolic(&mut self, symbolic: bool) { self.symbolic = symbolic }
+
+	pub fn is_symbolic(&self) -> bool { self.symbolic }
+
+	/// Sets the world the hex is cast in, which entity actions read from. Without a scene, nothing is known about the world.
+	pub fn set_scene(&mut self, scene: Option<Rc<Scene>>) { self.scene = scene }
+
+	pub fn scene(&self) -> Option<&Scene> { self.scene.as_deref() }
+
+	/// The world the hex is cast in, to be changed by a spell; other branches sharing the scene keep the scene as it was.
+	pub fn scene_mut(&mut self) -> Option<&mut Scene> { self.scene.as_mut().map(Rc::make_mut) }
+
+	/// Whether both states are in the same world, which is cheap to check if they share it.
+	fn same_scene(&self, other: &StackState) -> bool {
+		match (&self.scene, &other.scene) {
+			(None, None) => true,
+			(Some(s0), Some(s1)) => Rc::ptr_eq(s0, s1) || s0 == s1,
+			_ => false,
+		}
+	}
+
+	pub fn is_approximate(&self) -> bool { self.approximate }
+
+	/// The most branches the StackManager casting the branch keeps track of.
+	pub fn branch_cap(&self) -> usize { self.branch_cap }
+
+	pub fn mishaps(&self) -> &[Mishap] { &self.mishaps }
+
+	pub fn possible_mishaps(&self) -> &[Mishap] { &self.possible_mishaps }
+
+	/// Records a mishap that might have happened, without applying its effect.
+	pub fn flag(&mut self, mishap: Mishap) { self.possible_mishaps.push(mishap) }
+
+	pub fn effects(&self) -> &[SpellEffect] { &self.effects }
+
+	/// The frames the branch is casting, outermost first; empty if it is casting the hex itself.
+	pub fn frames(&self) -> &[Frame] { &self.frames }
+
+	pub fn push_frame(&mut self, frame: Frame) { self.frames.push(frame) }
+
+	pub fn pop_frame(&mut self) -> Option<Frame> { self.frames.pop() }
+
+	/// The least and most media that could have been spent to reach this state.
+	pub fn cost(&self) -> Interval { self.cost }
+
+	pub fn add_cost(&mut self, cost: &Interval) { self.cost = self.cost.add(cost) }
+
+	/// Returns the state Hex Casting would continue from after the mishap happened in this state.
+	pub fn mishap(&self, mishap: Mishap) -> StackState {
+		let mut state = self.clone();
+		mishap.error.apply_effect(&mut state.stack);
+		state.mishaps.push(mishap);
+		state
+	}
+
+	/// Returns a state that approximates both states, or None if they can't be approximated by one state (e.g. their
+	/// stacks are different lengths, or a spell changed the world in only one of them). If `only_unknown` is true, the
+	/// states are only joined if every iota that differs between them is unknown in both, or is covered by the other
+	/// (e.g. an unknown double in [0, 3] covers 2). Iotas of different types are never joined then, so a possible Null
+	/// isn't hidden inside an unknown iota.
+	pub fn join(&self, other: &StackState, only_unknown: bool) -> Option<StackState> {
+		if self.stack.len() != other.stack.len() || self.symbolic != other.symbolic || self.mishaps != other.mishaps ||
+			self.possible_mishaps != other.possible_mishaps || self.effects != other.effects || self.frames != other.frames ||
+			!self.same_scene(other) {
+			return None
+		}
+
+		let mergeable = |i0: &Iota, i1: &Iota| {
+			if i0 == i1 {
+				return true
+			}
+			if std::mem::discriminant(i0.value()) != std::mem::discriminant(i1.value()) {
+				return false
+			}
+			(!i0.is_known() && !i1.is_known()) || i0.join(i1).map_or(false, |joined| joined == *i0 || joined == *i1)
+		};
+
+		if only_unknown && !self.stack.iter().zip(other.stack.iter()).all(|(i0, i1)| mergeable(i0, i1)) {
+			return None
+		}
+
+		let stack: Option<Vec<Iota>> = self.stack.iter().zip(other.stack.iter()).map(|(i0, i1)| i0.join(i1)).collect();
+		let ravenmind = match (&self.ravenmind, &other.ravenmind) {
+			(None, None) => Some(None),
+			(Some(r0), Some(r1)) => r0.join(r1).map(Some),
+			_ => None
+		};
+
+		let mut joined = StackState {
+			stack: stack?,
+			ravenmind: ravenmind?,
+			symbolic: self.symbolic,
+			approximate: false,
+			mishaps: self.mishaps.clone(),
+			possible_mishaps: self.possible_mishaps.clone(),
+			effects: self.effects.clone(),
+			frames: self.frames.clone(),
+			cost: self.cost.hull(&other.cost),
+			scene: self.scene.clone(),
+			branch_cap: self.branch_cap
+		};
+		joined.approximate = self.approximate || other.approximate || (joined != *self && joined != *other);
+		Some(joined)
+	}
+}
+
+/// The type of an iota, as named in mishap messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IotaType {
+	Pattern,
+	Double,
+	Bool,
+	Vec,
+	Null,
+	Garbage,
+	List,
+	Entity,
+	Unknown,
+	Addon,
+	String,
+	Matrix
+}
+
+impl IotaType {
+	/// Every type, for arguments that accept any iota.
+	pub const ANY: &'static [IotaType] = &[
+		IotaType::Pattern, IotaType::Double, IotaType::Bool, IotaType::Vec, IotaType::Null,
+		IotaType::Garbage, IotaType::List, IotaType::Entity, IotaType::Unknown, IotaType::Addon,
+		IotaType::String, IotaType::Matrix
+	];
+
+	pub fn of(iota: &Iota) -> IotaType {
+		match iota.value() {
+			Iota::Pattern(_) => IotaType::Pattern,
+			Iota::Double(_) => IotaType::Double,
+			Iota::Bool(_) => IotaType::Bool,
+			Iota::Vec(_) => IotaType::Vec,
+			Iota::Widget => IotaType::Null,
+			Iota::Garbage => IotaType::Garbage,
+			Iota::List(_) => IotaType::List,
+			Iota::Entity(_) => IotaType::Entity,
+			Iota::Addon(_) => IotaType::Addon,
+			Iota::String(_) => IotaType::String,
+			Iota::Matrix(_) => IotaType::Matrix,
+			Iota::Unknown | Iota::Symbolic(_, _) => IotaType::Unknown,
+		}
+	}
+
+	/// A slice holding only this type, e.g. for the signature of an action that pushes an iota of this type.
+	pub fn only(&self) -> &'static [IotaType] { std::slice::from_ref(&IotaType::ANY[*self as usize]) }
+
+	/// The name of the type, e.g. "number".
+	pub fn name(&self) -> &'static str {
+		match self {
+			IotaType::Pattern => "pattern",
+			IotaType::Double => "number",
+			IotaType::Bool => "boolean",
+			IotaType::Vec => "vector",
+			IotaType::Null => "null",
+			IotaType::Garbage => "garbage",
+			IotaType::List => "list",
+			IotaType::Entity => "entity",
+			IotaType::Unknown => "unknown iota",
+			IotaType::Addon => "addon iota",
+			IotaType::String => "string",
+			IotaType::Matrix => "matrix",
+		}
+	}
+}
+
+/// Displays the type the way mishap messages do, e.g. "a number".
+impl Display for IotaType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			IotaType::Null | IotaType::Garbage => write!(f, "{}", self.name()),
+			IotaType::Entity | IotaType::Unknown | IotaType::Addon => write!(f, "an {}", self.name()),
+			_ => write!(f, "a {}", self.name()),
+		}
+	}
+}
+
+/// The type of one of an action's outputs, for stack effect inference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputType {
+	/// The output is one of these types.
+	Types(&'static [IotaType]),
+	/// The output is the input at this index (deepest first), moved or copied.
+	Input(usize)
+}
+
+/// What an action does to the stack regardless of the values of its inputs: the types of the iotas it takes (deepest
+/// first), and of the iotas it pushes in their place.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+	pub inputs: Vec<&'static [IotaType]>,
+	pub outputs: Vec<OutputType>
 }
 
-#[derive(Clone, Copy)]
+/// What went wrong when an action was applied. Arguments are counted from the top of the stack, starting at 0,
+/// the way Hex Casting counts them in mishap messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum ActionError {
-	OutOfBounds,
-	StackTooSmall,
+	/// The argument was a number outside of the range the action accepts, or wasn't a whole number when it needed to be.
+	OutOfBounds { argument: usize },
+	/// The action needed `expected` arguments, but there were only `found` iotas on the stack.
+	StackTooSmall { expected: usize, found: usize },
 	DivByZero,
-	InvalidType
+	/// The argument was a position or entity outside of the caster's ambit (see [`crate::world::Caster`]).
+	OutOfAmbit { argument: usize },
+	/// The argument was `found`, but the action needed one of the `expected` types.
+	InvalidType {
+		argument: usize,
+		#[serde(deserialize_with = "crate::serialization::static_types")]
+		expected: &'static [IotaType],
+		found: IotaType
+	},
+	/// The argument was a matrix with the wrong numbers of rows or columns for the action (e.g. multiplying matrices that
+	/// don't fit together, or taking the determinant of one that isn't square).
+	WrongDimensions { argument: usize },
+	/// The argument was a position without an Akashic Library's record in it (see [`crate::akashic::Library::record`]).
+	NoAkashicRecord { argument: usize },
+	/// The hex would spend more media than it has (see [`crate::world::Caster::available_media`]).
+	NotEnoughMedia,
+	/// The item in the caster's other hand can't be read (or written, if `writing` is true) by Scribe's Reflection or
+	/// Scribe's Gambit (see [`crate::world::Caster::held_item`]).
+	BadHeldItem { writing: bool },
+	/// The pattern doesn't exist in the version of Hex Casting being simulated (see [`crate::version`]).
+	InvalidPattern { version: GameVersion },
+	/// Not a mishap; the simulator can't represent the stack that would result (e.g. splatting a list of unknown length).
+	Indeterminate,
+	/// Not a mishap; there were too many possible stacks to keep track of, so some of them were dropped.
+	TooManyPossibilities,
+	/// Not a mishap; the branch was still casting after too many steps (e.g. a Hermes' Gambit that casts itself forever).
+	TooManySteps
+}
+
+impl ActionError {
+	/// Whether Hex Casting would mishap; if not, the simulator has given up on the branch instead.
+	pub fn is_mishap(&self) -> bool { !matches!(self, ActionError::Indeterminate | ActionError::TooManyPossibilities | ActionError::TooManySteps) }
+
+	/// Applies the mishap's effect to the stack the action was applied to, the way Hex Casting does:
+	/// missing arguments are filled in with Garbage, invalid arguments are replaced with Garbage, and dividing by zero or
+	/// drawing a pattern that doesn't exist pushes Garbage. The rest of the stack is left as it was.
+	pub fn apply_effect(&self, stack: &mut Vec<Iota>) {
+		match self {
+			ActionError::StackTooSmall { expected, found } => {
+				stack.extend(std::iter::repeat(Iota::Garbage).take(expected.saturating_sub(*found)))
+			},
+			ActionError::OutOfBounds { argument } | ActionError::InvalidType { argument, .. } | ActionError::WrongDimensions { argument } => {
+				if *argument < stack.len() {
+					let index = stack.len() - 1 - argument;
+					stack[index] = Iota::Garbage;
+				}
+			},
+			ActionError::DivByZero | ActionError::InvalidPattern { .. } => stack.push(Iota::Garbage),
+			ActionError::OutOfAmbit { .. } | ActionError::NoAkashicRecord { .. } | ActionError::NotEnoughMedia | ActionError::BadHeldItem { .. } |
+			ActionError::Indeterminate | ActionError::TooManyPossibilities | ActionError::TooManySteps => {},
+		}
+	}
+}
+
+impl Display for ActionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ActionError::OutOfBounds { argument } => write!(f, "argument {} was out of range", argument),
+			ActionError::StackTooSmall { expected, found } => write!(f, "expected {} arguments but the stack was only {} tall", expected, found),
+			ActionError::DivByZero => write!(f, "attempted to divide by zero"),
+			ActionError::OutOfAmbit { argument } => write!(f, "argument {} is out of ambit", argument),
+			ActionError::WrongDimensions { argument } => write!(f, "argument {} is a matrix of the wrong size", argument),
+			ActionError::NoAkashicRecord { argument } => write!(f, "argument {} isn't the position of an akashic record", argument),
+			ActionError::NotEnoughMedia => write!(f, "not enough media"),
+			ActionError::InvalidPattern { version } => write!(f, "the pattern doesn't exist in Hex Casting {}", version),
+			ActionError::BadHeldItem { writing: false } => write!(f, "the held item can't be read from"),
+			ActionError::BadHeldItem { writing: true } => write!(f, "the held item can't be written to"),
+			ActionError::InvalidType { argument, expected, found } => {
+				write!(f, "got {} at argument {}, expected ", found, argument)?;
+				for (i, expected) in expected.iter().enumerate() {
+					if i > 0 {
+						write!(f, " or ")?;
+					}
+					write!(f, "{}", expected)?;
+				}
+				Ok(())
+			},
+			ActionError::Indeterminate => write!(f, "the simulator can't represent the result"),
+			ActionError::TooManyPossibilities => write!(f, "too many possibilities"),
+			ActionError::TooManySteps => write!(f, "still casting after too many steps"),
+		}
+	}
+}
+
+/// A mishap that happened on a branch, and where in the hex it happened.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct Mishap {
+	/// The index of the pattern in the hex that mishapped.
+	pub pattern_index: usize,
+	/// The name of the action that mishapped (see [`Action::name`]).
+	pub action: String,
+	pub error: ActionError
+}
+
+impl Display for Mishap {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "mishap at pattern {} ({}): {}", self.pattern_index, self.action, self.error)
+	}
+}
+
+/// The kinds of change a spell makes to the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EffectKind {
+	Impulse,
+	Explosion,
+	Fireball,
+	Blink,
+	BreakBlock,
+	PlaceBlock,
+	CreateWater,
+	CreateLava,
+	ConjureBlock,
+	ConjureLight
+}
+
+impl EffectKind {
+	/// The name of the spell, e.g. "Break Block".
+	pub fn name(&self) -> &'static str {
+		match self {
+			EffectKind::Impulse => "Impulse",
+			EffectKind::Explosion => "Explosion",
+			EffectKind::Fireball => "Fireball",
+			EffectKind::Blink => "Blink",
+			EffectKind::BreakBlock => "Break Block",
+			EffectKind::PlaceBlock => "Place Block",
+			EffectKind::CreateWater => "Create Water",
+			EffectKind::CreateLava => "Create Lava",
+			EffectKind::ConjureBlock => "Conjure Block",
+			EffectKind::ConjureLight => "Conjure Light",
+		}
+	}
+}
+
+/// A change a spell made to the world on a branch, and where in the hex it was cast.
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+pub struct SpellEffect {
+	/// The index of the pattern in the hex that cast the spell.
+	pub pattern_index: usize,
+	pub kind: EffectKind,
+	/// The entity or position the spell was cast on.
+	pub target: Iota,
+	/// How strong the spell was, for spells that have a strength (e.g. the vector of an Impulse, or the strength of an
+	/// Explosion).
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub magnitude: Option<Iota>,
+	/// The media the spell cost, in media units (see [`crate::media`]).
+	pub cost: Interval
+}
+
+impl SpellEffect {
+	/// An effect of the kind on the target, to be recorded with its pattern index and cost by [`StackHolder::apply_action`].
+	pub fn new(kind: EffectKind, target: Iota, magnitude: Option<Iota>) -> SpellEffect {
+		SpellEffect { pattern_index: 0, kind, target, magnitude, cost: Interval::point(0.0) }
+	}
+}
+
+impl PartialEq for SpellEffect {
+	fn eq(&self, other: &Self) -> bool {
+		self.pattern_index == other.pattern_index && self.kind == other.kind && self.target == other.target &&
+			self.magnitude == other.magnitude && interval_eq(&self.cost, &other.cost)
+	}
+}
+
+impl Eq for SpellEffect {}
+
+impl Hash for SpellEffect {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.pattern_index.hash(state);
+		self.kind.hash(state);
+		self.target.hash(state);
+		self.magnitude.hash(state);
+		hash_interval(&self.cost, state);
+	}
+}
+
+/// Describes the effect, e.g. "pattern 2: Fireball at (0, 64, 0), strength 3 (10 dust)".
+impl Display for SpellEffect {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "pattern {}: {} ", self.pattern_index, self.kind.name())?;
+		match self.target.value() {
+			Iota::Entity(_) => write!(f, "on {}", self.target)?,
+			_ => write!(f, "at {}", self.target)?,
+		}
+		match (self.kind, &self.magnitude) {
+			(EffectKind::Impulse, Some(magnitude)) => write!(f, " by {}", magnitude)?,
+			(EffectKind::Blink, Some(magnitude)) => write!(f, " by {} blocks", magnitude)?,
+			(_, Some(magnitude)) => write!(f, ", strength {}", magnitude)?,
+			(_, None) => {},
+		}
+		write!(f, " ({})", crate::media::describe_dust(&self.cost))
+	}
+}
+
+/// Converts the name of a type to snake case, dropping the path, e.g. `hexedit::actions::maths::DeconstructVec`
+/// becomes `deconstruct_vec`.
+fn snake_case_name<T: ?Sized>() -> String {
+	let type_name = std::any::type_name::<T>();
+	let name = type_name.rsplit("::").next().unwrap_or(type_name);
+
+	name.chars().enumerate().fold(String::new(), |mut acc, (i, c)| {
+		if c.is_uppercase() && i != 0 {
+			acc.push('_');
+		}
+		acc.push(c.to_ascii_lowercase());
+		acc
+	})
 }
 
 pub trait Action {
 	/// Takes in a list of vectors representing one possible stack at the point the action is called,
 	/// and returns a vector of possible resulting stacks.
 	fn apply(&self, iotas: &StackState) -> StackHolder;
+
+	/// The name of the action, as recorded in mishaps; by default the name of its type in snake case.
+	fn name(&self) -> String { snake_case_name::<Self>() }
+
+	/// How much media the action costs when applied to the stack, in media units (see [`crate::media`]); free by default.
+	/// Only charged if the action doesn't mishap.
+	fn cost(&self, _stack_state: &StackState) -> Interval { Interval::point(0.0) }
+
+	/// What the action does to the stack regardless of the values of its inputs, or None if that depends on the values
+	/// (e.g. Gemini Gambit, which pushes as many iotas as its input says).
+	fn signature(&self) -> Option<Signature> { None }
+
+	/// The arguments (counted from the top of the stack) that are targets, which must be in the caster's ambit; none by default.
+	fn targets(&self) -> Vec<usize> { vec![] }
+
+	/// The change the action makes to the world when applied to the stack, for spells; none by default. Only recorded
+	/// if the action doesn't mishap.
+	fn effect(&self, _stack_state: &StackState) -> Option<SpellEffect> { None }
 }
 
 pub trait ConstLenAction {
@@ -292,6 +1477,26 @@ pub trait ConstLenAction {
 	// takes in an array of vectors representing one possible input the ConstLenAction could receive,
 	// and returns a vec of possible outputs of the action.
 	fn apply(&self, iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>>;
+
+	/// How the outputs of the action are written in symbolic mode; by default as a call to the action's name.
+	/// Returns None if the action handles symbolic inputs itself (e.g. it only moves its inputs around), in which case it is
+	/// passed its inputs with their symbolic expressions still attached, and should look through them with [`Iota::value`].
+	fn op(&self) -> Option<Op> { Some(Op::Call(snake_case_name::<Self>())) }
+
+	/// How much media the action costs given its inputs, in media units (see [`crate::media`]); free by default.
+	fn cost(&self, _iotas: &[Iota]) -> Interval { Interval::point(0.0) }
+
+	/// The types each input may be, deepest first; any type by default.
+	fn input_types(&self) -> Vec<&'static [IotaType]> { vec![IotaType::ANY; Self::len()] }
+
+	/// The types of the outputs, deepest first, or None if the number or types of the outputs depend on the values of the inputs.
+	fn output_types(&self) -> Option<Vec<OutputType>> { None }
+
+	/// The arguments (counted from the top of the stack) that must be in the caster's ambit; see [`Action::targets`].
+	fn targets(&self) -> Vec<usize> { vec![] }
+
+	/// The change the action makes to the world given its inputs; see [`Action::effect`].
+	fn effect(&self, _iotas: &[Iota]) -> Option<SpellEffect> { None }
 }
 
 pub trait FixedArgsAction<Args> {
@@ -299,21 +1504,80 @@ pub trait FixedArgsAction<Args> {
 }
 
 impl <T> Action for T where T: ConstLenAction {
+	fn targets(&self) -> Vec<usize> { ConstLenAction::targets(self) }
+
+	fn signature(&self) -> Option<Signature> {
+		let inputs = self.input_types();
+		debug_assert_eq!(inputs.len(), T::len());
+		self.output_types().map(|outputs| Signature { inputs, outputs })
+	}
+
+	fn cost(&self, stack_state: &StackState) -> Interval {
+		let len = T::len();
+		let stack_len = stack_state.stack.len();
+
+		if stack_len < len {
+			return Interval::point(0.0)
+		}
+
+		ConstLenAction::cost(self, &Vec::from_iter(stack_state.stack[stack_len - len..].iter().map(|iota| iota.value().clone())))
+	}
+
+	fn effect(&self, stack_state: &StackState) -> Option<SpellEffect> {
+		let len = T::len();
+		let stack_len = stack_state.stack.len();
+
+		if stack_len < len {
+			return None
+		}
+
+		ConstLenAction::effect(self, &Vec::from_iter(stack_state.stack[stack_len - len..].iter().map(|iota| iota.value().clone())))
+	}
+
 	fn apply(&self, stack_state: &StackState) -> StackHolder {
 		let len = T::len();
 		let stack_len = stack_state.stack.len();
 
 		if stack_len < len {
-			return StackHolder::single(Err(ActionError::StackTooSmall));
+			return StackHolder::single(Err(ActionError::StackTooSmall { expected: len, found: stack_len }));
 		}
 
+		let inputs = &stack_state.stack[stack_len - len..];
+		let op = self.op();
+
+		let results = match op {
+			Some(_) => self.apply(&Vec::from_iter(inputs.iter().map(|iota| iota.value().clone()))),
+			None => self.apply(inputs),
+		};
+
+		// in symbolic mode, outputs that were computed from symbolic inputs (rather than passed through) record how.
+		let call = match op {
+			Some(op) if stack_state.symbolic && inputs.iter().any(|iota| matches!(iota, Iota::Symbolic(_, _))) => {
+				Some(Expr::apply(op, Vec::from_iter(inputs.iter().map(Iota::expr))))
+			},
+			_ => None
+		};
+
 		StackHolder::new(
-			Vec::from_iter(self.apply(&stack_state.stack[stack_len - len..])
-			.iter()
-			.map(|result| match result {
-				Ok(iota_vec) => Ok(StackState::new(iota_vec.clone(), stack_state.ravenmind.clone())),
-				Err(action_error) => Err(*action_error),
-			})
+			Vec::from_iter(results
+			.into_iter()
+			.map(|result| result.map(|mut iota_vec| {
+				if let Some(call) = &call {
+					let outputs = iota_vec.len();
+					iota_vec = Vec::from_iter(iota_vec.into_iter().enumerate().map(|(i, iota)| {
+						if iota.is_known() || matches!(iota, Iota::Symbolic(_, _)) {
+							return iota
+						}
+
+						let expr = if outputs == 1 { call.clone() } else { Rc::new(Expr::Output(call.clone(), i)) };
+						Iota::Symbolic(expr, Box::new(iota))
+					}));
+				}
+
+				let mut stack = stack_state.stack[..stack_len - len].to_vec();
+				stack.append(&mut iota_vec);
+				stack_state.with_stack(stack)
+			}))
 		))
 	}
 }
@@ -339,9 +1603,13 @@ mod tests {
 		let pattern = Iota::Pattern(HexPattern { pattern_vec: vec![HexDir::W], start_dir: HexAbsoluteDir::East });
 		let def_double: Iota = 124.1231.into();
 		let indef_double: Iota = None.into();
+		let bounded_double: Iota = Interval::integer(0.0, 3.0).into();
 		let def_vec: Iota = (123.0123, 63.0, -523.0).into();
 		let indef_vec: Iota = Right(false).into();
 		let widget = Iota::Widget;
+		let garbage = Iota::Garbage;
+		let def_bool: Iota = true.into();
+		let indef_bool: Iota = Iota::Bool(None);
 		let entity = Iota::Entity(IotaEntity::new("Zombie")
 			.add_guaranteed(EntityType::Monster)
 			.add_guaranteed(EntityType::Living)
@@ -355,9 +1623,13 @@ mod tests {
 		assert_eq!(&pattern.to_string(), "HexPattern(W, East)");
 		assert_eq!(&def_double.to_string(), "124.1231");
 		assert_eq!(&indef_double.to_string(), "UNKOWN");
+		assert_eq!(&bounded_double.to_string(), "UNKOWN integer in [0, 3]");
 		assert_eq!(&def_vec.to_string(), "(123.0123, 63, -523)");
 		assert_eq!(&indef_vec.to_string(), "(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: false)");
 		assert_eq!(&widget.to_string(), "Null");
+		assert_eq!(&garbage.to_string(), "Garbage");
+		assert_eq!(&def_bool.to_string(), "True");
+		assert_eq!(&indef_bool.to_string(), "UNKNOWN");
 		assert_eq!(&entity.to_string(), "Zombie");
 		assert_eq!(&indef_list.to_string(), "[UNKOWN, len=4]");
 		assert_eq!(&indef_list_unknown_length.to_string(), "[UNKOWN, len=UNKOWN]");
@@ -0,0 +1,209 @@
+use std::fmt;
+use std::ops::Range;
+
+use crate::actions::registry::action_for;
+use crate::hex_pattern::HexPattern;
+use crate::simulator::{IotaType, OutputType};
+
+/// The angle signatures of Introspection and Retrospection, which start and end a block of patterns that is pushed as a
+/// list instead of being cast.
+pub const INTROSPECTION: &str = "qqq";
+pub const RETROSPECTION: &str = "eee";
+
+/// A set of iota types; the types an iota could be, without knowing its value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TypeSet(u16);
+
+impl TypeSet {
+	pub fn of(types: &[IotaType]) -> TypeSet { TypeSet(types.iter().fold(0, |bits, t| bits | 1 << *t as u16)) }
+
+	pub fn any() -> TypeSet { TypeSet::of(IotaType::ANY) }
+
+	pub fn contains(&self, iota_type: IotaType) -> bool { self.0 & 1 << iota_type as u16 != 0 }
+
+	pub fn intersect(&self, other: &TypeSet) -> TypeSet { TypeSet(self.0 & other.0) }
+
+	pub fn is_empty(&self) -> bool { self.0 == 0 }
+
+	pub fn types(&self) -> Vec<IotaType> { IotaType::ANY.iter().filter(|t| self.contains(**t)).copied().collect() }
+}
+
+impl fmt::Display for TypeSet {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if *self == TypeSet::any() {
+			return write!(f, "any")
+		}
+
+		let names: Vec<&str> = self.types().iter().map(IotaType::name).collect();
+		write!(f, "{}", names.join(" or "))
+	}
+}
+
+/// What a list of patterns does to the stack regardless of the values it is given, like the type of a function:
+/// the types of the iotas it takes from the stack and of the iotas it leaves in their place, both deepest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StackEffect {
+	pub inputs: Vec<TypeSet>,
+	pub outputs: Vec<TypeSet>
+}
+
+fn write_types(f: &mut fmt::Formatter<'_>, types: &[TypeSet]) -> fmt::Result {
+	let types: Vec<String> = types.iter().map(TypeSet::to_string).collect();
+	write!(f, "({})", types.join(", "))
+}
+
+impl fmt::Display for StackEffect {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "takes ")?;
+		write_types(f, &self.inputs)?;
+		write!(f, ", returns ")?;
+		write_types(f, &self.outputs)
+	}
+}
+
+/// Why the stack effect of a list of patterns couldn't be inferred. Pattern indices are into the whole list of patterns.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EffectError {
+	/// The simulator doesn't know the pattern.
+	UnknownPattern { pattern_index: usize },
+	/// What the pattern does to the stack depends on the values it's given.
+	Dynamic { pattern_index: usize },
+	/// An Introspection without a matching Retrospection, or the other way around.
+	UnbalancedBlock { pattern_index: usize },
+	/// The pattern's argument can never be one of the types it needs. Arguments are counted from the top of the stack.
+	Mismatch { pattern_index: usize, argument: usize, expected: TypeSet, found: TypeSet }
+}
+
+impl EffectError {
+	/// Whether the error is because the effect depends on values that are only known when the hex is cast.
+	pub fn is_dynamic(&self) -> bool { matches!(self, EffectError::Dynamic { .. }) }
+}
+
+impl fmt::Display for EffectError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			EffectError::UnknownPattern { pattern_index } => write!(f, "pattern {} isn't known to the simulator", pattern_index),
+			EffectError::Dynamic { pattern_index } => write!(f, "depends on runtime values at pattern {}", pattern_index),
+			EffectError::UnbalancedBlock { pattern_index } => write!(f, "pattern {} has no matching Introspection or Retrospection", pattern_index),
+			EffectError::Mismatch { pattern_index, argument, expected, found } => {
+				write!(f, "pattern {} needs {} for argument {}, but is given {}", pattern_index, expected, argument, found)
+			},
+		}
+	}
+}
+
+/// Returns the index of the Retrospection that closes the Introspection at `start`, allowing for nested blocks.
+pub(crate) fn block_end(patterns: &[HexPattern], start: usize) -> Result<usize, EffectError> {
+	let mut depth = 0;
+
+	for (i, pattern) in patterns.iter().enumerate().skip(start) {
+		match pattern.angle_signature().as_str() {
+			INTROSPECTION => depth += 1,
+			RETROSPECTION => depth -= 1,
+			_ => {}
+		}
+
+		if depth == 0 {
+			return Ok(i)
+		}
+	}
+
+	Err(EffectError::UnbalancedBlock { pattern_index: start })
+}
+
+/// Returns the range of patterns inside each Introspection block (not including the Introspection and Retrospection),
+/// in the order the blocks start. Blocks nested inside other blocks are included.
+pub fn introspection_blocks(patterns: &[HexPattern]) -> Result<Vec<Range<usize>>, EffectError> {
+	let mut blocks = vec![];
+	let mut open = 0;
+
+	for (i, pattern) in patterns.iter().enumerate() {
+		match pattern.angle_signature().as_str() {
+			INTROSPECTION => {
+				blocks.push(i + 1..block_end(patterns, i)?);
+				open += 1;
+			},
+			RETROSPECTION if open == 0 => return Err(EffectError::UnbalancedBlock { pattern_index: i }),
+			RETROSPECTION => open -= 1,
+			_ => {}
+		}
+	}
+
+	Ok(blocks)
+}
+
+/// Where an iota on the stack came from during inference.
+#[derive(Clone, Copy)]
+enum Slot {
+	/// One of the inputs, by the order they were taken from below the stack.
+	Input(usize),
+	/// Pushed by a pattern.
+	Pushed(TypeSet)
+}
+
+/// Infers the stack effect of casting the patterns in `range`, using each action's signature. Blocks inside the range
+/// are pushed as lists.
+pub fn infer(patterns: &[HexPattern], range: Range<usize>) -> Result<StackEffect, EffectError> {
+	// inputs in the order they are taken, so each is deeper than the ones before it.
+	let mut inputs: Vec<TypeSet> = vec![];
+	let mut stack: Vec<Slot> = vec![];
+	let mut i = range.start;
+
+	while i < range.end {
+		match patterns[i].angle_signature().as_str() {
+			INTROSPECTION => {
+				let end = block_end(patterns, i)?;
+				if end >= range.end {
+					return Err(EffectError::UnbalancedBlock { pattern_index: i })
+				}
+				stack.push(Slot::Pushed(TypeSet::of(&[IotaType::List])));
+				i = end + 1;
+				continue
+			},
+			RETROSPECTION => return Err(EffectError::UnbalancedBlock { pattern_index: i }),
+			_ => {}
+		}
+
+		let action = action_for(&patterns[i]).ok_or(EffectError::UnknownPattern { pattern_index: i })?;
+		let signature = action.signature().ok_or(EffectError::Dynamic { pattern_index: i })?;
+		let len = signature.inputs.len();
+
+		while stack.len() < len {
+			inputs.push(TypeSet::any());
+			stack.insert(0, Slot::Input(inputs.len() - 1));
+		}
+
+		let args = stack.split_off(stack.len() - len);
+
+		for (j, (slot, expected)) in args.iter().zip(signature.inputs.iter()).enumerate() {
+			let expected = TypeSet::of(expected);
+			let found = match slot {
+				Slot::Input(input) => inputs[*input],
+				Slot::Pushed(types) => *types,
+			};
+
+			let narrowed = found.intersect(&expected);
+			if narrowed.is_empty() {
+				return Err(EffectError::Mismatch { pattern_index: i, argument: len - 1 - j, expected, found })
+			}
+			if let Slot::Input(input) = slot {
+				inputs[*input] = narrowed;
+			}
+		}
+
+		stack.extend(signature.outputs.iter().map(|output| match output {
+			OutputType::Types(types) => Slot::Pushed(TypeSet::of(types)),
+			OutputType::Input(j) => args[*j],
+		}));
+
+		i += 1;
+	}
+
+	let outputs = stack.iter().map(|slot| match slot {
+		Slot::Input(input) => inputs[*input],
+		Slot::Pushed(types) => *types,
+	}).collect();
+	inputs.reverse();
+
+	Ok(StackEffect { inputs, outputs })
+}
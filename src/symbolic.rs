@@ -0,0 +1,242 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::rc::Rc;
+
+use crate::numbers;
+use crate::simulator::hash_f64;
+
+/// An operation in a symbolic expression. The arithmetic operations are known to the simplifier and displayed
+/// the way they'd be written by hand; any other action is displayed as a call to its name.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+	Add,
+	Sub,
+	Mul,
+	Div,
+	Pow,
+	Mod,
+	Abs,
+	Floor,
+	Ceil,
+	/// Combines three doubles into a vector.
+	Vec,
+	/// Indexes into a list.
+	Index,
+	Call(String)
+}
+
+/// The expression a value was computed from, in terms of the named unknown inputs of a hex. Subexpressions shared
+/// through an `Rc` are written out in full each time they are serialized.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expr {
+	/// A named unknown input.
+	Var(String),
+	Const(#[serde(with = "crate::serialization::float")] f64),
+	/// Any other known iota, stored as it's displayed.
+	Literal(String),
+	Apply(Op, Vec<Rc<Expr>>),
+	/// One of the outputs of an action that pushes more than one iota.
+	Output(Rc<Expr>, usize)
+}
+
+impl Eq for Expr {}
+
+impl Hash for Expr {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		std::mem::discriminant(self).hash(state);
+		match self {
+			Expr::Var(name) => name.hash(state),
+			Expr::Const(c) => hash_f64(*c, state),
+			Expr::Literal(literal) => literal.hash(state),
+			Expr::Apply(op, args) => { op.hash(state); args.hash(state) },
+			Expr::Output(call, index) => { call.hash(state); index.hash(state) },
+		}
+	}
+}
+
+impl Expr {
+	pub fn var(name: &str) -> Rc<Expr> { Rc::new(Expr::Var(name.to_string())) }
+
+	pub fn apply(op: Op, args: Vec<Rc<Expr>>) -> Rc<Expr> { Rc::new(Expr::Apply(op, args)) }
+
+	fn as_const(&self) -> Option<f64> {
+		if let Expr::Const(c) = self { Some(*c) } else { None }
+	}
+
+	/// Returns an equivalent expression with constants folded and trivial operations (adding 0, multiplying by 1, etc.) removed.
+	pub fn simplify(self: &Rc<Expr>) -> Rc<Expr> {
+		match &**self {
+			Expr::Apply(op, args) => {
+				let args: Vec<Rc<Expr>> = args.iter().map(|arg| arg.simplify()).collect();
+				simplify_apply(op, args)
+			},
+			Expr::Output(call, index) => {
+				let call = call.simplify();
+				if let Expr::Apply(Op::Call(name), args) = &*call {
+					if name == "deconstruct_vec" && args.len() == 1 {
+						if let Expr::Apply(Op::Vec, components) = &*args[0] {
+							return components[*index].clone()
+						}
+					}
+				}
+				Rc::new(Expr::Output(call, *index))
+			},
+			_ => self.clone()
+		}
+	}
+
+	fn precedence(&self) -> u8 {
+		match self {
+			Expr::Apply(Op::Add, _) | Expr::Apply(Op::Sub, _) => 1,
+			Expr::Apply(Op::Mul, _) | Expr::Apply(Op::Div, _) | Expr::Apply(Op::Mod, _) => 2,
+			Expr::Apply(Op::Pow, _) => 3,
+			Expr::Const(c) if *c < 0.0 => 1,
+			_ => 4
+		}
+	}
+}
+
+fn simplify_apply(op: &Op, args: Vec<Rc<Expr>>) -> Rc<Expr> {
+	let consts: Option<Vec<f64>> = args.iter().map(|arg| arg.as_const()).collect();
+
+	if let Some(consts) = consts {
+		let folded = match (op, consts.as_slice()) {
+			(Op::Add, [c0, c1]) => Some(c0 + c1),
+			(Op::Sub, [c0, c1]) => Some(c0 - c1),
+			(Op::Mul, [c0, c1]) => Some(c0 * c1),
+			(Op::Div, [c0, c1]) if *c1 != 0.0 => Some(c0 / c1),
+			(Op::Pow, [c0, c1]) => Some(c0.powf(*c1)),
+			(Op::Mod, [c0, c1]) if *c1 != 0.0 => Some(numbers::modulo(*c0, *c1)),
+			(Op::Abs, [c]) => Some(c.abs()),
+			(Op::Floor, [c]) => Some(numbers::floor(*c)),
+			(Op::Ceil, [c]) => Some(numbers::ceil(*c)),
+			_ => None
+		};
+
+		if let Some(folded) = folded {
+			return Rc::new(Expr::Const(folded))
+		}
+	}
+
+	let lhs = args.first().and_then(|arg| arg.as_const());
+	let rhs = args.get(1).and_then(|arg| arg.as_const());
+
+	match (op, lhs, rhs) {
+		(Op::Add, Some(0.0), _) => args[1].clone(),
+		(Op::Add, _, Some(c)) | (Op::Sub, _, Some(c)) if c == 0.0 => args[0].clone(),
+		(Op::Mul, Some(1.0), _) => args[1].clone(),
+		(Op::Mul, _, Some(c)) | (Op::Div, _, Some(c)) | (Op::Pow, _, Some(c)) if c == 1.0 => args[0].clone(),
+		(Op::Mul, Some(c), _) | (Op::Mul, _, Some(c)) if c == 0.0 => Rc::new(Expr::Const(0.0)),
+		(Op::Pow, _, Some(0.0)) => Rc::new(Expr::Const(1.0)),
+		(Op::Sub, _, _) if args[0] == args[1] => Rc::new(Expr::Const(0.0)),
+		(Op::Floor, _, _) | (Op::Ceil, _, _) if matches!(&*args[0], Expr::Apply(Op::Floor, _) | Expr::Apply(Op::Ceil, _)) => args[0].clone(),
+		(Op::Abs, _, _) if matches!(&*args[0], Expr::Apply(Op::Abs, _)) => args[0].clone(),
+		_ => Rc::new(Expr::Apply(op.clone(), args))
+	}
+}
+
+/// Writes the argument, wrapped in brackets if it binds less tightly than the operator it's an argument of.
+fn write_arg(f: &mut fmt::Formatter<'_>, arg: &Expr, min_precedence: u8) -> fmt::Result {
+	if arg.precedence() < min_precedence {
+		write!(f, "({})", arg)
+	} else {
+		write!(f, "{}", arg)
+	}
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, args: &[Rc<Expr>]) -> fmt::Result {
+	for (i, arg) in args.iter().enumerate() {
+		if i > 0 {
+			write!(f, ", ")?;
+		}
+		write!(f, "{}", arg)?;
+	}
+	Ok(())
+}
+
+impl fmt::Display for Expr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Expr::Var(name) => write!(f, "{}", name),
+			Expr::Const(c) => write!(f, "{}", c),
+			Expr::Literal(literal) => write!(f, "{}", literal),
+			Expr::Apply(op, args) => {
+				let infix = match op {
+					Op::Add => Some(" + "),
+					Op::Sub => Some(" - "),
+					Op::Mul => Some(" * "),
+					Op::Div => Some(" / "),
+					Op::Mod => Some(" % "),
+					Op::Pow => Some("^"),
+					_ => None
+				};
+
+				match (op, infix, args.as_slice()) {
+					(Op::Pow, Some(infix), [lhs, rhs]) => {
+						// ^ is right associative, so the left side needs brackets if it's another power.
+						write_arg(f, lhs, self.precedence() + 1)?;
+						write!(f, "{}", infix)?;
+						write_arg(f, rhs, self.precedence())
+					},
+					(_, Some(infix), [lhs, rhs]) => {
+						write_arg(f, lhs, self.precedence())?;
+						write!(f, "{}", infix)?;
+						write_arg(f, rhs, self.precedence() + 1)
+					},
+					(Op::Abs, _, [arg]) => write!(f, "|{}|", arg),
+					(Op::Vec, _, _) => {
+						write!(f, "(")?;
+						write_list(f, args)?;
+						write!(f, ")")
+					},
+					(Op::Index, _, [list, index]) => {
+						write_arg(f, list, 4)?;
+						write!(f, "[{}]", index)
+					},
+					_ => {
+						let name = match op {
+							Op::Floor => "floor",
+							Op::Ceil => "ceil",
+							Op::Call(name) => name.as_str(),
+							_ => "?"
+						};
+						write!(f, "{}(", name)?;
+						write_list(f, args)?;
+						write!(f, ")")
+					}
+				}
+			},
+			Expr::Output(call, index) => write!(f, "{}.{}", call, index),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn display_with_brackets() {
+		let x = Expr::var("x");
+		let y = Expr::var("y");
+		let sum = Expr::apply(Op::Add, vec![x.clone(), y]);
+		let product = Expr::apply(Op::Mul, vec![sum, Rc::new(Expr::Const(2.0))]);
+		let difference = Expr::apply(Op::Sub, vec![x.clone(), Expr::apply(Op::Sub, vec![x, Rc::new(Expr::Const(1.0))])]);
+
+		assert_eq!(product.to_string(), "(x + y) * 2");
+		assert_eq!(difference.to_string(), "x - (x - 1)");
+	}
+
+	#[test]
+	fn simplify_identities() {
+		let x = Expr::var("x");
+		let expr = Expr::apply(Op::Add, vec![
+			Expr::apply(Op::Mul, vec![x.clone(), Rc::new(Expr::Const(1.0))]),
+			Expr::apply(Op::Add, vec![Rc::new(Expr::Const(2.0)), Rc::new(Expr::Const(-2.0))])
+		]);
+
+		assert_eq!(expr.simplify(), x);
+	}
+}
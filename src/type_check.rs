@@ -0,0 +1,56 @@
+use crate::actions::registry::hex_actions;
+use crate::debugger::cast_frames;
+use crate::hex_pattern::HexPattern;
+use crate::simulator::{ActionError, IotaType, Mishap, StackHolder, StackManager, StackState};
+
+/// Whether the error is one the checker reports: a type error, or a pattern that doesn't exist in the version being
+/// simulated. Mishaps that depend on values (like dividing by zero) aren't reported.
+fn is_type_error(error: &ActionError) -> bool {
+	matches!(error, ActionError::InvalidType { .. } | ActionError::StackTooSmall { .. } | ActionError::InvalidPattern { .. })
+}
+
+/// Whether the mishap only happened because of an earlier one on the branch: its argument was the Garbage an earlier
+/// mishap left behind, or something unknown. Those aren't reported, so the root cause isn't buried under its effects.
+fn follows_earlier_mishap(state: &StackState, mishap: &Mishap) -> bool {
+	state.mishaps().len() > 1 && matches!(mishap.error, ActionError::InvalidType { found: IotaType::Garbage | IotaType::Unknown, .. })
+}
+
+/// Casts the hex from an empty stack and returns the mishaps at each pattern where every branch that reaches it would
+/// mishap with an invalid type or too small a stack, or at patterns that don't exist in the selected version (see
+/// [`crate::version`]). Branches carry on after a mishap the way the game does, so later patterns are still checked
+/// (see [`follows_earlier_mishap`] for the mishaps that aren't reported then), and any frames a pattern pushes (see [`crate::simulator::Frame`]) are cast before the next pattern is checked.
+/// Checking stops at the first pattern the simulator doesn't know.
+pub fn check(patterns: &[HexPattern]) -> Vec<Mishap> {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::default()));
+	let mut diagnostics: Vec<Mishap> = vec![];
+
+	for (i, action) in hex_actions(patterns) {
+		let action = match action {
+			Some(action) => action,
+			None => break,
+		};
+
+		stack_manager.apply_action_at(action, i);
+		cast_frames(&mut stack_manager, i);
+
+		let mut mishaps = vec![];
+		let mut every_branch = true;
+
+		for state in stack_manager.stacks().iter().filter_map(|result| result.as_ref().ok()) {
+			match state.mishaps().last() {
+				Some(mishap) if mishap.pattern_index == i && is_type_error(&mishap.error) => {
+					if !follows_earlier_mishap(state, mishap) && !mishaps.contains(mishap) {
+						mishaps.push(mishap.clone());
+					}
+				},
+				_ => every_branch = false,
+			}
+		}
+
+		if every_branch {
+			diagnostics.append(&mut mishaps);
+		}
+	}
+
+	diagnostics
+}
@@ -0,0 +1,147 @@
+//! The Hex Casting releases the simulator can be set to. Patterns, costs and mishap rules changed between releases, so
+//! the simulator casts hexes the way the selected version's [`Profile`] says, and reports patterns that version doesn't
+//! have. The profile is selected per thread, like the addon registry (see [`crate::addons`]).
+//!
+//! Every difference a profile records names where it is documented (e.g. a changelog entry), so that the tables can be
+//! checked against the game. The built-in profiles (see [`Profile::builtin`]) only record differences for the patterns
+//! the simulator casts; servers running other releases, or with patches of their own, can load a profile from a file
+//! (see [`Profile::load`]).
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::Path;
+use std::rc::Rc;
+
+use crate::interval::Interval;
+use crate::serialization::{from_json, FormatError};
+
+/// A series of Hex Casting releases that behave the same as far as the simulator is concerned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+pub enum GameVersion {
+	/// 0.10.x, for Minecraft 1.19.2.
+	#[serde(rename = "0.10")]
+	V0_10,
+	/// 0.11.x, for Minecraft 1.20.1.
+	#[serde(rename = "0.11")]
+	V0_11
+}
+
+impl Default for GameVersion {
+	fn default() -> Self { GameVersion::LATEST }
+}
+
+impl fmt::Display for GameVersion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			GameVersion::V0_10 => write!(f, "0.10.x"),
+			GameVersion::V0_11 => write!(f, "0.11.x"),
+		}
+	}
+}
+
+impl GameVersion {
+	pub const ALL: [GameVersion; 2] = [GameVersion::V0_10, GameVersion::V0_11];
+	pub const LATEST: GameVersion = GameVersion::V0_11;
+}
+
+/// A pattern a version doesn't have.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PatternChange {
+	/// the pattern's angle signature, e.g. "waaw".
+	pub signature: String,
+	/// where the difference is documented.
+	pub source: String
+}
+
+/// An action whose cost in a version differs from the one the simulator gives it.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CostChange {
+	/// the action's name (see [`crate::simulator::Action::name`]), e.g. "create_water".
+	pub action: String,
+	/// the action's cost in the version, in media units (see [`crate::media`]).
+	pub cost: f64,
+	/// where the difference is documented.
+	pub source: String
+}
+
+/// How a version of Hex Casting differs from what the simulator does by default.
+///
+/// Written in JSON as e.g. `{"version": "0.10", "missing_patterns": [{"signature": "qwaqde", "source": "..."}], "costs":
+/// [{"action": "create_water", "cost": 10000, "source": "..."}], "positive_fisherman": "..."}`.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Profile {
+	/// the version the profile describes, which mishaps are reported against.
+	pub version: GameVersion,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub missing_patterns: Vec<PatternChange>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub costs: Vec<CostChange>,
+	/// where it is documented that Fisherman's Gambit doesn't accept negative numbers in the version, if it doesn't.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub positive_fisherman: Option<String>
+}
+
+/// Where the differences between 0.10.x and 0.11.x are documented.
+const CHANGELOG_0_11: &str = "Hex Casting 0.11.0 changelog (CHANGELOG.md in the HexMod repository)";
+
+impl Profile {
+	/// The profile hexedit ships for the version. The simulator casts hexes as 0.11.x does, so only older versions
+	/// record differences.
+	pub fn builtin(version: GameVersion) -> Profile {
+		match version {
+			GameVersion::V0_10 => Profile {
+				version,
+				missing_patterns: vec![
+					// Iris' Gambit
+					PatternChange { signature: "qwaqde".to_string(), source: CHANGELOG_0_11.to_string() },
+					// Thanatos' Reflection
+					PatternChange { signature: "qqaed".to_string(), source: CHANGELOG_0_11.to_string() },
+				],
+				costs: vec![],
+				positive_fisherman: Some(CHANGELOG_0_11.to_string())
+			},
+			GameVersion::V0_11 => Profile { version, missing_patterns: vec![], costs: vec![], positive_fisherman: None },
+		}
+	}
+
+	/// Reads a profile from a file written in the versioned JSON format of [`crate::serialization`].
+	pub fn load(path: &Path) -> Result<Profile, FormatError> {
+		let json = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+		from_json(&json)
+	}
+
+	/// Whether Hex Casting itself has the pattern with the angle signature in this version. Patterns the simulator
+	/// doesn't know are assumed to be in every version.
+	pub fn has_pattern(&self, signature: &str) -> bool {
+		!self.missing_patterns.iter().any(|missing| missing.signature == signature)
+	}
+
+	/// The action's cost in this version, given the cost the simulator gives it.
+	pub fn cost(&self, action: &str, default: Interval) -> Interval {
+		self.costs.iter().find(|change| change.action == action).map_or(default, |change| Interval::point(change.cost))
+	}
+
+	/// Whether Fisherman's Gambit takes negative numbers, moving the top iota down the stack.
+	pub fn negative_fisherman(&self) -> bool { self.positive_fisherman.is_none() }
+}
+
+impl Default for Profile {
+	fn default() -> Self { Profile::builtin(GameVersion::default()) }
+}
+
+thread_local! {
+	/// The profile every part of hexedit simulates.
+	static PROFILE: RefCell<Rc<Profile>> = RefCell::new(Rc::new(Profile::default()));
+}
+
+/// The profile hexes are simulated with on this thread.
+pub fn profile() -> Rc<Profile> { PROFILE.with(|profile| profile.borrow().clone()) }
+
+/// Selects the profile hexes are simulated with on this thread.
+pub fn set_profile(profile: Profile) { PROFILE.with(|current| *current.borrow_mut() = Rc::new(profile)) }
+
+/// The version hexes are simulated in on this thread.
+pub fn game_version() -> GameVersion { profile().version }
+
+/// Selects the built-in profile of the version (see [`Profile::builtin`]) on this thread.
+pub fn set_game_version(version: GameVersion) { set_profile(Profile::builtin(version)) }
@@ -0,0 +1,456 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use itertools::Either::{Left, Right};
+
+use crate::akashic::Library;
+use crate::items::{Item, ItemKind};
+use crate::numbers::{floor, normalize};
+use crate::serialization::{from_json, FormatError};
+use crate::simulator::{EntityType, Iota, IotaEntity};
+
+/// How far from the caster's eyes a staff or trinket can affect, in blocks.
+pub const AMBIT_RADIUS: f64 = 32.0;
+/// How far from a greater sentinel a staff or trinket can affect, in blocks.
+pub const SENTINEL_RADIUS: f64 = 16.0;
+/// How far Archer's and Architect's Distillations look for a block, in blocks.
+pub const RAYCAST_DISTANCE: f64 = 32.0;
+
+/// The world a hex is cast in, as far as the simulator knows it. Entity actions read the properties of entities from
+/// the scene; any entity or property that isn't in the scene is unknown.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Scene {
+	pub entities: Vec<SceneEntity>,
+	/// who is casting the hex, and how; if there is no caster, targets aren't checked against the ambit.
+	pub caster: Option<Caster>,
+	/// the blocks around the caster, which raycasts look through and block spells change.
+	#[serde(skip_serializing_if = "Blocks::is_unknown")]
+	pub blocks: Blocks,
+	/// the Akashic Library the hex can read and write, or None if nothing is known about one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub library: Option<Library>
+}
+
+/// How the hex is cast, which decides the caster's ambit: the area spells and entity actions can target.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Casting {
+	/// Cast from a staff; the ambit is [`AMBIT_RADIUS`] around the caster's eyes and [`SENTINEL_RADIUS`] around their
+	/// greater sentinel.
+	Staff,
+	/// Cast from a trinket, cypher or artifact (see [`Caster::casting_item`]); the ambit is the same as a staff's, since
+	/// the hex is still cast by the player holding it.
+	Trinket,
+	/// Cast by a spell circle; the ambit is the box the circle encloses, from its `min` corner up to (but not including)
+	/// its `max` corner. Sentinels don't extend it.
+	Circle { min: (f64, f64, f64), max: (f64, f64, f64) }
+}
+
+impl Default for Casting {
+	fn default() -> Self { Casting::Staff }
+}
+
+/// The caster of the hex.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct Caster {
+	/// the position of the caster's eyes.
+	pub position: (f64, f64, f64),
+	/// the position of the caster's greater sentinel, if they have one.
+	pub sentinel: Option<(f64, f64, f64)>,
+	pub casting: Casting,
+	/// the block Place Block places, from the caster's inventory, or None if it isn't known.
+	pub held_block: Option<String>,
+	/// the item in the caster's other hand, which Scribe's Reflection and Scribe's Gambit read and write, or None if it isn't
+	/// known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub held_item: Option<Item>,
+	/// the trinket, cypher or artifact the hex is cast from, when casting from one.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub casting_item: Option<Item>,
+	/// the media the caster can spend from their inventory, or the impetus's media when cast by a circle, in media units
+	/// (see [`crate::media`]); None if it isn't known. Overcasting from health isn't simulated.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub media: Option<f64>
+}
+
+fn distance_squared(v0: (f64, f64, f64), v1: (f64, f64, f64)) -> f64 {
+	(v0.0 - v1.0).powi(2) + (v0.1 - v1.1).powi(2) + (v0.2 - v1.2).powi(2)
+}
+
+impl Caster {
+	/// The most media the hex can spend, or None if that isn't known. Items spend their own media; an artifact spends the
+	/// caster's once its own runs out.
+	pub fn available_media(&self) -> Option<f64> {
+		match self.casting {
+			Casting::Trinket => {
+				let item = self.casting_item.as_ref()?;
+				let charge = item.media?.media;
+				match item.kind {
+					ItemKind::Artifact => self.media.map(|media| charge + media),
+					_ => Some(charge),
+				}
+			},
+			Casting::Staff | Casting::Circle { .. } => self.media,
+		}
+	}
+
+	/// Whether the position is in the caster's ambit.
+	pub fn in_ambit(&self, position: (f64, f64, f64)) -> bool {
+		match self.casting {
+			Casting::Circle { min, max } => {
+				(min.0..max.0).contains(&position.0) && (min.1..max.1).contains(&position.1) && (min.2..max.2).contains(&position.2)
+			},
+			Casting::Staff | Casting::Trinket => {
+				distance_squared(position, self.position) < AMBIT_RADIUS * AMBIT_RADIUS ||
+					self.sentinel.map_or(false, |sentinel| distance_squared(position, sentinel) < SENTINEL_RADIUS * SENTINEL_RADIUS)
+			},
+		}
+	}
+}
+
+/// An entity in a scene. Vectors are (x, y, z), written in the scene file as `[x, y, z]`.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct SceneEntity {
+	pub name: String,
+	/// the uuid entity iotas refer to the entity by; the name is used if there is no uuid.
+	pub uuid: Option<String>,
+	/// the position of the entity's eyes, which is what Compass' Purification returns.
+	pub position: Option<(f64, f64, f64)>,
+	/// the unit vector the entity is looking along.
+	pub look: Option<(f64, f64, f64)>,
+	/// in blocks per tick.
+	pub velocity: Option<(f64, f64, f64)>,
+	pub height: Option<f64>,
+	pub health: Option<f64>,
+	/// every type the entity is, or None if they aren't known. Animals, monsters and players are living whether or
+	/// not that is listed.
+	pub types: Option<Vec<EntityType>>
+}
+
+impl SceneEntity {
+	pub fn uuid(&self) -> &str { self.uuid.as_deref().unwrap_or(&self.name) }
+
+	/// The entity as an entity iota would refer to it, with its types known if they are in the scene.
+	pub fn entity(&self) -> IotaEntity {
+		let mut builder = IotaEntity::new(&self.name);
+		builder.uuid(self.uuid());
+
+		if let Some(types) = &self.types {
+			for entity_type in EntityType::ALL {
+				let is = types.contains(&entity_type) || (entity_type == EntityType::Living && types.iter().any(EntityType::is_living));
+				if is {
+					builder.add_guaranteed(entity_type);
+				} else {
+					builder.remove_possible(entity_type);
+				}
+			}
+		}
+
+		(&mut builder).into()
+	}
+
+	pub fn iota(&self) -> Iota { Iota::Entity(self.entity()) }
+}
+
+impl Scene {
+	/// Reads a scene from a file written in the versioned JSON format of [`crate::serialization`].
+	pub fn load(path: &Path) -> Result<Scene, FormatError> {
+		let json = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+		from_json(&json)
+	}
+
+	/// Returns whether the target (a vector or an entity) is in the caster's ambit, or None if that isn't known. Without a
+	/// caster, and for iotas that can't be targets, there is nothing to check, so they are in the ambit.
+	pub fn in_ambit(&self, target: &Iota) -> Option<bool> {
+		let caster = match &self.caster {
+			Some(caster) => caster,
+			None => return Some(true),
+		};
+
+		match target.value() {
+			Iota::Vec(Left(position)) => Some(caster.in_ambit(*position)),
+			Iota::Vec(Right(true)) => Some(true),
+			Iota::Vec(Right(false)) => None,
+			Iota::Entity(entity) => match self.entity(entity).and_then(|scene_entity| scene_entity.position) {
+				Some(position) => Some(caster.in_ambit(position)),
+				None if entity.is_guaranteed_in_range() => Some(true),
+				None => None,
+			},
+			_ => Some(true),
+		}
+	}
+
+	/// Returns the entity the entity iota refers to, if it is in the scene.
+	pub fn entity(&self, entity: &IotaEntity) -> Option<&SceneEntity> {
+		self.entities.iter().find(|scene_entity| scene_entity.uuid() == entity.uuid())
+	}
+}
+
+/// The position of a block, as (x, y, z).
+pub type BlockPos = (i64, i64, i64);
+
+/// The block a position is in, the way Hex Casting rounds vectors to blocks.
+pub fn block_pos(position: (f64, f64, f64)) -> BlockPos {
+	(floor(position.0) as i64, floor(position.1) as i64, floor(position.2) as i64)
+}
+
+/// Blocks with no collision box, which raycasts pass through; every other block is treated as a full cube.
+const PASSABLE: &[&str] = &[
+	"air", "cave_air", "void_air", "water", "lava", "grass", "tall_grass", "fern", "large_fern", "dead_bush", "torch",
+	"wall_torch", "conjured_light"
+];
+/// Blocks that blocks can be placed in without breaking them first.
+const REPLACEABLE: &[&str] = &["air", "cave_air", "void_air", "water", "lava", "grass", "tall_grass", "fern", "large_fern", "dead_bush"];
+/// Blocks Break Block can't break.
+const UNBREAKABLE: &[&str] = &[
+	"bedrock", "barrier", "end_portal_frame", "end_portal", "command_block", "structure_block", "jigsaw", "reinforced_deepslate"
+];
+
+/// The name of a block without the `minecraft:` namespace, which is how blocks are named in a [`Blocks`].
+fn block_id(block: &str) -> &str { block.strip_prefix("minecraft:").unwrap_or(block) }
+
+pub fn is_air(block: &str) -> bool { matches!(block_id(block), "air" | "cave_air" | "void_air") }
+
+pub fn is_fluid(block: &str) -> bool { matches!(block_id(block), "water" | "lava") }
+
+pub fn is_passable(block: &str) -> bool { PASSABLE.contains(&block_id(block)) }
+
+pub fn is_replaceable(block: &str) -> bool { REPLACEABLE.contains(&block_id(block)) }
+
+pub fn is_breakable(block: &str) -> bool { !is_air(block) && !is_fluid(block) && !UNBREAKABLE.contains(&block_id(block)) }
+
+/// The blocks in a scene, by position. Blocks that aren't listed are `default`; a block that isn't known is None.
+///
+/// Written in JSON as `{"default": "air", "blocks": [{"at": [0, 64, 0], "block": "stone"}]}`, or in the text format
+/// read by [`Blocks::from_text`].
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(from = "BlocksRepr", into = "BlocksRepr")]
+pub struct Blocks {
+	blocks: BTreeMap<BlockPos, Option<String>>,
+	/// the block at every position that isn't listed, or None if they aren't known.
+	pub default: Option<String>
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BlocksRepr {
+	#[serde(default)]
+	default: Option<String>,
+	#[serde(default)]
+	blocks: Vec<BlockRepr>
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct BlockRepr {
+	at: BlockPos,
+	block: Option<String>
+}
+
+impl From<BlocksRepr> for Blocks {
+	fn from(repr: BlocksRepr) -> Self {
+		let mut blocks = Blocks { blocks: BTreeMap::new(), default: repr.default };
+		for block in repr.blocks {
+			blocks.set(block.at, block.block);
+		}
+		blocks
+	}
+}
+
+impl From<Blocks> for BlocksRepr {
+	fn from(blocks: Blocks) -> Self {
+		BlocksRepr {
+			default: blocks.default,
+			blocks: blocks.blocks.into_iter().map(|(at, block)| BlockRepr { at, block }).collect()
+		}
+	}
+}
+
+/// What a raycast found.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RaycastHit {
+	/// The ray hit the block through the face with this normal.
+	Hit { block: BlockPos, normal: (f64, f64, f64) },
+	/// The ray didn't hit a block within range.
+	Miss,
+	/// The ray passed through blocks that aren't known before it hit a known block; if `can_miss` is true, it didn't
+	/// hit a known block at all.
+	Unknown { can_miss: bool }
+}
+
+/// The largest box the text format can fill with one line, so a typo can't use up all the memory.
+const MAX_FILL: i64 = 1 << 20;
+
+impl Blocks {
+	/// Blocks where every block is the same, e.g. all air.
+	pub fn filled(block: &str) -> Blocks { Blocks { blocks: BTreeMap::new(), default: Some(block_id(block).to_string()) } }
+
+	/// Whether nothing at all is known about the blocks.
+	pub fn is_unknown(&self) -> bool { self.default.is_none() && self.blocks.values().all(Option::is_none) }
+
+	/// The block at the position, or None if it isn't known.
+	pub fn get(&self, pos: BlockPos) -> Option<&str> {
+		self.blocks.get(&pos).unwrap_or(&self.default).as_deref()
+	}
+
+	/// Sets the block at the position, with None for a block that isn't known.
+	pub fn set(&mut self, pos: BlockPos, block: Option<String>) {
+		self.blocks.insert(pos, block.map(|block| block_id(&block).to_string()));
+	}
+
+	/// Every position that is listed, rather than being the default, in order.
+	pub fn positions(&self) -> impl Iterator<Item = BlockPos> + '_ { self.blocks.keys().copied() }
+
+	/// Forgets every block (including the default) that `changed` is true for, for when a spell could have changed any
+	/// of them.
+	pub fn forget(&mut self, changed: impl Fn(&str) -> bool) {
+		for block in self.blocks.values_mut() {
+			if block.as_deref().map_or(false, &changed) {
+				*block = None;
+			}
+		}
+
+		if self.default.as_deref().map_or(false, &changed) {
+			self.default = None;
+		}
+	}
+
+	/// The listed positions where the blocks differ from `before`.
+	pub fn changes(&self, before: &Blocks) -> Vec<BlockPos> {
+		let mut positions: Vec<BlockPos> = self.positions().chain(before.positions()).filter(|pos| self.get(*pos) != before.get(*pos)).collect();
+		positions.sort_unstable();
+		positions.dedup();
+		positions
+	}
+
+	/// Looks along the direction from the origin for the first block a raycast would hit, up to `distance` blocks away.
+	/// Fluids and blocks without a collision box are passed through.
+	pub fn raycast(&self, origin: (f64, f64, f64), direction: (f64, f64, f64), distance: f64) -> RaycastHit {
+		let direction = match normalize(direction) {
+			(x, y, z) if x == 0.0 && y == 0.0 && z == 0.0 => return RaycastHit::Miss,
+			(x, y, z) => [x, y, z],
+		};
+		let origin = [origin.0, origin.1, origin.2];
+		let (x, y, z) = block_pos((origin[0], origin[1], origin[2]));
+		let mut block = [x, y, z];
+
+		let step: Vec<i64> = direction.iter().map(|d| if *d > 0.0 { 1 } else if *d < 0.0 { -1 } else { 0 }).collect();
+		let mut t_max: Vec<f64> = (0..3).map(|axis| match step[axis] {
+			1 => (block[axis] as f64 + 1.0 - origin[axis]) / direction[axis],
+			-1 => (block[axis] as f64 - origin[axis]) / direction[axis],
+			_ => f64::INFINITY,
+		}).collect();
+		let t_delta: Vec<f64> = direction.iter().map(|d| 1.0 / d.abs()).collect();
+
+		// a ray that starts inside a block hits it through the face it is heading away from.
+		let mut normal = nearest_direction(direction).map(|d| if d == 0.0 { 0.0 } else { -d });
+		let mut passed_unknown = false;
+
+		loop {
+			match self.get((block[0], block[1], block[2])) {
+				Some(found) if !is_passable(found) => {
+					return if passed_unknown {
+						RaycastHit::Unknown { can_miss: false }
+					} else {
+						RaycastHit::Hit { block: (block[0], block[1], block[2]), normal: (normal[0], normal[1], normal[2]) }
+					}
+				},
+				Some(_) => {},
+				None => passed_unknown = true,
+			}
+
+			let axis = (0..3).fold(0, |nearest, axis| if t_max[axis] < t_max[nearest] { axis } else { nearest });
+			if t_max[axis] > distance {
+				break
+			}
+
+			block[axis] += step[axis];
+			t_max[axis] += t_delta[axis];
+			normal = [0.0; 3];
+			normal[axis] = -step[axis] as f64;
+		}
+
+		if passed_unknown { RaycastHit::Unknown { can_miss: true } } else { RaycastHit::Miss }
+	}
+
+	/// Reads blocks from the text format, where each line is one of:
+	/// - `x y z block`, setting the block at a position;
+	/// - `x0 y0 z0 x1 y1 z1 block`, filling the box between two corners (inclusive) with a block;
+	/// - `default block`, setting the block at every position that isn't listed.
+	///
+	/// A block of `?` isn't known. Later lines replace earlier ones, and everything after a `#` is a comment.
+	pub fn from_text(text: &str) -> Result<Blocks, FormatError> {
+		let mut blocks = Blocks::default();
+
+		for (i, line) in text.lines().enumerate() {
+			let syntax_error = |message: &str| FormatError::Syntax { line: i + 1, message: message.to_string() };
+			let words: Vec<&str> = line.split('#').next().unwrap_or("").split_whitespace().collect();
+			let (block, coordinates) = match words.split_last() {
+				Some((block, coordinates)) => (if *block == "?" { None } else { Some(block.to_string()) }, coordinates),
+				None => continue,
+			};
+
+			if coordinates == ["default"] {
+				blocks.default = block.map(|block| block_id(&block).to_string());
+				continue
+			}
+
+			let coordinates: Vec<i64> = coordinates.iter()
+				.map(|coordinate| coordinate.parse().map_err(|_| syntax_error(&format!("\"{}\" isn't a whole number", coordinate))))
+				.collect::<Result<_, _>>()?;
+
+			match coordinates.as_slice() {
+				[x, y, z] => blocks.set((*x, *y, *z), block),
+				[x0, y0, z0, x1, y1, z1] => {
+					let (xs, ys, zs) = (*x0.min(x1)..=*x0.max(x1), *y0.min(y1)..=*y0.max(y1), *z0.min(z1)..=*z0.max(z1));
+					let volume = [&xs, &ys, &zs].iter().fold(1_i64, |volume, range| volume.saturating_mul(range.end() - range.start() + 1));
+					if volume > MAX_FILL {
+						return Err(syntax_error(&format!("can't fill more than {} blocks at once", MAX_FILL)))
+					}
+
+					for x in xs {
+						for y in ys.clone() {
+							for z in zs.clone() {
+								blocks.set((x, y, z), block.clone());
+							}
+						}
+					}
+				},
+				_ => return Err(syntax_error("expected \"x y z block\", \"x0 y0 z0 x1 y1 z1 block\" or \"default block\"")),
+			}
+		}
+
+		Ok(blocks)
+	}
+
+	/// Reads blocks from a file; `.json` files are read in the versioned JSON format of [`crate::serialization`], and any
+	/// other file in the text format of [`Blocks::from_text`].
+	pub fn load(path: &Path) -> Result<Blocks, FormatError> {
+		let contents = std::fs::read_to_string(path).map_err(|error| FormatError::Io(error.to_string()))?;
+
+		if path.extension().map_or(false, |extension| extension == "json") {
+			from_json(&contents)
+		} else {
+			Blocks::from_text(&contents)
+		}
+	}
+}
+
+/// The axis-aligned unit vector nearest to the direction, the way Minecraft picks it (checking down, up, north, south,
+/// west then east, keeping the first of any ties).
+fn nearest_direction(direction: [f64; 3]) -> [f64; 3] {
+	const DIRECTIONS: [[f64; 3]; 6] = [[0.0, -1.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, -1.0], [0.0, 0.0, 1.0], [-1.0, 0.0, 0.0], [1.0, 0.0, 0.0]];
+
+	let mut nearest = DIRECTIONS[2];
+	let mut most = f64::MIN_POSITIVE;
+	for candidate in DIRECTIONS {
+		let dot = candidate[0] * direction[0] + candidate[1] * direction[1] + candidate[2] * direction[2];
+		if dot > most {
+			most = dot;
+			nearest = candidate;
+		}
+	}
+
+	nearest
+}
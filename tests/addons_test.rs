@@ -0,0 +1,111 @@
+use hexedit::actions::registry::{action_for, pattern_name};
+use hexedit::addons::*;
+use hexedit::serialization::*;
+use hexedit::simulator::*;
+
+mod common;
+use common::pattern;
+
+/// Pushes a colour, for testing addon iotas.
+struct PushRed;
+
+impl ConstLenAction for PushRed {
+	fn len() -> usize { 0 }
+
+	fn apply(&self, _iotas: &[Iota]) -> Vec<Result<Vec<Iota>, ActionError>> {
+		vec![Ok(vec![Iota::Addon(AddonIota::new("test:colour", "red"))])]
+	}
+}
+
+struct Colour;
+
+impl IotaKind for Colour {
+	fn name(&self) -> &'static str { "test:colour" }
+
+	fn display(&self, data: &str) -> String { format!("Colour {}", data) }
+
+	fn parse(&self, text: &str) -> Option<String> { text.strip_prefix("colour ").map(str::to_string) }
+}
+
+struct TestAddon;
+
+impl Addon for TestAddon {
+	fn name(&self) -> &'static str { "test" }
+
+	fn register(&self, registry: &mut AddonRegistry) {
+		registry.pattern("wwaqqqqq", "push_red", || Box::new(PushRed));
+		// Hex Casting's own patterns take priority
+		registry.pattern("aqae", "push_red", || Box::new(PushRed));
+		registry.named_pattern("wwdeeeee", "push_blue");
+		registry.iota_kind(Colour);
+	}
+}
+
+#[test]
+fn registers_patterns_and_iotas() {
+	assert!(action_for(&pattern("wwaqqqqq")).is_none());
+	install(&TestAddon);
+	with_registry(|registry| assert!(registry.addons().contains(&"test")));
+
+	assert_eq!(pattern_name(&pattern("wwaqqqqq")), Some("push_red".to_string()));
+	assert_eq!(pattern_name(&pattern("wwdeeeee")), Some("push_blue".to_string()));
+	assert_eq!(pattern_name(&pattern("aqae")), Some("push_true".to_string()));
+	assert_eq!(pattern_name(&pattern("qqq")), Some("introspection".to_string()));
+	assert_eq!(pattern_name(&pattern("wwwwwwwwww")), None);
+	assert!(action_for(&pattern("wwdeeeee")).is_none());
+
+	let stacks = common::run(vec![], action_for(&pattern("wwaqqqqq")).unwrap());
+	assert_eq!(stacks, vec!["Colour red"]);
+	assert_eq!(common::run(vec![], action_for(&pattern("aqae")).unwrap()), vec!["True"]);
+}
+
+#[test]
+fn serializes_addon_iotas() {
+	install(&TestAddon);
+
+	let red = Iota::Addon(AddonIota::new("test:colour", "red"));
+	let json = to_json(&red);
+	assert!(from_json::<Iota>(&json).unwrap() == red, "{}", json);
+
+	let typed = r#"{"version": 1, "data": {"type": "addon", "kind": "test:colour", "text": "colour red"}}"#;
+	assert!(from_json::<Iota>(typed).unwrap() == red);
+	let mistyped = r#"{"version": 1, "data": {"type": "addon", "kind": "test:colour", "text": "red"}}"#;
+	assert!(from_json::<Iota>(mistyped).is_err());
+
+	// iotas of kinds that aren't registered keep their JSON
+	let unregistered = r#"{"version": 1, "data": {"type": "addon", "kind": "other:thing", "value": {"size": 3}}}"#;
+	let thing = from_json::<Iota>(unregistered).unwrap();
+	assert_eq!(thing.to_string(), r#"other:thing({"size":3})"#);
+	assert!(from_json::<Iota>(&to_json(&thing)).unwrap() == thing);
+}
+
+#[cfg(feature = "hexal")]
+#[test]
+fn hexal_patterns() {
+	assert_eq!(common::run(vec![5.0.into()], action_for(&pattern("wawdedwaw")).unwrap()), vec!["120"]);
+	assert_eq!(common::run(vec![(-1.0).into()], action_for(&pattern("wawdedwaw")).unwrap()), vec!["OutOfBounds { argument: 0 }"]);
+	let numbers: Iota = vec![1.0.into(), 2.0.into(), 3.0.into()].into();
+	assert_eq!(common::run(vec![numbers.clone()], action_for(&pattern("aea")).unwrap()), vec!["[1, 3, 6]"]);
+	assert_eq!(common::run(vec![numbers], action_for(&pattern("qaawaaq")).unwrap()), vec!["[1, 2, 6]"]);
+	assert_eq!(common::run(vec![1.0.into()], action_for(&pattern("awd")).unwrap()), vec!["Type: number"]);
+
+	let gate = r#"{"version": 1, "data": {"type": "addon", "kind": "hexal:gate", "value": 4}}"#;
+	let gate = from_json::<Iota>(gate).unwrap();
+	assert_eq!(gate.to_string(), "Gate 4");
+	assert!(to_json(&gate).contains(r#""value": 4"#));
+}
+
+#[cfg(feature = "moreiotas")]
+#[test]
+fn moreiotas_strings() {
+	let typed = r#"{"version": 1, "data": {"type": "addon", "kind": "moreiotas:string", "text": "\"hello\\nworld\""}}"#;
+	assert_eq!(from_json::<Iota>(typed).unwrap().to_string(), r#""hello\nworld""#);
+}
+
+#[cfg(feature = "moreiotas")]
+#[test]
+fn moreiotas_registers_no_unchecked_patterns() {
+	// signatures an earlier table guessed for Concatenation Distillation and a matrix determinant
+	assert_eq!(pattern_name(&pattern("waawaqwawqq")), None);
+	assert_eq!(with_registry(|registry| registry.pattern_for("aeeeeea").map(|pattern| pattern.addon)), None);
+}
@@ -0,0 +1,97 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use hexedit::actions::akashic::*;
+use hexedit::actions::registry::pattern_name;
+use hexedit::akashic::Library;
+use hexedit::hex_pattern::*;
+use hexedit::serialization::{from_json, to_json};
+use hexedit::simulator::*;
+use hexedit::world::Scene;
+
+mod common;
+use common::{describe, pattern, pattern_from, run_each_in};
+
+fn key() -> Iota { Iota::Pattern(pattern("qaq")) }
+
+fn scene(library: Library) -> Rc<Scene> { Rc::new(Scene { library: Some(library), ..Scene::default() }) }
+
+fn library_of(state: &StackState) -> &Library { state.scene().unwrap().library.as_ref().unwrap() }
+
+#[test]
+fn read_and_write() {
+	assert_eq!(pattern_name(&pattern_from(HexAbsoluteDir::West, "qqqwqqqqqaq")).as_deref(), Some("akashic_read"));
+	assert_eq!(pattern_name(&pattern("eeeweeeeede")).as_deref(), Some("akashic_write"));
+
+	let scene = scene(Library::default());
+	let written = run_each_in(&scene, vec![vec![(0.0, 0.0, 0.0).into(), key(), 4.0.into()]], Box::new(AkashicWrite));
+	assert_eq!(describe(&written[0]), "");
+	assert_eq!(library_of(&written[0]).version(), 1);
+
+	// keys are compared by their angle signatures, so the pattern can be drawn in any direction
+	let read_key = Iota::Pattern(pattern_from(HexAbsoluteDir::West, "qaq"));
+	let read = run_each_in(&written[0].scene().cloned().map(Rc::new).unwrap(), vec![vec![(0.0, 0.0, 0.0).into(), read_key]], Box::new(AkashicRead));
+	assert_eq!(describe(&read[0]), "4");
+
+	let missing = run_each_in(&scene, vec![vec![(0.0, 0.0, 0.0).into(), key()]], Box::new(AkashicRead));
+	assert_eq!(describe(&missing[0]), "Null");
+
+	let mut state = StackState::new(vec![(0.0, 0.0, 0.0).into(), key()], None);
+	state.set_scene(None);
+	let mut stack_manager = StackManager::new(StackHolder::single_state(state));
+	stack_manager.apply_action(Box::new(AkashicRead));
+	assert_eq!(stack_manager.stacks().iter().next().unwrap().as_ref().unwrap().to_string(), "UNKNOWN");
+}
+
+#[test]
+fn writes_are_versioned_per_branch() {
+	let mut before = Library::default();
+	before.write(pattern("qaq"), 1.0.into());
+	let scene = scene(before);
+
+	let branches = run_each_in(&scene, vec![
+		vec![(0.0, 0.0, 0.0).into(), key(), 2.0.into()],
+		vec![(0.0, 0.0, 0.0).into(), Iota::Pattern(pattern("ede")), 3.0.into()],
+	], Box::new(AkashicWrite));
+
+	let first: Vec<String> = library_of(&branches[0]).entries().map(|entry| format!("{} {}", entry.value, entry.version)).collect();
+	let second: Vec<String> = library_of(&branches[1]).entries().map(|entry| format!("{} {}", entry.value, entry.version)).collect();
+	assert_eq!(first, vec!["2 2"]);
+	assert_eq!(second, vec!["3 2", "1 1"]);
+	assert_eq!(library_of(&branches[1]).written_since(1).count(), 1);
+
+	// the library the hex was cast with is left as it was
+	assert_eq!(scene.library.as_ref().unwrap().version(), 1);
+}
+
+#[test]
+fn record() {
+	let mut library = Library::default();
+	library.record = Some((0, 64, 0));
+	let scene = scene(library);
+
+	let elsewhere = run_each_in(&scene, vec![vec![(5.0, 64.0, 0.0).into(), key(), 4.0.into()]], Box::new(AkashicWrite));
+	assert_eq!(describe(&elsewhere[0]), "NoAkashicRecord { argument: 2 }");
+	assert_eq!(library_of(&elsewhere[0]).version(), 0);
+
+	let unknown = run_each_in(&scene, vec![vec![Iota::Vec(itertools::Either::Right(false)), key()]], Box::new(AkashicRead));
+	let unknown: Vec<String> = unknown.iter().map(describe).collect();
+	assert_eq!(unknown, vec!["Null", "NoAkashicRecord { argument: 1 }"]);
+
+	let wrong_key = run_each_in(&scene, vec![vec![(0.5, 64.5, 0.5).into(), 1.0.into()]], Box::new(AkashicRead));
+	assert_eq!(describe(&wrong_key[0]), "InvalidType { argument: 0, expected: [Pattern], found: Double }");
+}
+
+#[test]
+fn files() {
+	let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example_library.json");
+	let library = Library::load(&path).unwrap();
+	assert_eq!(library.record, Some((0, 64, 2)));
+	assert_eq!(library.get(&pattern_from(HexAbsoluteDir::NorthEast, "qaq")).unwrap().value.to_string(), "[1, 2, 3]");
+
+	let mut written = library.clone();
+	written.write(pattern("ede"), "hello".to_string().into());
+	let read = from_json::<Library>(&to_json(&written)).unwrap();
+	assert!(read == written);
+	assert_eq!(read.version(), library.version() + 1);
+}
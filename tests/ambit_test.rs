@@ -0,0 +1,95 @@
+use std::rc::Rc;
+
+use itertools::Either::Right;
+
+use hexedit::actions::entities::*;
+use hexedit::actions::spells::*;
+use hexedit::simulator::*;
+use hexedit::world::*;
+
+mod common;
+use common::{example_scene, run_in};
+
+fn entity(name: &str) -> Iota {
+	example_scene().entities.iter().find(|entity| entity.name == name).unwrap().iota()
+}
+
+/// Applies the action to the stack in the scene, returning each branch's stack and the mishaps that happened and might
+/// have happened.
+fn outcomes(scene: Scene, stack: Vec<Iota>, action: Box<dyn Action>) -> Vec<(String, Vec<ActionError>, Vec<ActionError>)> {
+	run_in(&Rc::new(scene), stack, action).iter().map(|state| (
+		state.to_string(),
+		state.mishaps().iter().map(|mishap| mishap.error).collect(),
+		state.possible_mishaps().iter().map(|mishap| mishap.error).collect()
+	)).collect()
+}
+
+#[test]
+fn targets_in_ambit_are_allowed() {
+	assert_eq!(outcomes(example_scene(), vec![entity("Zombie")], Box::new(EntityHeight)), vec![("1.95".to_string(), vec![], vec![])]);
+	// the Skeleton is only in range because of the sentinel
+	assert_eq!(outcomes(example_scene(), vec![entity("Skeleton"), (0.0, 1.0, 0.0).into()], Box::new(Impulse)), vec![("".to_string(), vec![], vec![])]);
+}
+
+#[test]
+fn targets_out_of_ambit_mishap() {
+	assert_eq!(
+		outcomes(example_scene(), vec![entity("Enderman")], Box::new(EntityPos)),
+		vec![("Enderman".to_string(), vec![ActionError::OutOfAmbit { argument: 0 }], vec![])]
+	);
+	assert_eq!(
+		outcomes(example_scene(), vec![(100.0, 65.0, 0.0).into(), 2.0.into()], Box::new(Explode { fire: false })),
+		vec![("(100, 65, 0)\n2".to_string(), vec![ActionError::OutOfAmbit { argument: 1 }], vec![])]
+	);
+}
+
+#[test]
+fn targets_that_might_be_out_of_ambit_are_flagged() {
+	assert_eq!(
+		outcomes(example_scene(), vec![Iota::Vec(Right(false)), 2.0.into()], Box::new(Explode { fire: false })),
+		vec![("".to_string(), vec![], vec![ActionError::OutOfAmbit { argument: 1 }])]
+	);
+
+	// an entity that isn't in the scene is only known to be in range if the iota says so
+	let stranger = Iota::Entity(IotaEntity::new("Stranger").set_guaranteed_in_range(false).into());
+	assert_eq!(
+		outcomes(example_scene(), vec![stranger, (0.0, 0.0, 0.0).into()], Box::new(Impulse)),
+		vec![("".to_string(), vec![], vec![ActionError::OutOfAmbit { argument: 1 }])]
+	);
+}
+
+#[test]
+fn ambit_depends_on_casting() {
+	let mut circle = example_scene();
+	circle.caster.as_mut().unwrap().casting = Casting::Circle { min: (-8.0, 57.0, -8.0), max: (8.0, 73.0, 8.0) };
+
+	// the Zombie is within a staff's ambit, but not the circle's
+	assert_eq!(
+		outcomes(circle.clone(), vec![entity("Zombie")], Box::new(EntityHeight)),
+		vec![("Zombie".to_string(), vec![ActionError::OutOfAmbit { argument: 0 }], vec![])]
+	);
+	// sentinels don't extend a circle's ambit
+	assert_eq!(outcomes(circle, vec![entity("Skeleton")], Box::new(EntityPos))[0].1, vec![ActionError::OutOfAmbit { argument: 0 }]);
+
+	let mut trinket = example_scene();
+	trinket.caster.as_mut().unwrap().casting = Casting::Trinket;
+	assert_eq!(outcomes(trinket, vec![entity("Skeleton")], Box::new(EntityPos))[0].1, vec![]);
+
+	// without a caster nothing is checked
+	let mut no_caster = example_scene();
+	no_caster.caster = None;
+	assert_eq!(outcomes(no_caster, vec![entity("Enderman")], Box::new(EntityPos))[0].1, vec![]);
+}
+
+#[test]
+fn zones_leave_out_entities_out_of_ambit() {
+	// the Enderman is within the zone, but out of the caster's ambit
+	assert_eq!(outcomes(example_scene(), vec![(0.5, 65.62, 0.5).into(), 100.0.into()], Box::new(Zone(ZoneFilter::Is(EntityType::Monster))))[0].0, "[Zombie]");
+
+	let mut no_caster = example_scene();
+	no_caster.caster = None;
+	assert_eq!(
+		outcomes(no_caster, vec![(0.5, 65.62, 0.5).into(), 100.0.into()], Box::new(Zone(ZoneFilter::Is(EntityType::Monster))))[0].0,
+		"[Zombie, Enderman]"
+	);
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use std::path::Path;
+use std::rc::Rc;
+
+use itertools::Either::Right;
+
+use hexedit::actions::raycasts::*;
+use hexedit::actions::spells::*;
+use hexedit::interval::Interval;
+use hexedit::media::DUST;
+use hexedit::serialization::{from_json, to_json, FormatError};
+use hexedit::simulator::*;
+use hexedit::world::*;
+use common::{describe, example_scene, run_in};
+
+const CASTER_EYES: (f64, f64, f64) = (0.5, 65.62, 0.5);
+
+fn scene() -> Scene {
+	let mut scene = example_scene();
+	scene.blocks = Blocks::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example_blocks.txt")).unwrap();
+	scene
+}
+
+fn raycast(scene: Scene, origin: (f64, f64, f64), direction: (f64, f64, f64), normal: bool) -> Vec<String> {
+	run_in(&Rc::new(scene), vec![origin.into(), direction.into()], Box::new(BlockRaycast { normal }))
+		.iter()
+		.map(describe)
+		.collect()
+}
+
+/// The block at the position after the spell is cast there, and how much the spell cost.
+fn cast_at(position: (f64, f64, f64), action: Box<dyn Action>) -> (Option<String>, Interval) {
+	let states = run_in(&Rc::new(scene()), vec![position.into()], action);
+	assert_eq!(states.len(), 1);
+	(states[0].scene().unwrap().blocks.get(block_pos(position)).map(str::to_string), states[0].cost())
+}
+
+#[test]
+fn reads_the_text_format() {
+	let blocks = scene().blocks;
+
+	assert_eq!(blocks.get((0, 64, 0)), Some("air"));
+	assert_eq!(blocks.get((-8, 63, 8)), Some("stone"));
+	assert_eq!(blocks.get((3, 63, -2)), Some("water"));
+	assert_eq!(blocks.get((0, 62, 0)), Some("bedrock"));
+	assert_eq!(blocks.get((12, 60, 0)), None);
+
+	assert_eq!(Blocks::from_text("default minecraft:stone\n1 2 3 ?").unwrap().get((0, 0, 0)), Some("stone"));
+	assert_eq!(
+		Blocks::from_text("# a comment\n\n1 2 three stone"),
+		Err(FormatError::Syntax { line: 3, message: "\"three\" isn't a whole number".to_string() })
+	);
+	assert!(matches!(Blocks::from_text("1 2 stone"), Err(FormatError::Syntax { line: 1, .. })));
+	assert!(matches!(Blocks::from_text("0 0 0 10000 10000 10000 stone"), Err(FormatError::Syntax { line: 1, .. })));
+}
+
+#[test]
+fn blocks_round_trip_through_json() {
+	let blocks = scene().blocks;
+	let json = to_json(&blocks);
+
+	assert!(from_json::<Blocks>(&json).unwrap() == blocks);
+	assert!(json.contains("\"at\": ["));
+}
+
+#[test]
+fn raycasts_hit_the_first_solid_block() {
+	assert_eq!(raycast(scene(), CASTER_EYES, (0.0, 0.0, 1.0), false), vec!["(0, 65, 5)"]);
+	assert_eq!(raycast(scene(), CASTER_EYES, (0.0, 0.0, 1.0), true), vec!["(0, 0, -1)"]);
+	// the direction doesn't need to be a unit vector
+	assert_eq!(raycast(scene(), CASTER_EYES, (0.0, -5.0, 0.0), true), vec!["(0, 1, 0)"]);
+	// fluids are passed through
+	assert_eq!(raycast(scene(), (2.5, 65.0, -2.5), (0.0, -1.0, 0.0), false), vec!["(2, 62, -3)"]);
+	// a ray that starts inside a block hits it through the face it is heading away from
+	assert_eq!(raycast(scene(), (0.5, 63.5, 0.5), (1.0, -0.2, 0.0), true), vec!["(-1, 0, 0)"]);
+}
+
+#[test]
+fn raycasts_can_miss() {
+	assert_eq!(raycast(scene(), CASTER_EYES, (-1.0, 0.0, 0.0), false), vec!["Null"]);
+	assert_eq!(raycast(scene(), CASTER_EYES, (0.0, 0.0, 0.0), false), vec!["Null"]);
+
+	// the block is in range of the raycast, but not in the caster's ambit
+	let mut far = scene();
+	far.blocks.set((0, 65, 40), Some("stone".to_string()));
+	assert_eq!(raycast(far, (0.5, 65.5, 10.5), (0.0, 0.0, 1.0), false), vec!["Null"]);
+}
+
+#[test]
+fn raycasts_through_unknown_blocks_are_unknown() {
+	assert_eq!(
+		raycast(scene(), CASTER_EYES, (1.0, 0.0, 0.0), false),
+		vec!["(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: true)", "Null"]
+	);
+	assert_eq!(
+		raycast(scene(), CASTER_EYES, (1.0, 0.0, 0.0), true),
+		vec!["(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: false)", "Null"]
+	);
+	// without a scene nothing is known about the blocks
+	assert_eq!(
+		common::run(vec![CASTER_EYES.into(), (0.0, -1.0, 0.0).into()], Box::new(BlockRaycast { normal: false })),
+		vec!["(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: true)", "Null"]
+	);
+}
+
+#[test]
+fn raycasts_mishap_out_of_ambit() {
+	assert_eq!(raycast(scene(), (100.0, 65.0, 0.0), (0.0, -1.0, 0.0), false), vec!["OutOfAmbit { argument: 1 }"]);
+}
+
+#[test]
+fn spells_change_blocks() {
+	assert_eq!(cast_at((0.5, 63.5, 0.5), Box::new(BreakBlock)), (Some("air".to_string()), Interval::point(0.125 * DUST)));
+	assert_eq!(cast_at((0.5, 62.5, 0.5), Box::new(BreakBlock)), (Some("bedrock".to_string()), Interval::point(0.125 * DUST)));
+	assert_eq!(cast_at((2.5, 63.5, -2.5), Box::new(BreakBlock)).0, Some("water".to_string()));
+
+	assert_eq!(cast_at((0.5, 64.5, 0.5), Box::new(PlaceBlock)).0, Some("oak_planks".to_string()));
+	assert_eq!(cast_at((0.5, 63.5, 0.5), Box::new(PlaceBlock)).0, Some("stone".to_string()));
+
+	assert_eq!(cast_at((0.5, 64.5, 0.5), Box::new(CreateWater)), (Some("water".to_string()), Interval::point(DUST)));
+	assert_eq!(cast_at((0.5, 64.5, 5.5), Box::new(ConjureBlock { light: false })).0, Some("cobblestone".to_string()));
+	assert_eq!(cast_at((0.5, 64.5, 0.5), Box::new(ConjureBlock { light: true })).0, Some("conjured_light".to_string()));
+	// blocks that aren't known stay unknown
+	assert_eq!(cast_at((12.5, 60.5, 0.5), Box::new(BreakBlock)).0, None);
+}
+
+#[test]
+fn spells_at_unknown_positions_forget_blocks() {
+	let states = run_in(&Rc::new(scene()), vec![Iota::Vec(Right(true))], Box::new(BreakBlock));
+	let blocks = &states[0].scene().unwrap().blocks;
+
+	assert_eq!(blocks.get((0, 63, 0)), None);
+	assert_eq!(blocks.get((0, 62, 0)), Some("bedrock"));
+	assert_eq!(blocks.get((2, 63, -2)), Some("water"));
+	assert_eq!(blocks.get((50, 64, 0)), Some("air"));
+
+	let states = run_in(&Rc::new(scene()), vec![Iota::Vec(Right(true))], Box::new(CreateWater));
+	assert_eq!(states[0].scene().unwrap().blocks.get((50, 64, 0)), None);
+	assert_eq!(states[0].scene().unwrap().blocks.get((0, 63, 0)), Some("stone"));
+}
+
+#[test]
+fn branches_in_different_worlds_are_kept_apart() {
+	let broken = run_in(&Rc::new(scene()), vec![(0.5, 63.5, 0.5).into()], Box::new(BreakBlock)).remove(0);
+	let unchanged = run_in(&Rc::new(scene()), vec![(0.5, 64.5, 0.5).into()], Box::new(BreakBlock)).remove(0);
+
+	assert!(broken.stack().is_empty() && unchanged.stack().is_empty());
+	assert!(broken != unchanged);
+	assert!(broken.join(&unchanged, false).is_none());
+	assert_eq!(broken.scene().unwrap().blocks.changes(&scene().blocks), vec![(0, 63, 0)]);
+	assert!(unchanged.scene().unwrap().blocks.changes(&scene().blocks).is_empty());
+}
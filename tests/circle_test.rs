@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use hexedit::actions::registry::run_hex_from;
+use hexedit::circle::*;
+use hexedit::hex_pattern::*;
+use hexedit::serialization::{from_json, to_json};
+use hexedit::simulator::StackState;
+use hexedit::world::{Caster, Casting};
+
+mod common;
+use common::pattern;
+
+fn slate(signature: Option<&str>) -> CircleBlock { CircleBlock::Slate { pattern: signature.map(pattern), attached: Direction::Down } }
+
+fn example() -> Circle { Circle::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example_circle.json")).unwrap() }
+
+#[test]
+fn wave_order() {
+	let circle = example();
+	let wave = circle.wave().unwrap();
+	assert!(wave.closed);
+	assert_eq!(wave.fronts.len(), 8);
+	assert_eq!(wave.slates, vec![(1, 64, 0), (2, 64, 2), (1, 64, 2)]);
+
+	let signatures: Vec<String> = circle.patterns().unwrap().iter().map(HexPattern::angle_signature).collect();
+	assert_eq!(signatures, vec!["aqae", "dedq", "aqae"]);
+
+	let patterns = circle.patterns().unwrap();
+	let stack_manager = run_hex_from(&patterns, StackState::default()).unwrap();
+	let stacks: Vec<String> = stack_manager.stacks().iter().map(|result| result.as_ref().unwrap().to_string()).collect();
+	assert_eq!(stacks, vec!["True\nFalse\nTrue"]);
+}
+
+#[test]
+fn directrices() {
+	let mut circle = Circle::default();
+	circle.set((0, 0, 0), CircleBlock::Impetus { facing: Direction::East });
+	circle.set((1, 0, 0), CircleBlock::Directrix { facing: Direction::North, powered: false });
+	circle.set((1, 0, -1), slate(Some("aqae")));
+	circle.set((1, 0, 1), slate(Some("dedq")));
+
+	assert_eq!(circle.wave().unwrap().slates, vec![(1, 0, -1)]);
+	assert!(circle.patterns() == Err(CircleError::NotClosed { stopped_at: vec![(1, 0, -1)] }));
+
+	circle.set((1, 0, 0), CircleBlock::Directrix { facing: Direction::North, powered: true });
+	assert_eq!(circle.wave().unwrap().slates, vec![(1, 0, 1)]);
+}
+
+#[test]
+fn slates_carry_the_wave_along_their_surface() {
+	// slates on a wall pass the wave up the wall, but one on the floor doesn't
+	let mut circle = Circle::default();
+	circle.set((0, 0, 0), CircleBlock::Impetus { facing: Direction::East });
+	circle.set((1, 0, 0), CircleBlock::Slate { pattern: None, attached: Direction::South });
+	circle.set((1, 1, 0), CircleBlock::Slate { pattern: Some(pattern("aqae")), attached: Direction::South });
+	assert_eq!(circle.wave().unwrap().slates, vec![(1, 1, 0)]);
+
+	circle.set((1, 0, 0), slate(None));
+	assert!(circle.wave().unwrap().slates.is_empty());
+}
+
+#[test]
+fn impetuses() {
+	let mut circle = Circle::default();
+	circle.set((0, 0, 0), slate(Some("aqae")));
+	assert!(circle.wave() == Err(CircleError::NoImpetus));
+
+	circle.set((1, 0, 0), CircleBlock::Impetus { facing: Direction::East });
+	circle.set((2, 0, 0), CircleBlock::Impetus { facing: Direction::East });
+	assert!(circle.wave() == Err(CircleError::TooManyImpetuses(vec![(1, 0, 0), (2, 0, 0)])));
+}
+
+#[test]
+fn ambit_and_files() {
+	let circle = example();
+	let casting = circle.wave().unwrap().casting();
+	assert_eq!(casting, Casting::Circle { min: (0.0, 64.0, 0.0), max: (3.0, 65.0, 3.0) });
+
+	// the ambit is the box itself, not a sphere around it
+	let caster = Caster { casting, ..Caster::default() };
+	assert!(caster.in_ambit((0.1, 64.9, 2.9)));
+	assert!(!caster.in_ambit((1.5, 65.5, 1.5)));
+	assert!(!caster.in_ambit((3.0, 64.5, 1.5)));
+
+	let read = from_json::<Circle>(&to_json(&circle)).unwrap();
+	assert!(read == circle);
+	assert!(to_json(&circle).contains("\"HexPattern(dedq, EAST)\""));
+	assert!(circle.hex_text().unwrap().starts_with("HexPattern(aqae, EAST),\nHexPattern(dedq, EAST)"));
+}
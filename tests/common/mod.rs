@@ -0,0 +1,70 @@
+// each test crate uses only some of the helpers
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::rc::Rc;
+
+use hexedit::hex_pattern::*;
+use hexedit::simulator::*;
+use hexedit::world::Scene;
+
+/// The pattern with the angle signature, starting in the direction.
+pub fn pattern_from(start_dir: HexAbsoluteDir, signature: &str) -> HexPattern {
+	let dirs = signature.chars().map(|c| match c {
+		'a' => HexDir::A,
+		'q' => HexDir::Q,
+		'w' => HexDir::W,
+		'e' => HexDir::E,
+		_ => HexDir::D,
+	}).collect();
+	HexPattern::hex_pattern(start_dir, dirs).unwrap()
+}
+
+/// The pattern with the angle signature, starting east.
+pub fn pattern(signature: &str) -> HexPattern { pattern_from(HexAbsoluteDir::East, signature) }
+
+/// The patterns with the angle signatures, each starting east.
+pub fn patterns(signatures: &[&str]) -> Vec<HexPattern> { signatures.iter().map(|signature| pattern(signature)).collect() }
+
+/// The example scene, from scenes/example.json.
+pub fn example_scene() -> Scene { Scene::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example.json")).unwrap() }
+
+/// Displays the branch as its stack, or as the error of its last mishap if it mishapped.
+pub fn describe(state: &StackState) -> String {
+	match state.mishaps().last() {
+		Some(mishap) => format!("{:?}", mishap.error),
+		None => state.to_string(),
+	}
+}
+
+/// Displays each branch (see [`describe`]), or the error the simulator gave up on it with.
+pub fn describe_all(stacks: &StackHolder) -> Vec<String> {
+	stacks.iter().map(|result| match result {
+		Ok(state) => describe(state),
+		Err(action_error) => format!("{action_error:?}"),
+	}).collect()
+}
+
+/// Applies the action to the given stack and returns each resulting branch, displayed as a string.
+/// Branches that mishapped are displayed as the mishap's error instead of the stack it left behind.
+pub fn run(stack: Vec<Iota>, action: Box<dyn Action>) -> Vec<String> {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(stack, None)));
+	stack_manager.apply_action(action);
+	describe_all(stack_manager.stacks())
+}
+
+/// Applies the action to each of the stacks in the scene, returning every resulting branch.
+pub fn run_each_in(scene: &Rc<Scene>, stacks: Vec<Vec<Iota>>, action: Box<dyn Action>) -> Vec<StackState> {
+	let states = stacks.into_iter().map(|stack| {
+		let mut state = StackState::new(stack, None);
+		state.set_scene(Some(scene.clone()));
+		Ok(state)
+	}).collect();
+	let mut stack_manager = StackManager::new(StackHolder::new(states));
+	stack_manager.apply_action(action);
+
+	stack_manager.stacks().iter().map(|result| result.as_ref().unwrap().clone()).collect()
+}
+
+/// Applies the action to the stack in the scene, returning every resulting branch.
+pub fn run_in(scene: &Rc<Scene>, stack: Vec<Iota>, action: Box<dyn Action>) -> Vec<StackState> { run_each_in(scene, vec![stack], action) }
@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use hexedit::actions::registry::run_hex;
+use hexedit::debugger::*;
+use hexedit::simulator::*;
+
+mod common;
+use common::{describe_all, patterns};
+
+fn debugger(signatures: &[&str]) -> Debugger { Debugger::new(patterns(signatures), StackState::default()) }
+
+fn frames(debugger: &Debugger) -> Vec<String> {
+	debugger.stacks().iter().flat_map(|result| result.as_ref().unwrap().frames().iter().map(Frame::to_string)).collect()
+}
+
+#[test]
+fn hermes_casts_patterns() {
+	// Introspection, True Reflection, False Reflection, Retrospection, Hermes' Gambit
+	assert_eq!(describe_all(run_hex(&patterns(&["qqq", "aqae", "dedq", "eee", "deaqq"])).unwrap().stacks()), vec!["True\nFalse"]);
+
+	let stack = vec![Iota::List(IotaList::unknown(Some(2), None))];
+	assert_eq!(common::run(stack, Box::new(hexedit::actions::meta::Eval)), vec!["Indeterminate"]);
+	assert_eq!(common::run(vec![1.0.into()], Box::new(hexedit::actions::meta::Eval)), vec!["InvalidType { argument: 0, expected: [Pattern, List], found: Double }"]);
+}
+
+#[test]
+fn thoth_collects_stacks() {
+	// casts Hermes' Gambit on each of [True Reflection, False Reflection]
+	let patterns = patterns(&["qqq", "deaqq", "eee", "qqq", "aqae", "dedq", "eee", "dadad"]);
+	assert_eq!(describe_all(run_hex(&patterns).unwrap().stacks()), vec!["[True, False]"]);
+}
+
+#[test]
+fn stepping_into_frames() {
+	let mut debugger = debugger(&["qqq", "aqae", "eee", "deaqq", "dedq"]);
+
+	debugger.step_into();
+	assert_eq!(debugger.escape_depth(), 1);
+	debugger.step_into();
+	debugger.step_into();
+	assert_eq!(debugger.escape_depth(), 0);
+	assert_eq!(debugger.position(), 3);
+
+	debugger.step_into();
+	assert_eq!(frames(&debugger), vec!["Hermes' Gambit: pattern 0 of 1"]);
+	assert_eq!(debugger.position(), 3);
+
+	debugger.step_into();
+	assert!(frames(&debugger).is_empty());
+	assert_eq!(describe_all(debugger.stacks()), vec!["True"]);
+	assert_eq!(debugger.position(), 4);
+
+	debugger.step_into();
+	assert!(debugger.is_finished());
+	assert_eq!(describe_all(debugger.stacks()), vec!["True\nFalse"]);
+}
+
+#[test]
+fn stepping_over_frames() {
+	let mut debugger = debugger(&["qqq", "deaqq", "eee", "qqq", "aqae", "dedq", "eee", "dadad"]);
+	debugger.resume(&BTreeSet::from([7]));
+	assert_eq!(debugger.position(), 7);
+
+	debugger.step_over();
+	assert!(debugger.is_finished());
+	assert_eq!(describe_all(debugger.stacks()), vec!["[True, False]"]);
+}
+
+#[test]
+fn resuming_stops_at_breakpoints() {
+	let mut debugger = debugger(&["aqae", "dedq", "aqae", "dedq"]);
+	let breakpoints = BTreeSet::from([1, 3]);
+
+	debugger.resume(&breakpoints);
+	assert_eq!(debugger.position(), 1);
+	debugger.resume(&breakpoints);
+	assert_eq!(debugger.position(), 3);
+	assert_eq!(describe_all(debugger.stacks()), vec!["True\nFalse\nTrue"]);
+	debugger.resume(&breakpoints);
+	assert!(debugger.is_finished());
+}
+
+#[test]
+fn hexes_that_cast_themselves_give_up() {
+	// pushes [Gemini Decomposition, Hermes' Gambit], then casts it on a copy of itself
+	let mut debugger = debugger(&["qqq", "aadaa", "deaqq", "eee", "aadaa", "deaqq"]);
+	debugger.run();
+	assert_eq!(describe_all(debugger.stacks()), vec!["TooManySteps"]);
+}
+
+#[test]
+fn unknown_patterns_stop_casting() {
+	let mut debugger = debugger(&["aqae", "wwwwwwwwwww"]);
+	debugger.run();
+	assert_eq!(debugger.unknown_pattern(), Some(1));
+	assert!(run_hex(&patterns(&["aqae", "wwwwwwwwwww"])).is_err());
+}
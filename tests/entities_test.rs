@@ -0,0 +1,99 @@
+mod common;
+
+use std::rc::Rc;
+
+use hexedit::actions::entities::*;
+use hexedit::simulator::*;
+use hexedit::world::*;
+use common::{describe, example_scene, run, run_in};
+
+fn scene() -> Rc<Scene> { Rc::new(example_scene()) }
+
+/// Applies the action to the stack in the example scene and returns each resulting branch, displayed as a string.
+fn run_in_scene(stack: Vec<Iota>, action: Box<dyn Action>) -> Vec<String> { run_in(&scene(), stack, action).iter().map(describe).collect() }
+
+fn entity(name: &str) -> Iota {
+	scene().entities.iter().find(|entity| entity.name == name).map_or_else(|| Iota::Entity(IotaEntity::new(name).set_guaranteed_in_range(true).into()), SceneEntity::iota)
+}
+
+#[test]
+fn reads_properties_from_the_scene() {
+	assert_eq!(run_in_scene(vec![entity("Caster")], Box::new(EntityPos)), vec!["(0.5, 65.62, 0.5)"]);
+	assert_eq!(run_in_scene(vec![entity("Caster")], Box::new(EntityLook)), vec!["(0, 0, 1)"]);
+	assert_eq!(run_in_scene(vec![entity("Caster")], Box::new(EntityHeight)), vec!["1.8"]);
+	assert_eq!(run_in_scene(vec![entity("Caster")], Box::new(EntityVelocity)), vec!["(0, 0, 0)"]);
+	// the Zombie is found by its uuid
+	assert_eq!(run_in_scene(vec![entity("Zombie")], Box::new(EntityPos)), vec!["(3.5, 65.74, 8.5)"]);
+}
+
+#[test]
+fn partially_knows_missing_properties() {
+	assert_eq!(run_in_scene(vec![entity("Zombie")], Box::new(EntityVelocity)), vec!["(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: false)"]);
+	assert_eq!(run(vec![entity("Caster")], Box::new(EntityPos)), vec!["(UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: true)"]);
+	assert_eq!(run(vec![entity("Caster")], Box::new(EntityHeight)), vec!["UNKOWN in [0, inf]"]);
+}
+
+#[test]
+fn mishaps_without_an_entity() {
+	assert_eq!(run_in_scene(vec![], Box::new(EntityPos)), vec!["StackTooSmall { expected: 1, found: 0 }"]);
+	assert_eq!(
+		run_in_scene(vec![true.into()], Box::new(EntityHeight)),
+		vec!["InvalidType { argument: 0, expected: [Entity], found: Bool }"]
+	);
+}
+
+fn zone(filter: ZoneFilter, radius: f64) -> Vec<String> {
+	run_in_scene(vec![(0.5, 65.62, 0.5).into(), radius.into()], Box::new(Zone(filter)))
+}
+
+#[test]
+fn zones_find_entities_in_the_scene() {
+	assert_eq!(zone(ZoneFilter::Any, 10.0), vec!["[Caster, Zombie, Dropped Sword]"]);
+	assert_eq!(zone(ZoneFilter::Any, 100.0), vec!["[Caster, Zombie, Cow, Dropped Sword]"]);
+	assert_eq!(zone(ZoneFilter::Is(EntityType::Monster), 10.0), vec!["[Zombie]"]);
+	assert_eq!(zone(ZoneFilter::IsNot(EntityType::Monster), 10.0), vec!["[Caster, Dropped Sword]"]);
+	assert_eq!(zone(ZoneFilter::Is(EntityType::Living), 100.0), vec!["[Caster, Zombie, Cow]"]);
+	assert_eq!(zone(ZoneFilter::IsNot(EntityType::Living), 100.0), vec!["[Dropped Sword]"]);
+	assert_eq!(zone(ZoneFilter::Is(EntityType::Player), 0.5), vec!["[Caster]"]);
+}
+
+#[test]
+fn zones_constrain_unknown_entities() {
+	let stack = vec![(0.0, 0.0, 0.0).into(), 10.0.into()];
+	assert_eq!(run(stack.clone(), Box::new(Zone(ZoneFilter::Is(EntityType::Monster)))), vec!["[UNKOWN, len=UNKOWN, each monster]"]);
+
+	// an unknown radius means the scene can't be used
+	assert_eq!(
+		run_in_scene(vec![(0.5, 65.0, 0.5).into(), None.into()], Box::new(Zone(ZoneFilter::IsNot(EntityType::Living)))),
+		vec!["[UNKOWN, len=UNKOWN, each non-living]"]
+	);
+
+	let monster = IotaEntity::of_type("monster", EntityType::Monster, true);
+	assert_eq!(monster.is_type(EntityType::Living), Some(true));
+	assert_eq!(monster.is_type(EntityType::Animal), Some(false));
+	assert_eq!(monster.is_type(EntityType::Item), Some(false));
+
+	let non_living = IotaEntity::of_type("non-living", EntityType::Living, false);
+	assert_eq!(non_living.is_type(EntityType::Player), Some(false));
+	assert_eq!(non_living.is_type(EntityType::Item), None);
+
+	// taking an entity out of the list keeps its types
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(stack, None)));
+	stack_manager.apply_action(Box::new(Zone(ZoneFilter::IsNot(EntityType::Player))));
+	stack_manager.apply_action(Box::new(hexedit::actions::lists::Pop));
+	match stack_manager.stacks().iter().next() {
+		Some(Ok(state)) => match state.stack().last() {
+			Some(Iota::Entity(entity)) => assert_eq!(entity.is_type(EntityType::Player), Some(false)),
+			_ => panic!("expected an entity"),
+		},
+		_ => panic!("expected a state"),
+	}
+}
+
+#[test]
+fn zones_mishap_without_a_position() {
+	assert_eq!(
+		run_in_scene(vec![true.into(), 3.0.into()], Box::new(Zone(ZoneFilter::Any))),
+		vec!["InvalidType { argument: 1, expected: [Vec], found: Bool }"]
+	);
+}
@@ -0,0 +1,73 @@
+use std::collections::BTreeSet;
+
+use hexedit::debugger::Debugger;
+use hexedit::history::*;
+use hexedit::simulator::*;
+
+mod common;
+use common::patterns;
+
+fn changes(before: Vec<Iota>, after: Vec<Iota>) -> Vec<String> { diff(&before, &after).iter().map(StackChange::to_string).collect() }
+
+#[test]
+fn diffs_stacks() {
+	assert_eq!(changes(vec![1.0.into()], vec![1.0.into(), 2.0.into()]), vec!["pushed 2 to 1"]);
+	assert_eq!(changes(vec![1.0.into(), 2.0.into(), 3.0.into()], vec![1.0.into(), 5.0.into()]), vec!["popped 3 from 2", "popped 2 from 1", "pushed 5 to 1"]);
+	assert_eq!(changes(vec![1.0.into(), 2.0.into()], vec![2.0.into(), 1.0.into()]), vec!["changed 1 to 2 at 0", "changed 2 to 1 at 1"]);
+	assert!(changes(vec![true.into()], vec![true.into()]).is_empty());
+}
+
+#[test]
+fn shares_the_bottom_of_stacks() {
+	let first = SharedStack::record(&SharedStack::default(), &[1.0.into(), 2.0.into(), 3.0.into()]);
+	let second = SharedStack::record(&first, &[1.0.into(), 2.0.into(), 4.0.into(), 5.0.into()]);
+
+	assert_eq!(second.len(), 4);
+	assert!(second.to_vec() == vec![1.0.into(), 2.0.into(), 4.0.into(), 5.0.into()]);
+	assert_eq!(second.shared_with(&first), 2);
+	assert_eq!(first.shared_with(&SharedStack::record(&SharedStack::default(), &first.to_vec())), 0);
+}
+
+#[test]
+fn debugger_records_and_rewinds() {
+	// True Reflection, False Reflection, Jester's Gambit
+	let mut debugger = Debugger::new(patterns(&["aqae", "dedq", "aawdd"]), StackState::default());
+	debugger.resume(&BTreeSet::new());
+
+	let history = debugger.history();
+	assert_eq!(history.len(), 4);
+	assert_eq!(history.get(0).unwrap().pattern_index(), None);
+	assert_eq!(history.get(2).unwrap().pattern_index(), Some(1));
+	let changes: Vec<String> = history.changes(3)[0].as_ref().unwrap().iter().map(StackChange::to_string).collect();
+	assert_eq!(changes, vec!["changed True to False at 0", "changed False to True at 1"]);
+
+	debugger.rewind(1);
+	assert_eq!(debugger.history().len(), 2);
+	assert_eq!(debugger.position(), 1);
+	assert_eq!(debugger.stacks().iter().next().unwrap().as_ref().unwrap().to_string(), "True");
+
+	debugger.resume(&BTreeSet::new());
+	assert!(debugger.is_finished());
+	assert_eq!(debugger.stacks().iter().next().unwrap().as_ref().unwrap().to_string(), "False\nTrue");
+}
+
+#[test]
+fn history_shares_stacks_between_snapshots() {
+	let stacks = |stack: Vec<Iota>| StackHolder::single_state(StackState::new(stack, None));
+	let shared = |history: &History, i: usize, j: usize| {
+		let stack = |k: usize| history.get(k).unwrap().branches()[0].as_ref().unwrap().stack().clone();
+		stack(i).shared_with(&stack(j))
+	};
+
+	let mut history = History::default();
+	history.record(None, &stacks(vec![1.0.into(), 2.0.into(), 3.0.into()]));
+	history.record(Some(0), &stacks(vec![1.0.into(), 2.0.into(), 4.0.into()]));
+	assert_eq!(shared(&history, 0, 1), 2);
+
+	// after rewinding, the next step shares with the snapshot it follows
+	history.truncate(1);
+	history.record(Some(0), &stacks(vec![1.0.into(), 5.0.into()]));
+	assert_eq!(shared(&history, 0, 1), 1);
+	let changes: Vec<String> = history.changes(1)[0].as_ref().unwrap().iter().map(StackChange::to_string).collect();
+	assert_eq!(changes, vec!["popped 3 from 2", "popped 2 from 1", "pushed 5 to 1"]);
+}
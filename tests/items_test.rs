@@ -0,0 +1,138 @@
+use std::path::Path;
+use std::rc::Rc;
+
+use itertools::Either::Right;
+
+use hexedit::actions::items::*;
+use hexedit::actions::registry::{pattern_name, run_hex_from};
+use hexedit::actions::spells::Explode;
+use hexedit::hex_pattern::*;
+use hexedit::interval::Interval;
+use hexedit::items::{Charge, Item, ItemError, ItemKind, Page};
+use hexedit::media::DUST;
+use hexedit::serialization::{from_json, to_json};
+use hexedit::simulator::*;
+use hexedit::world::{Caster, Casting, Scene};
+
+mod common;
+use common::{describe, pattern, run_in};
+
+fn focus(iota: Option<Iota>, sealed: bool) -> Item { Item { pages: vec![Page { iota, sealed }], ..Item::new(ItemKind::Focus) } }
+
+fn scene(caster: Caster) -> Rc<Scene> { Rc::new(Scene { caster: Some(caster), ..Scene::default() }) }
+
+fn holding(item: Item) -> Rc<Scene> { scene(Caster { held_item: Some(item), ..Caster::default() }) }
+
+fn held_item(state: &StackState) -> &Item { state.scene().unwrap().caster.as_ref().unwrap().held_item.as_ref().unwrap() }
+
+#[test]
+fn scribes_reflection() {
+	assert_eq!(pattern_name(&pattern("aqqqqq")).as_deref(), Some("read_held_item"));
+	assert_eq!(pattern_name(&pattern("deeeee")).as_deref(), Some("write_held_item"));
+
+	let read = run_in(&holding(focus(Some(3.0.into()), false)), vec![], Box::new(ReadHeldItem));
+	assert_eq!(describe(&read[0]), "3");
+
+	let blank = run_in(&holding(Item::new(ItemKind::Focus)), vec![], Box::new(ReadHeldItem));
+	assert_eq!(describe(&blank[0]), "Null");
+
+	let trinket = run_in(&holding(Item::new(ItemKind::Trinket)), vec![], Box::new(ReadHeldItem));
+	assert_eq!(describe(&trinket[0]), "BadHeldItem { writing: false }");
+
+	let unknown = run_in(&scene(Caster::default()), vec![], Box::new(ReadHeldItem));
+	assert_eq!(describe(&unknown[0]), "UNKNOWN");
+}
+
+#[test]
+fn scribes_gambit() {
+	let before = holding(focus(Some(3.0.into()), false));
+	let written = run_in(&before, vec![1.0.into(), 4.0.into()], Box::new(WriteHeldItem));
+	assert_eq!(describe(&written[0]), "1");
+	assert!(held_item(&written[0]).read() == Ok(4.0.into()));
+	// only the branch's copy of the item is written
+	assert!(before.caster.as_ref().unwrap().held_item.as_ref().unwrap().read() == Ok(3.0.into()));
+
+	let sealed = run_in(&holding(focus(Some(3.0.into()), true)), vec![4.0.into()], Box::new(WriteHeldItem));
+	assert_eq!(describe(&sealed[0]), "BadHeldItem { writing: true }");
+	assert_eq!(sealed[0].stack().len(), 1);
+
+	let empty = run_in(&holding(Item::new(ItemKind::Focus)), vec![], Box::new(WriteHeldItem));
+	assert_eq!(describe(&empty[0]), "StackTooSmall { expected: 1, found: 0 }");
+}
+
+#[test]
+fn readable_and_writable() {
+	let sealed = holding(focus(None, true));
+	assert_eq!(describe(&run_in(&sealed, vec![], Box::new(HeldItemAccess { writing: false }))[0]), "True");
+	assert_eq!(describe(&run_in(&sealed, vec![], Box::new(HeldItemAccess { writing: true }))[0]), "False");
+
+	let cypher = holding(Item::new(ItemKind::Cypher));
+	assert_eq!(describe(&run_in(&cypher, vec![], Box::new(HeldItemAccess { writing: false }))[0]), "False");
+
+	let unknown = scene(Caster::default());
+	assert_eq!(describe(&run_in(&unknown, vec![], Box::new(HeldItemAccess { writing: true }))[0]), "UNKNOWN");
+}
+
+#[test]
+fn spellbook_pages() {
+	let mut spellbook = Item::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example_spellbook.json")).unwrap();
+	assert_eq!(spellbook.to_string(), "spellbook, page 2: (2.5, 65, 3.5)");
+
+	spellbook.select_page(0).unwrap();
+	assert!(spellbook.readable());
+	assert!(!spellbook.writable());
+	assert_eq!(spellbook.write(1.0.into()), Err(ItemError::Sealed));
+
+	// pages past the ones written are blank, up to the last page
+	spellbook.select_page(10).unwrap();
+	assert!(spellbook.read() == Ok(Iota::Widget));
+	spellbook.write(1.0.into()).unwrap();
+	assert_eq!(spellbook.pages.len(), 11);
+	assert_eq!(spellbook.select_page(64), Err(ItemError::NoPage(64)));
+
+	let read_back: Item = from_json(&to_json(&spellbook)).unwrap();
+	assert_eq!(read_back, spellbook);
+}
+
+#[test]
+fn casting_from_a_trinket() {
+	let trinket = Item::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/example_trinket.json")).unwrap();
+	assert_eq!(trinket.to_string(), "trinket with 2 patterns, 1.5 dust of 10 dust");
+
+	let caster = |casting_item: Item| Caster {
+		position: (0.5, 65.62, 0.5),
+		casting: Casting::Trinket,
+		held_item: Some(focus(Some((2.5, 65.0, 3.5).into()), false)),
+		casting_item: Some(casting_item),
+		..Caster::default()
+	};
+	let cast = |scene: Rc<Scene>, hex: &[HexPattern]| {
+		let mut input = StackState::default();
+		input.set_scene(Some(scene));
+		let stack_manager = run_hex_from(hex, input).unwrap();
+		stack_manager.stacks().iter().map(|result| describe(result.as_ref().unwrap())).collect::<Vec<_>>()
+	};
+
+	assert_eq!(cast(scene(caster(trinket.clone())), &trinket.hex), vec![""]);
+
+	let drained = Item { media: Some(Charge { media: 0.5 * DUST, capacity: 10.0 * DUST }), ..trinket.clone() };
+	assert_eq!(cast(scene(caster(drained.clone())), &trinket.hex), vec!["NotEnoughMedia"]);
+
+	// an artifact draws on the caster's media once its own runs out
+	let artifact = Item { kind: ItemKind::Artifact, ..drained };
+	assert_eq!(cast(scene(Caster { media: Some(DUST), ..caster(artifact) }), &trinket.hex), vec![""]);
+
+	// a staff uses the caster's media, when it is known
+	let staff = Caster { casting: Casting::Staff, media: Some(0.5 * DUST), ..caster(trinket.clone()) };
+	assert_eq!(cast(scene(staff), &trinket.hex), vec!["NotEnoughMedia"]);
+	let unknown = Caster { casting: Casting::Staff, ..caster(trinket.clone()) };
+	assert_eq!(cast(scene(unknown), &trinket.hex), vec![""]);
+}
+
+#[test]
+fn might_run_out_of_media() {
+	let strength = Iota::Double(Right(Interval::new(0.0, 10.0)));
+	let branches = run_in(&scene(Caster { media: Some(DUST), ..Caster::default() }), vec![(0.0, 0.0, 0.0).into(), strength], Box::new(Explode { fire: false }));
+	assert_eq!(describe(&branches[0]), "");
+	assert_eq!(branches[0].possible_mishaps().iter().map(|mishap| mishap.error).collect::<Vec<_>>(), vec![ActionError::NotEnoughMedia]);
+}
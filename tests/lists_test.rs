@@ -0,0 +1,69 @@
+use itertools::Either::{Left, Right};
+
+use hexedit::simulator::*;
+use hexedit::actions::lists::*;
+
+mod common;
+use common::run;
+
+#[test]
+fn append_and_pop() {
+	let list: Iota = vec![1.0.into(), 2.0.into()].into();
+
+	assert_eq!(run(vec![list.clone(), 3.0.into()], Box::new(Append)), vec!["[1, 2, 3]"]);
+	assert_eq!(run(vec![list, 3.0.into()], Box::new(Pop)), vec!["InvalidType { argument: 0, expected: [List], found: Double }"]);
+	assert_eq!(run(vec![Vec::new().into()], Box::new(Pop)), vec!["[]\nNull"]);
+	assert_eq!(run(vec![IotaList::new(Right(Some(3))).into()], Box::new(Pop)), vec!["[UNKOWN, len=2]\nUNKNOWN"]);
+}
+
+#[test]
+fn index_and_slice() {
+	let list: Iota = vec![1.0.into(), 2.0.into(), 3.0.into()].into();
+
+	assert_eq!(run(vec![list.clone(), 1.0.into()], Box::new(Index)), vec!["2"]);
+	assert_eq!(run(vec![list.clone(), 5.0.into()], Box::new(Index)), vec!["Null"]);
+	// indices are rounded as Java's Math.round does, with halves rounded up
+	assert_eq!(run(vec![list.clone(), (-0.5).into()], Box::new(Index)), vec!["1"]);
+	assert_eq!(run(vec![list.clone(), 1.5.into()], Box::new(Index)), vec!["3"]);
+	assert_eq!(run(vec![list.clone(), 2.5.into()], Box::new(Index)), vec!["Null"]);
+	assert_eq!(run(vec![list.clone(), hexedit::interval::Interval::new(-0.5, 0.4).into()], Box::new(Index)), vec!["UNKNOWN"]);
+	assert_eq!(run(vec![list.clone(), 2.0.into(), 0.0.into()], Box::new(Slice)), vec!["[1, 2]"]);
+	assert_eq!(run(vec![list, 0.0.into(), 4.0.into()], Box::new(Slice)), vec!["OutOfBounds { argument: 0 }"]);
+	assert_eq!(run(vec![IotaList::new(Right(Some(5))).into(), 1.0.into(), 3.0.into()], Box::new(Slice)), vec!["[UNKOWN, len=2]"]);
+}
+
+#[test]
+fn construct_keeps_rest_of_stack() {
+	assert_eq!(run(vec![5.0.into(), 1.0.into(), 2.0.into(), 2.0.into()], Box::new(Construct)), vec!["5\n[1, 2]"]);
+	assert_eq!(run(vec![1.0.into(), 2.0.into(), None.into()], Box::new(Construct)), vec!["1\n2\n[]", "1\n[2]", "[1, 2]", "OutOfBounds { argument: 0 }"]);
+}
+
+#[test]
+fn splat_deconstruct_and_reverse() {
+	let list: Iota = vec![1.0.into(), 2.0.into()].into();
+
+	assert_eq!(run(vec![list.clone()], Box::new(Splat)), vec!["1\n2"]);
+	assert_eq!(run(vec![IotaList::new(Right(None)).into()], Box::new(Splat)), vec!["Indeterminate"]);
+	assert_eq!(run(vec![IotaList::new(Right(Some(2))).into()], Box::new(Splat)), vec!["UNKNOWN\nUNKNOWN"]);
+	assert_eq!(run(vec![IotaList::new(Right(Some(MAX_STACK_LEN + 1))).into()], Box::new(Splat)), vec!["Indeterminate"]);
+	assert_eq!(run(vec![list.clone()], Box::new(Deconstruct)), vec!["[2]\n1"]);
+	assert_eq!(run(vec![list], Box::new(Reverse)), vec!["[2, 1]"]);
+}
+
+#[test]
+fn index_of_and_replace() {
+	let list: Iota = vec![1.0.into(), 2.0.into()].into();
+
+	assert_eq!(run(vec![list.clone(), 2.00001.into()], Box::new(IndexOf)), vec!["1"]);
+	assert_eq!(run(vec![list.clone(), Iota::Widget], Box::new(IndexOf)), vec!["-1"]);
+	assert_eq!(run(vec![list.clone(), 0.0.into(), Iota::Widget], Box::new(Replace)), vec!["[Null, 2]"]);
+	assert_eq!(run(vec![list, 2.0.into(), Iota::Widget], Box::new(Replace)), vec!["OutOfBounds { argument: 1 }"]);
+	assert_eq!(run(vec![], Box::new(EmptyList)), vec!["[]"]);
+}
+
+#[test]
+fn list_items() {
+	let list = IotaList::new(Left(vec![1.0.into(), 2.0.into()]));
+	assert_eq!(list.items().map(<[Iota]>::len), Some(2));
+	assert!(IotaList::unknown(Some(2), None).items().is_none());
+}
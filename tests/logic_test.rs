@@ -0,0 +1,40 @@
+use hexedit::simulator::*;
+use hexedit::actions::logic::*;
+
+mod common;
+use common::run;
+
+#[test]
+fn comparisons_use_tolerance() {
+	assert_eq!(run(vec![1.0.into(), 1.00001.into()], Box::new(Greater)), vec!["False"]);
+	assert_eq!(run(vec![1.0.into(), 1.00001.into()], Box::new(LessEq)), vec!["True"]);
+	assert_eq!(run(vec![2.0.into(), 1.0.into()], Box::new(Greater)), vec!["True"]);
+	assert_eq!(run(vec![2.0.into(), None.into()], Box::new(Less)), vec!["UNKNOWN"]);
+	assert_eq!(run(vec![2.0.into(), Iota::Widget], Box::new(Less)), vec!["InvalidType { argument: 0, expected: [Double], found: Null }"]);
+	assert_eq!(run(vec![Iota::Garbage, Iota::Garbage], Box::new(Equals)), vec!["True"]);
+	assert_eq!(run(vec![Iota::Widget, 0.0.into()], Box::new(NotEquals)), vec!["True"]);
+}
+
+#[test]
+fn if_branches_on_unknown() {
+	assert_eq!(run(vec![true.into(), 1.0.into(), 2.0.into()], Box::new(If)), vec!["1"]);
+	assert_eq!(run(vec![Iota::Bool(None), 1.0.into(), 2.0.into()], Box::new(If)), vec!["1", "2"]);
+	assert_eq!(run(vec![1.0.into(), 1.0.into(), 2.0.into()], Box::new(If)), vec!["InvalidType { argument: 2, expected: [Bool], found: Double }"]);
+}
+
+#[test]
+fn three_valued_logic() {
+	assert_eq!(run(vec![Iota::Bool(None), true.into()], Box::new(Or)), vec!["True"]);
+	assert_eq!(run(vec![Iota::Bool(None), true.into()], Box::new(And)), vec!["UNKNOWN"]);
+	assert_eq!(run(vec![false.into(), Iota::Bool(None)], Box::new(And)), vec!["False"]);
+	assert_eq!(run(vec![false.into()], Box::new(Not)), vec!["True"]);
+	assert_eq!(run(vec![], Box::new(PushFalse)), vec!["False"]);
+}
+
+#[test]
+fn truthiness() {
+	assert_eq!(run(vec![0.0.into()], Box::new(Truthy)), vec!["False"]);
+	assert_eq!(run(vec![Vec::new().into()], Box::new(Truthy)), vec!["False"]);
+	assert_eq!(run(vec![(0.0, 1.0, 0.0).into()], Box::new(Truthy)), vec!["True"]);
+	assert_eq!(run(vec![None.into()], Box::new(Truthy)), vec!["UNKNOWN"]);
+}
@@ -0,0 +1,43 @@
+use hexedit::interval::Interval;
+use hexedit::simulator::*;
+use hexedit::actions::maths::*;
+use hexedit::actions::lists::Index;
+
+mod common;
+use common::run;
+
+#[test]
+fn known_arithmetic() {
+	assert_eq!(run(vec![13.0.into(), 8.5.into()], Box::new(Add)), vec!["21.5"]);
+	assert_eq!(run(vec![(1.0, 2.0, 3.0).into(), (1.0, 1.0, 1.0).into()], Box::new(Sub)), vec!["(0, 1, 2)"]);
+	assert_eq!(run(vec![(1.0, 2.0, 3.0).into(), (1.0, 1.0, 1.0).into()], Box::new(Mul)), vec!["6"]);
+	assert_eq!(run(vec![(1.0, 0.0, 0.0).into(), (0.0, 1.0, 0.0).into()], Box::new(Div)), vec!["(0, 0, 1)"]);
+	assert_eq!(run(vec![(3.0, 4.0, 0.0).into()], Box::new(Abs)), vec!["5"]);
+	assert_eq!(run(vec![1.0.into(), 0.0.into()], Box::new(Div)), vec!["DivByZero"]);
+	assert_eq!(run(vec![7.0.into(), 3.0.into()], Box::new(Modulo)), vec!["1"]);
+	assert_eq!(run(vec![1.0.into(), 2.0.into(), 3.0.into()], Box::new(ConstructVec)), vec!["(1, 2, 3)"]);
+}
+
+#[test]
+fn intervals_propagate() {
+	assert_eq!(run(vec![Interval::new(0.0, 3.5).into()], Box::new(Floor)), vec!["UNKOWN integer in [0, 3]"]);
+	assert_eq!(run(vec![Interval::new(0.0, 3.5).into(), 1.0.into()], Box::new(Add)), vec!["UNKOWN in [1, 4.5]"]);
+	assert_eq!(run(vec![Interval::new(-2.0, 1.0).into()], Box::new(Abs)), vec!["UNKOWN in [0, 2]"]);
+	assert_eq!(run(vec![None.into(), 0.0.into()], Box::new(Mul)), vec!["0"]);
+	assert_eq!(run(vec![None.into()], Box::new(Floor)), vec!["UNKOWN integer in [-inf, inf]"]);
+}
+
+#[test]
+fn division_by_interval_containing_zero() {
+	assert_eq!(run(vec![1.0.into(), Interval::new(1.0, 2.0).into()], Box::new(Div)), vec!["UNKOWN in [0.5, 1]"]);
+	assert_eq!(run(vec![1.0.into(), Interval::new(-1.0, 2.0).into()], Box::new(Div)), vec!["UNKOWN", "DivByZero"]);
+}
+
+#[test]
+fn index_in_range() {
+	let list: Iota = vec![1.0.into(), 2.0.into(), 3.0.into()].into();
+
+	assert_eq!(run(vec![list.clone(), Interval::integer(0.0, 2.0).into()], Box::new(Index)), vec!["UNKNOWN"]);
+	assert_eq!(run(vec![list.clone(), Interval::integer(1.0, 5.0).into()], Box::new(Index)), vec!["UNKNOWN", "Null"]);
+	assert_eq!(run(vec![list, Interval::integer(4.0, 5.0).into()], Box::new(Index)), vec!["Null"]);
+}
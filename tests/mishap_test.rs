@@ -0,0 +1,45 @@
+use hexedit::simulator::*;
+use hexedit::actions::logic::PushTrue;
+use hexedit::actions::maths::{Add, Div};
+use hexedit::actions::stack::Duplicate;
+
+fn single_state(stack_manager: &StackManager) -> &StackState {
+	assert_eq!(stack_manager.stacks().len(), 1);
+	stack_manager.stacks().iter().next().unwrap().as_ref().unwrap()
+}
+
+#[test]
+fn invalid_argument_is_replaced_with_garbage() {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![1.0.into()], None)));
+
+	stack_manager.apply_action(Box::new(PushTrue));
+	stack_manager.apply_action(Box::new(Add));
+
+	let state = single_state(&stack_manager);
+	assert_eq!(state.to_string(), "1\nGarbage");
+	assert_eq!(state.mishaps().len(), 1);
+	assert_eq!(state.mishaps()[0].pattern_index, 1);
+	assert_eq!(state.mishaps()[0].action, "add");
+	assert_eq!(state.mishaps()[0].to_string(), "mishap at pattern 1 (add): got a boolean at argument 0, expected a number or a vector");
+}
+
+#[test]
+fn missing_arguments_are_garbage() {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![1.0.into()], None)));
+
+	stack_manager.apply_action(Box::new(Add));
+
+	assert_eq!(single_state(&stack_manager).to_string(), "1\nGarbage");
+}
+
+#[test]
+fn casting_continues_after_mishap() {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![1.0.into(), 0.0.into()], None)));
+
+	stack_manager.apply_action(Box::new(Div));
+	stack_manager.apply_action(Box::new(Duplicate));
+
+	let state = single_state(&stack_manager);
+	assert_eq!(state.to_string(), "1\n0\nGarbage\nGarbage");
+	assert_eq!(state.mishaps().iter().map(|mishap| mishap.error).collect::<Vec<_>>(), vec![ActionError::DivByZero]);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "moreiotas")]
+
+use hexedit::addons::moreiotas::*;
+use hexedit::matrix::Matrix;
+use hexedit::serialization::*;
+use hexedit::simulator::*;
+
+mod common;
+
+fn cast(action: impl Action + 'static, stack: Vec<Iota>) -> Vec<String> { common::run(stack, Box::new(action)) }
+
+fn matrix(text: &str) -> Iota { text.parse::<Matrix>().unwrap().into() }
+
+#[test]
+fn strings() {
+	assert_eq!(cast(Concat, vec!["hex".to_string().into(), "edit".to_string().into()]), vec![r#""hexedit""#]);
+	assert_eq!(cast(Split, vec!["a,b,c".to_string().into(), ",".to_string().into()]), vec![r#"["a", "b", "c"]"#]);
+	assert_eq!(cast(Split, vec!["ab".to_string().into(), "".to_string().into()]), vec![r#"["a", "b"]"#]);
+	assert_eq!(cast(NumberToString, vec![2.5.into()]), vec![r#""2.5""#]);
+	assert_eq!(cast(Concat, vec![Iota::String(None), "edit".to_string().into()]), vec!["UNKNOWN string"]);
+}
+
+#[test]
+fn matrices() {
+	assert_eq!(cast(MatrixMul, vec![matrix("[1, 2; 3, 4]"), matrix("[1; 1]")]), vec!["Matrix[3; 7]"]);
+	assert_eq!(cast(MatrixMul, vec![matrix("[1, 2]"), matrix("[1, 2]")]), vec!["WrongDimensions { argument: 0 }"]);
+	assert_eq!(cast(Transpose, vec![matrix("[1, 2, 3]")]), vec!["Matrix[1; 2; 3]"]);
+	assert_eq!(cast(Determinant, vec![matrix("[1, 2; 3, 4]")]), vec!["-2"]);
+	assert_eq!(cast(Inverse, vec![matrix("[2, 0; 0, 4]")]), vec!["Matrix[0.5, 0; 0, 0.25]"]);
+	assert_eq!(cast(Inverse, vec![matrix("[1, 2; 2, 4]")]), vec!["DivByZero"]);
+	assert_eq!(cast(Determinant, vec![matrix("[1, 2]")]), vec!["WrongDimensions { argument: 0 }"]);
+}
+
+#[test]
+fn unknown_matrices() {
+	let unknown = Iota::Matrix(itertools::Either::Right((Some(2), None)));
+	assert_eq!(unknown.to_string(), "UNKNOWN 2×? matrix");
+	assert_eq!(cast(Transpose, vec![unknown.clone()]), vec!["UNKNOWN ?×2 matrix"]);
+	assert_eq!(cast(Determinant, vec![unknown]).len(), 2);
+}
+
+#[test]
+fn serialization() {
+	for iota in [Iota::from("a \"quote\"".to_string()), matrix("[1, 2; 3, 4.5]"), Iota::String(None)] {
+		assert!(from_json::<Iota>(&to_json(&iota)).unwrap() == iota);
+	}
+
+	let typed = r#"{"version": 1, "data": {"type": "text", "text": "Matrix[1, 0; 0, 1]"}}"#;
+	assert!(from_json::<Iota>(typed).unwrap() == matrix("[1, 0; 0, 1]"));
+	assert!("\"hello\\nworld\"".parse::<Iota>().unwrap() == Iota::from("hello\nworld".to_string()));
+	assert!("[1, \"a, b\"]".parse::<Iota>().unwrap().to_string() == r#"[1, "a, b"]"#);
+}
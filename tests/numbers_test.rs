@@ -0,0 +1,75 @@
+use hexedit::actions::logic::Equals;
+use hexedit::actions::maths::*;
+use hexedit::interval::Interval;
+use hexedit::numbers::*;
+use hexedit::simulator::*;
+use hexedit::world::{Blocks, RaycastHit};
+
+mod common;
+use common::run;
+
+#[test]
+fn tolerant_equality() {
+	assert!(tolerates(0.1 + 0.2, 0.3));
+	assert!(!tolerates(1.0, 1.001));
+	assert!(vec_tolerates((1.0, 2.0, 3.00001), (1.0, 2.0, 3.0)));
+	assert!(is_zero(-0.00005));
+
+	assert_eq!(run(vec![(0.1 + 0.2).into(), 0.3.into()], Box::new(Equals)), vec!["True"]);
+	assert_eq!(run(vec![(1.0, 0.0, 0.00002).into(), (1.0, 0.0, 0.0).into()], Box::new(Equals)), vec!["True"]);
+}
+
+#[test]
+fn rounding() {
+	// halves round up, as in Java
+	assert_eq!(round(2.5), 3.0);
+	assert_eq!(round(-2.5), -2.0);
+
+	// floor and ceiling don't snap to nearby whole numbers, even though the result compares equal to them
+	assert_eq!(run(vec![2.99999.into()], Box::new(Floor)), vec!["2"]);
+	assert_eq!(run(vec![(-0.5, 1.00001, 2.0).into()], Box::new(Ceil)), vec!["(-0, 2, 2)"]);
+
+	// the remainder has the sign of the dividend
+	assert_eq!(run(vec![(-7.0).into(), 3.0.into()], Box::new(Modulo)), vec!["-1"]);
+	assert_eq!(modulo(7.0, -3.0), 1.0);
+
+	// intervals are rounded the same way
+	assert_eq!(Interval::new(-0.5, 2.99999).floor(), Interval::integer(-1.0, 2.0));
+	assert_eq!(Interval::new(-0.5, 1.00001).ceil(), Interval::integer(-0.0, 2.0));
+	assert_eq!(Interval::point(-7.0).rem(&Interval::point(3.0)), Interval::point(-1.0));
+}
+
+#[test]
+fn normalisation() {
+	assert_eq!(normalize((3.0, 0.0, 4.0)), (0.6, 0.0, 0.8));
+	assert_eq!(normalize((0.00005, 0.0, 0.0)), (0.0, 0.0, 0.0));
+
+	// a direction too short to normalise doesn't hit anything
+	let blocks = Blocks::filled("stone");
+	assert_eq!(blocks.raycast((0.5, 0.5, 0.5), (0.00005, 0.0, 0.0), 32.0), RaycastHit::Miss);
+}
+
+#[test]
+fn game_format() {
+	assert_eq!(format_game(63.0), "63.00");
+	assert_eq!(format_game(2.0 / 3.0), "0.67");
+	// Java rounds halves away from zero where Rust would round them to even
+	assert_eq!(format_game(0.125), "0.13");
+	assert_eq!(format_game(-0.625), "-0.63");
+	assert_eq!(format_game(0.375), "0.38");
+	// and round the shortest digits of the double, not its exact value
+	assert_eq!(format_game(1.005), "1.01");
+	assert_eq!(format_game(2.675), "2.68");
+	assert_eq!(format_game(0.015), "0.02");
+	assert_eq!(format_game(9.995), "10.00");
+	assert_eq!(format_game(0.004), "0.00");
+	assert_eq!(format_game(-0.0), "-0.00");
+	assert_eq!(format_game(1.0e20), "100000000000000000000.00");
+	assert_eq!(format_game(f64::INFINITY), "Infinity");
+
+	let stack: Vec<Iota> = vec![63.0.into(), vec![Iota::from((1.0, 0.5, -0.0)), 0.1.into()].into()];
+	assert_eq!(StackState::new(stack.clone(), None).to_string(), "63\n[(1, 0.5, -0), 0.1]");
+
+	set_number_format(NumberFormat::Game);
+	assert_eq!(StackState::new(stack, None).to_string(), "63.00\n[(1.00, 0.50, -0.00), 0.10]");
+}
@@ -0,0 +1,152 @@
+use std::fs;
+use std::path::Path;
+
+use itertools::Either::Right;
+
+use hexedit::actions::registry::run_hex_from;
+use hexedit::hex_pattern::*;
+use hexedit::interval::Interval;
+use hexedit::serialization::*;
+use hexedit::simulator::*;
+use hexedit::symbolic::Expr;
+
+fn round_trip(iota: Iota) {
+	let json = to_json(&iota);
+	assert!(from_json::<Iota>(&json).unwrap() == iota, "{}", json);
+}
+
+#[test]
+fn round_trips_every_iota() {
+	let pattern = Iota::Pattern(HexPattern::hex_pattern(HexAbsoluteDir::East, vec![HexDir::A, HexDir::Q]).unwrap());
+	let entity = Iota::Entity(IotaEntity::new("Zombie")
+		.add_guaranteed(EntityType::Monster)
+		.remove_possible(EntityType::Animal)
+		.set_guaranteed_in_range(false).into());
+
+	for iota in [
+		pattern.clone(),
+		2.5.into(),
+		f64::INFINITY.into(),
+		Interval::integer(0.0, 3.0).into(),
+		None.into(),
+		true.into(),
+		Iota::Bool(None),
+		(1.0, -2.0, 3.5).into(),
+		Right(false).into(),
+		Iota::Widget,
+		Iota::Garbage,
+		vec![pattern, Iota::Unknown, vec![].into()].into(),
+		Iota::List(IotaList::new(Right(Some(4)))),
+		Iota::List(IotaList::new(Right(None))),
+		entity,
+		Iota::Unknown,
+		Iota::symbol("x", Interval::new(0.0, 1.0).into()),
+	] {
+		round_trip(iota);
+	}
+}
+
+#[test]
+fn writes_a_stable_shape() {
+	let json = to_json(&vec![Iota::from(2.0), Iota::from(Interval::new(0.0, f64::INFINITY))]);
+
+	assert_eq!(json, r#"{
+  "version": 1,
+  "data": [
+    {
+      "type": "double",
+      "value": 2.0
+    },
+    {
+      "type": "unknown_double",
+      "bounds": {
+        "lower": 0.0,
+        "upper": "inf",
+        "integer": false
+      }
+    }
+  ]
+}"#);
+}
+
+#[test]
+fn round_trips_states_and_errors() {
+	let mut state = StackState::new(vec![Iota::Garbage], Some(1.0.into()));
+	state.add_cost(&Interval::new(5.0, 10.0));
+	let state = state.mishap(Mishap {
+		pattern_index: 3,
+		action: "impulse".to_string(),
+		error: ActionError::InvalidType { argument: 1, expected: &[IotaType::Entity], found: IotaType::Bool }
+	});
+
+	let holder = StackHolder::new(vec![Ok(state), Err(ActionError::TooManyPossibilities)]);
+	let read: StackHolder = from_json(&to_json(&holder)).unwrap();
+
+	assert!(read.iter().eq(holder.iter()));
+}
+
+#[test]
+fn reads_only_expected_type_lists() {
+	let error = ActionError::InvalidType { argument: 0, expected: &[IotaType::Double, IotaType::Vec], found: IotaType::Bool };
+	let json = to_json(&error);
+
+	assert_eq!(from_json::<ActionError>(&json).unwrap(), error);
+	assert!(from_json::<ActionError>(&json.replace("\"vec\"", "\"entity\"")).is_err());
+}
+
+#[test]
+fn round_trips_expressions() {
+	let expr = Expr::apply(hexedit::symbolic::Op::Call("impulse".to_string()), vec![Expr::var("x"), std::rc::Rc::new(Expr::Const(f64::NAN))]);
+	let read: Expr = from_json(&to_json(&*expr)).unwrap();
+
+	assert_eq!(read.to_string(), expr.to_string());
+}
+
+#[test]
+fn rejects_other_versions() {
+	let json = to_json(&Iota::Garbage).replace("\"version\": 1", "\"version\": 2");
+
+	assert!(matches!(from_json::<Iota>(&json), Err(FormatError::UnsupportedVersion(2))));
+	assert!(matches!(from_json::<Iota>("{\"version\": 1, \"data\": {\"type\": \"teapot\"}}"), Err(FormatError::Json(_))));
+	assert!(from_json::<IotaList>(&to_json(&Iota::Garbage)).is_err());
+}
+
+/// A hex, the stack it is cast from, and every stack that results, kept on disk to catch changes in behaviour.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Fixture {
+	hex: Vec<HexPattern>,
+	input: StackState,
+	result: StackHolder
+}
+
+/// Casts the hex in each fixture in tests/fixtures and checks the result matches the one stored. Run with
+/// UPDATE_FIXTURES=1 to store the current results instead.
+#[test]
+fn golden_fixtures() {
+	let update = std::env::var_os("UPDATE_FIXTURES").is_some();
+	let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+	let mut checked = 0;
+
+	for entry in fs::read_dir(directory).unwrap() {
+		let path = entry.unwrap().path();
+		if path.extension().map_or(true, |extension| extension != "json") {
+			continue
+		}
+
+		let json = fs::read_to_string(&path).unwrap();
+		let mut fixture: Fixture = from_json(&json).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+		let result = run_hex_from(&fixture.hex, fixture.input.clone()).unwrap();
+
+		if update {
+			fixture.result = StackHolder::new(result.stacks().iter().cloned().collect());
+			fs::write(&path, to_json(&fixture) + "\n").unwrap();
+		} else {
+			assert!(result.stacks().iter().eq(fixture.result.iter()), "{} has a different result", path.display());
+			assert_eq!(to_json(&fixture) + "\n", json, "{} isn't in the current format", path.display());
+		}
+
+		checked += 1;
+	}
+
+	assert!(checked > 0);
+}
@@ -1,4 +1,5 @@
 use hexedit::simulator::*;
+use hexedit::interval::Interval;
 use hexedit::actions::maths::*;
 
 #[test]
@@ -10,4 +11,82 @@ fn adding() {
 	stack_manager.apply_action(Box::new(Add));
 
 	
-}
\ No newline at end of file
+}
+
+#[test]
+fn identical_branches_are_merged() {
+	let stack = StackState::new(vec![Iota::Bool(None), 1.0.into(), 1.0.into()], None);
+	let mut stack_manager = StackManager::new(StackHolder::single_state(stack));
+
+	stack_manager.apply_action(Box::new(hexedit::actions::logic::If));
+
+	assert_eq!(stack_manager.stacks().len(), 1);
+}
+
+#[test]
+fn unknown_branches_are_merged() {
+	let mut stack_holder = StackHolder::new(vec![
+		Ok(StackState::new(vec![Interval::integer(0.0, 3.0).into()], None)),
+		Ok(StackState::new(vec![2.0.into()], None)),
+		Ok(StackState::new(vec![Iota::Widget], None)),
+		Ok(StackState::new(vec![5.0.into()], None)),
+	]);
+
+	stack_holder.limit(DEFAULT_BRANCH_CAP);
+
+	let stacks: Vec<String> = stack_holder.iter().map(|result| result.as_ref().unwrap().to_string()).collect();
+	assert_eq!(stacks, vec!["UNKOWN integer in [0, 3]", "Null", "5"]);
+	assert!(stack_holder.iter().all(|result| !result.as_ref().unwrap().is_approximate()));
+
+	let mut stack_holder = StackHolder::new(vec![
+		Ok(StackState::new(vec![Interval::new(0.0, 1.0).into(), 1.0.into()], None)),
+		Ok(StackState::new(vec![Interval::new(2.0, 3.0).into(), 1.0.into()], None)),
+	]);
+
+	stack_holder.limit(DEFAULT_BRANCH_CAP);
+
+	let state = stack_holder.iter().next().unwrap().as_ref().unwrap();
+	assert_eq!(stack_holder.len(), 1);
+	assert_eq!(state.to_string(), "UNKOWN in [0, 3]\n1");
+	assert!(state.is_approximate());
+}
+
+#[test]
+fn branches_are_widened_over_cap() {
+	// every stack has a different length, so they can't be widened into one another.
+	let mut stack_holder = StackHolder::new((0..10).map(|n| Ok(StackState::new(vec![1.0.into(); n], None))).collect());
+
+	stack_holder.limit(4);
+
+	assert_eq!(stack_holder.len(), 4);
+	assert!(matches!(stack_holder.iter().last(), Some(Err(ActionError::TooManyPossibilities))));
+
+	let stack = StackState::new(vec![Iota::Bool(None), 1.0.into(), 2.0.into()], None);
+	let mut stack_manager = StackManager::new(StackHolder::single_state(stack));
+	stack_manager.set_branch_cap(1);
+
+	stack_manager.apply_action(Box::new(hexedit::actions::logic::If));
+
+	let state = stack_manager.stacks().iter().next().unwrap().as_ref().unwrap();
+	assert_eq!(stack_manager.stacks().len(), 1);
+	assert_eq!(state.to_string(), "UNKOWN integer in [1, 2]");
+	assert!(state.is_approximate());
+}
+
+#[test]
+fn gemini_follows_the_branch_cap() {
+	let stack = StackState::new(vec![1.0.into(), Interval::integer(0.0, 9.0).into()], None);
+	let mut stack_manager = StackManager::new(StackHolder::single_state(stack.clone()));
+
+	stack_manager.apply_action(Box::new(hexedit::actions::stack::DuplicateN));
+
+	assert_eq!(stack_manager.stacks().len(), 10);
+
+	let mut stack_manager = StackManager::new(StackHolder::single_state(stack));
+	stack_manager.set_branch_cap(4);
+
+	stack_manager.apply_action(Box::new(hexedit::actions::stack::DuplicateN));
+
+	assert_eq!(stack_manager.stacks().len(), 1);
+	assert!(matches!(stack_manager.stacks().iter().next(), Some(Err(ActionError::TooManyPossibilities))));
+}
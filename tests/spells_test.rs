@@ -0,0 +1,95 @@
+use itertools::Either::Right;
+
+use hexedit::hex_pattern::*;
+use hexedit::interval::Interval;
+use hexedit::media::DUST;
+use hexedit::simulator::*;
+use hexedit::actions::registry::run_hex;
+use hexedit::actions::spells::*;
+
+fn cost_of(stack: Vec<Iota>, actions: Vec<Box<dyn Action>>) -> Option<Interval> {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(stack, None)));
+	for action in actions {
+		stack_manager.apply_action(action);
+	}
+	stack_manager.stacks().cost()
+}
+
+#[test]
+fn explosion_cost_depends_on_strength() {
+	let stack = vec![(0.0, 0.0, 0.0).into(), 2.0.into(), (0.0, 0.0, 0.0).into(), Interval::new(1.0, 20.0).into()];
+
+	assert_eq!(cost_of(stack.clone(), vec![Box::new(Explode { fire: true })]), Some(Interval::new(4.0 * DUST, 31.0 * DUST)));
+	assert_eq!(
+		cost_of(stack, vec![Box::new(Explode { fire: true }), Box::new(Explode { fire: false })]),
+		Some(Interval::new(10.125 * DUST, 37.125 * DUST))
+	);
+}
+
+#[test]
+fn mishaps_are_free() {
+	assert_eq!(cost_of(vec![true.into(), 2.0.into()], vec![Box::new(Explode { fire: false })]), Some(Interval::point(0.0)));
+}
+
+#[test]
+fn hex_stops_at_unknown_pattern() {
+	let push_true = HexPattern::hex_pattern(HexAbsoluteDir::East, vec![HexDir::A, HexDir::Q, HexDir::A, HexDir::E]).unwrap();
+	let unknown = HexPattern::hex_pattern(HexAbsoluteDir::East, vec![HexDir::W, HexDir::W, HexDir::W]).unwrap();
+
+	let stack_manager = run_hex(&[push_true.clone()]).unwrap();
+	assert_eq!(stack_manager.stacks().iter().next().unwrap().as_ref().unwrap().to_string(), "True");
+	assert!(matches!(run_hex(&[push_true, unknown]), Err(1)));
+}
+
+fn effects_of(stack: Vec<Iota>, actions: Vec<Box<dyn Action>>) -> Vec<Vec<String>> {
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(stack, None)));
+	for action in actions {
+		stack_manager.apply_action(action);
+	}
+
+	stack_manager.stacks().iter()
+		.map(|result| result.as_ref().unwrap().effects().iter().map(SpellEffect::to_string).collect())
+		.collect()
+}
+
+fn caster() -> Iota { Iota::Entity(IotaEntity::new("Caster").set_guaranteed_in_range(true).into()) }
+
+#[test]
+fn spells_record_their_effects() {
+	let stack = vec![caster(), (0.0, 2.0, 0.0).into(), (0.0, 64.0, 0.0).into(), 3.0.into(), caster(), 4.0.into()];
+
+	assert_eq!(
+		effects_of(stack, vec![Box::new(Blink), Box::new(Explode { fire: true }), Box::new(Impulse)]),
+		vec![vec![
+			"pattern 0: Blink on Caster by 4 blocks (10 dust)",
+			"pattern 1: Fireball at (0, 64, 0), strength 3 (10 dust)",
+			"pattern 2: Impulse on Caster by (0, 2, 0) (4 dust)",
+		]]
+	);
+	assert_eq!(
+		effects_of(vec![Iota::Vec(Right(false)), (0.5, 64.0, 0.5).into()], vec![Box::new(CreateLava), Box::new(BreakBlock)]),
+		vec![vec![
+			"pattern 0: Create Lava at (0.5, 64, 0.5) (10 dust)",
+			"pattern 1: Break Block at (UNKNOWN, UNKNOWN, UNKNOWN ; guaranteed in range: false) (0.12 dust)",
+		]]
+	);
+}
+
+#[test]
+fn mishaps_have_no_effect() {
+	assert_eq!(effects_of(vec![true.into(), 2.0.into()], vec![Box::new(Explode { fire: false })]), vec![Vec::<String>::new()]);
+	assert_eq!(effects_of(vec![], vec![Box::new(ConjureBlock { light: true })]), vec![Vec::<String>::new()]);
+}
+
+#[test]
+fn effects_keep_branches_apart() {
+	let explode = |strength: f64| {
+		let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![(0.0, 0.0, 0.0).into(), strength.into()], None)));
+		stack_manager.apply_action(Box::new(Explode { fire: false }));
+		stack_manager.stacks().iter().next().unwrap().as_ref().unwrap().clone()
+	};
+
+	// without the effects, the states would be the same apart from their costs, and would be joined
+	assert!(explode(1.0).join(&explode(2.0), false).is_none());
+	assert!(explode(1.0) == explode(1.0));
+}
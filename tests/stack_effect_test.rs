@@ -0,0 +1,34 @@
+use hexedit::stack_effect::*;
+
+mod common;
+use common::patterns;
+
+fn effect(signatures: &[&str]) -> Result<String, EffectError> {
+	let patterns = patterns(signatures);
+	infer(&patterns, 0..patterns.len()).map(|effect| effect.to_string())
+}
+
+#[test]
+fn infers_types_through_shuffles() {
+	// Jester's Gambit, Impulse, True Reflection
+	assert_eq!(effect(&["aawdd", "awqqqwaqw", "aqae"]), Ok("takes (vector, entity), returns (boolean)".to_string()));
+	// Gemini Decomposition, Maximus Distillation
+	assert_eq!(effect(&["aadaa", "e"]), Ok("takes (number), returns (boolean)".to_string()));
+	assert_eq!(effect(&["waaw"]), Ok("takes (number or vector, number or vector), returns (number or vector)".to_string()));
+}
+
+#[test]
+fn reports_dynamic_and_mismatched_patterns() {
+	assert_eq!(effect(&["aqae", "aadaadaa"]), Err(EffectError::Dynamic { pattern_index: 1 }));
+	assert!(matches!(effect(&["aqae", "waaw"]), Err(EffectError::Mismatch { pattern_index: 1, argument: 0, .. })));
+}
+
+#[test]
+fn finds_introspection_blocks() {
+	let patterns = patterns(&["qqq", "aqae", "qqq", "waaw", "eee", "eee", "aadaa"]);
+
+	assert_eq!(introspection_blocks(&patterns), Ok(vec![1..5, 3..4]));
+	assert_eq!(infer(&patterns, 1..5).unwrap().to_string(), "takes (), returns (boolean, list)");
+	assert_eq!(infer(&patterns, 0..7).unwrap().to_string(), "takes (), returns (list, list)");
+	assert_eq!(introspection_blocks(&patterns[..4]), Err(EffectError::UnbalancedBlock { pattern_index: 0 }));
+}
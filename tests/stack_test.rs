@@ -0,0 +1,56 @@
+use hexedit::simulator::*;
+use hexedit::actions::stack::*;
+
+mod common;
+use common::run;
+
+fn abc() -> Vec<Iota> { vec![1.0.into(), 2.0.into(), 3.0.into()] }
+
+#[test]
+fn fixed_shuffles() {
+	assert_eq!(run(abc(), Box::new(Duplicate)), vec!["1\n2\n3\n3"]);
+	assert_eq!(run(abc(), Box::new(TwoDup)), vec!["1\n2\n3\n2\n3"]);
+	assert_eq!(run(abc(), Box::new(Swap)), vec!["1\n3\n2"]);
+	assert_eq!(run(abc(), Box::new(Rotate)), vec!["2\n3\n1"]);
+	assert_eq!(run(abc(), Box::new(RotateReverse)), vec!["3\n1\n2"]);
+	assert_eq!(run(abc(), Box::new(Over)), vec!["1\n2\n3\n2"]);
+	assert_eq!(run(abc(), Box::new(Tuck)), vec!["1\n3\n2\n3"]);
+	assert_eq!(run(vec![], Box::new(Swap)), vec!["StackTooSmall { expected: 2, found: 0 }"]);
+}
+
+#[test]
+fn duplicate_n_and_stack_size() {
+	assert_eq!(run(vec![1.0.into(), 3.0.into()], Box::new(DuplicateN)), vec!["1\n1\n1"]);
+	assert_eq!(run(vec![1.0.into(), 0.0.into()], Box::new(DuplicateN)), vec![""]);
+	assert_eq!(run(vec![1.0.into(), 1.5.into()], Box::new(DuplicateN)), vec!["OutOfBounds { argument: 0 }"]);
+	assert_eq!(run(vec![1.0.into(), 1e9.into()], Box::new(DuplicateN)), vec!["Indeterminate"]);
+	assert_ne!(run(vec![1.0.into(), (MAX_STACK_LEN as f64).into()], Box::new(DuplicateN)), vec!["Indeterminate"]);
+	assert_eq!(run(abc(), Box::new(StackSize)), vec!["1\n2\n3\n3"]);
+}
+
+#[test]
+fn fisherman() {
+	let mut stack = abc();
+	stack.push(2.0.into());
+	assert_eq!(run(stack.clone(), Box::new(Fisherman { copy: false })), vec!["2\n3\n1"]);
+	assert_eq!(run(stack, Box::new(Fisherman { copy: true })), vec!["1\n2\n3\n1"]);
+
+	let mut stack = abc();
+	stack.push((-1.0).into());
+	assert_eq!(run(stack.clone(), Box::new(Fisherman { copy: false })), vec!["1\n3\n2"]);
+	assert_eq!(run(stack, Box::new(Fisherman { copy: true })), vec!["OutOfBounds { argument: 0 }"]);
+
+	let mut stack = vec![1.0.into(), 2.0.into()];
+	stack.push(None.into());
+	assert_eq!(run(stack, Box::new(Fisherman { copy: true })), vec!["1\n2\n2", "1\n2\n1", "OutOfBounds { argument: 0 }"]);
+
+	assert_eq!(Fisherman { copy: false }.name(), "fisherman");
+	assert_eq!(Fisherman { copy: true }.name(), "fisherman_copy");
+}
+
+#[test]
+fn mask() {
+	assert_eq!(run(abc(), Box::new(Mask(vec![false, true]))), vec!["1\n3"]);
+	assert_eq!(run(abc(), Box::new(Mask(vec![true, false, true]))), vec!["1\n3"]);
+	assert_eq!(run(abc(), Box::new(Mask(vec![true; 4]))), vec!["StackTooSmall { expected: 4, found: 3 }"]);
+}
@@ -0,0 +1,66 @@
+use hexedit::interval::Interval;
+use hexedit::simulator::*;
+use hexedit::actions::{maths::*, stack::*, logic::*};
+
+fn symbolic_stack(stack: Vec<Iota>) -> StackManager {
+	let mut state = StackState::new(stack, None);
+	state.set_symbolic(true);
+	StackManager::new(StackHolder::single_state(state))
+}
+
+fn displayed(stack_manager: &StackManager) -> Vec<String> {
+	stack_manager.stacks().iter().map(|result| match result {
+		Ok(state) => state.to_string(),
+		Err(action_error) => format!("{action_error:?}"),
+	}).collect()
+}
+
+#[test]
+fn expressions_track_maths() {
+	let x = Iota::symbol("x", Interval::new(0.0, 10.0).into());
+	let mut stack_manager = symbolic_stack(vec![1.0.into(), x, 2.0.into()]);
+
+	stack_manager.apply_action(Box::new(Div));
+	stack_manager.apply_action(Box::new(Floor));
+	stack_manager.apply_action(Box::new(Add));
+
+	assert_eq!(displayed(&stack_manager), vec!["1 + floor(x / 2)"]);
+}
+
+#[test]
+fn shuffles_keep_expressions() {
+	let x = Iota::symbol("x", None.into());
+	let y = Iota::symbol("y", None.into());
+	let mut stack_manager = symbolic_stack(vec![x, y]);
+
+	stack_manager.apply_action(Box::new(Swap));
+	stack_manager.apply_action(Box::new(Over));
+	stack_manager.apply_action(Box::new(Sub));
+
+	assert_eq!(displayed(&stack_manager), vec!["y\nx - y"]);
+}
+
+#[test]
+fn vectors_simplify() {
+	let x = Iota::symbol("x", None.into());
+	let mut stack_manager = symbolic_stack(vec![x.clone(), 0.0.into(), 1.0.into()]);
+
+	stack_manager.apply_action(Box::new(ConstructVec));
+	stack_manager.apply_action(Box::new(DeconstructVec));
+
+	assert_eq!(displayed(&stack_manager), vec!["x\n0\n1"]);
+
+	let mut stack_manager = symbolic_stack(vec![x.clone(), x]);
+	stack_manager.apply_action(Box::new(Equals));
+	assert_eq!(displayed(&stack_manager), vec!["True"]);
+}
+
+#[test]
+fn symbols_are_plain_without_symbolic_mode() {
+	let x = Iota::symbol("x", None.into());
+	let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![x, 2.0.into()], None)));
+
+	stack_manager.apply_action(Box::new(Mul));
+
+	assert_eq!(displayed(&stack_manager), vec!["UNKOWN"]);
+}
@@ -0,0 +1,65 @@
+use hexedit::simulator::*;
+use hexedit::type_check::*;
+
+mod common;
+use common::patterns;
+
+#[test]
+fn flags_too_small_a_stack() {
+	// True Reflection, Additive Distillation
+	let diagnostics = check(&patterns(&["aqae", "waaw"]));
+
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].pattern_index, 1);
+	assert!(matches!(diagnostics[0].error, ActionError::StackTooSmall { expected: 2, found: 1 }));
+}
+
+#[test]
+fn flags_invalid_types() {
+	// True Reflection, True Reflection, Impulse
+	let diagnostics = check(&patterns(&["aqae", "aqae", "awqqqwaqw"]));
+
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].pattern_index, 2);
+	assert!(matches!(
+		diagnostics[0].error,
+		ActionError::InvalidType { argument: 1, expected: [IotaType::Entity], found: IotaType::Bool }
+	));
+	assert_eq!(diagnostics[0].to_string(), "mishap at pattern 2 (impulse): got a boolean at argument 1, expected an entity");
+}
+
+#[test]
+fn keeps_checking_after_a_mishap() {
+	// Additive Distillation, Additive Distillation
+	let diagnostics = check(&patterns(&["waaw", "waaw"]));
+
+	// the second only mishaps on the Garbage the first left behind
+	assert_eq!(diagnostics.iter().map(|mishap| mishap.pattern_index).collect::<Vec<_>>(), vec![0]);
+
+	// Additive Distillation, True Reflection, True Reflection, Impulse
+	let diagnostics = check(&patterns(&["waaw", "aqae", "aqae", "awqqqwaqw"]));
+
+	assert_eq!(diagnostics.iter().map(|mishap| mishap.pattern_index).collect::<Vec<_>>(), vec![0, 3]);
+}
+
+#[test]
+fn accepts_well_typed_hexes() {
+	// True Reflection, Gemini Decomposition, Jester's Gambit
+	assert!(check(&patterns(&["aqae", "aadaa", "aawdd"])).is_empty());
+}
+
+#[test]
+fn stops_at_unknown_patterns() {
+	// an unknown pattern, then Additive Distillation
+	assert!(check(&patterns(&["wwwwww", "waaw"])).is_empty());
+}
+
+#[test]
+fn casts_frames_before_checking_on() {
+	// Introspection, True Reflection, True Reflection, Retrospection, Hermes' Gambit, Equality Distillation
+	assert!(check(&patterns(&["qqq", "aqae", "aqae", "eee", "deaqq", "ad"])).is_empty());
+	// the same, with Additive Distillation after Hermes' Gambit instead
+	let diagnostics = check(&patterns(&["qqq", "aqae", "aqae", "eee", "deaqq", "waaw"]));
+	assert_eq!(diagnostics.len(), 1);
+	assert_eq!(diagnostics[0].pattern_index, 5);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use hexedit::actions::registry::{pattern_name, run_hex_from};
+use hexedit::actions::spells::CreateWater;
+use hexedit::actions::stack::Fisherman;
+use hexedit::interval::Interval;
+use hexedit::media::DUST;
+use hexedit::serialization::{from_json, to_json};
+use hexedit::simulator::*;
+use hexedit::type_check::check;
+use hexedit::version::*;
+
+use common::{patterns, run};
+
+/// A profile for a server that has patched its Hex Casting, as a team might load from a file.
+fn patched() -> Profile {
+	Profile {
+		version: GameVersion::V0_10,
+		missing_patterns: vec![PatternChange { signature: "qwaqde".to_string(), source: "server patch notes".to_string() }],
+		costs: vec![CostChange { action: "create_water".to_string(), cost: 2.0 * DUST, source: "server patch notes".to_string() }],
+		positive_fisherman: Some("server patch notes".to_string())
+	}
+}
+
+#[test]
+fn versions() {
+	assert_eq!(game_version(), GameVersion::V0_11);
+	assert_eq!(GameVersion::V0_10.to_string(), "0.10.x");
+	assert_eq!(to_json(&GameVersion::V0_10), "{\n  \"version\": 1,\n  \"data\": \"0.10\"\n}");
+	assert_eq!(from_json::<GameVersion>(&to_json(&GameVersion::V0_11)).unwrap(), GameVersion::V0_11);
+
+	// Iris' Gambit and Thanatos' Reflection were added in 0.11
+	let (older, latest) = (Profile::builtin(GameVersion::V0_10), Profile::builtin(GameVersion::V0_11));
+	assert!(!older.has_pattern("qwaqde") && !older.has_pattern("qqaed") && !older.negative_fisherman());
+	assert!(latest.has_pattern("qwaqde") && latest.has_pattern("qqaed") && latest.negative_fisherman());
+	assert!(older.missing_patterns.iter().all(|missing| !missing.source.is_empty()));
+	set_game_version(GameVersion::V0_10);
+	assert_eq!(*profile(), Profile::builtin(GameVersion::V0_10));
+
+	assert_eq!(from_json::<Profile>(&to_json(&patched())).unwrap(), patched());
+	let written = from_json::<Profile>(r#"{"version": 1, "data": {"version": "0.10", "costs": [{"action": "create_water", "cost": 20000, "source": "server patch notes"}]}}"#).unwrap();
+	assert_eq!(written.costs, patched().costs);
+	assert!(written.has_pattern("qwaqde") && written.negative_fisherman());
+}
+
+#[test]
+fn missing_patterns_mishap() {
+	// Iris' Gambit is known by name, but can't be cast
+	assert_eq!(pattern_name(&patterns(&["qwaqde"])[0]).as_deref(), Some("iris_gambit"));
+	assert_eq!(run_hex_from(&patterns(&["aqae", "qwaqde"]), StackState::default()).err(), Some(1));
+
+	set_game_version(GameVersion::V0_10);
+	let stack_manager = run_hex_from(&patterns(&["aqae", "qwaqde"]), StackState::default()).unwrap();
+	let state = stack_manager.stacks().iter().next().unwrap().as_ref().unwrap();
+	assert_eq!(state.to_string(), "True\nGarbage");
+	assert_eq!(state.mishaps()[0].to_string(), "mishap at pattern 1 (iris_gambit): the pattern doesn't exist in Hex Casting 0.10.x");
+
+	// True Reflection, Iris' Gambit, Additive Distillation
+	let diagnostics = check(&patterns(&["aqae", "qwaqde", "waaw"]));
+	assert_eq!(diagnostics.len(), 2);
+	assert!(diagnostics[0].error == ActionError::InvalidPattern { version: GameVersion::V0_10 });
+	assert_eq!(diagnostics[1].pattern_index, 2);
+}
+
+#[test]
+fn costs_depend_on_the_profile() {
+	let cost = || {
+		let mut stack_manager = StackManager::new(StackHolder::single_state(StackState::new(vec![(0.0, 0.0, 0.0).into()], None)));
+		stack_manager.apply_action(Box::new(CreateWater));
+		stack_manager.stacks().cost()
+	};
+
+	assert_eq!(cost(), Some(Interval::point(DUST)));
+	set_profile(patched());
+	assert_eq!(cost(), Some(Interval::point(2.0 * DUST)));
+}
+
+#[test]
+fn negative_fisherman() {
+	let stack: Vec<Iota> = vec![1.0.into(), 2.0.into(), 3.0.into(), (-1.0).into()];
+	assert_eq!(run(stack.clone(), Box::new(Fisherman { copy: false })), vec!["1\n3\n2"]);
+
+	set_game_version(GameVersion::V0_10);
+	assert_eq!(run(stack, Box::new(Fisherman { copy: false })), vec!["OutOfBounds { argument: 0 }"]);
+}
+
+#[test]
+fn builtin_versions_differ() {
+	// Fisherman's Gambit, with -1 on the stack
+	let cast = |version| {
+		set_game_version(version);
+		let start = StackState::new(vec![1.0.into(), 2.0.into(), 3.0.into(), (-1.0).into()], None);
+		let stack_manager = run_hex_from(&patterns(&["ddad"]), start).unwrap();
+		common::describe_all(stack_manager.stacks())
+	};
+
+	assert_eq!(cast(GameVersion::V0_11), vec!["1\n3\n2"]);
+	assert_eq!(cast(GameVersion::V0_10), vec!["OutOfBounds { argument: 0 }"]);
+
+	// True Reflection, Iris' Gambit
+	let check_in = |version| {
+		set_game_version(version);
+		check(&patterns(&["aqae", "qwaqde"])).len()
+	};
+
+	assert_eq!(check_in(GameVersion::V0_11), 0);
+	assert_eq!(check_in(GameVersion::V0_10), 1);
+}